@@ -0,0 +1,523 @@
+#![allow(clippy::field_reassign_with_default)] // This is triggered in `#[derive(JsonSchema)]`
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Coin, CosmosMsg, QuerierWrapper, QueryRequest, StdError, StdResult,
+    Uint256, WasmMsg, WasmQuery,
+};
+
+use crate::{
+    msg::{space_pad, ExecuteMsg, QueryAnswer, QueryMsg, SendAction, TransferAction},
+    state::{
+        expiration::Expiration,
+        state_structs::{BatchBalance, SendMode, TokenAmount, TokenIdBalance},
+        RESPONSE_BLOCK_SIZE,
+    },
+};
+
+/////////////////////////////////////////////////////////////////////////////////
+// Contract handle
+/////////////////////////////////////////////////////////////////////////////////
+
+/// a deployed SNIP1155 contract, used by other contracts to send it any `ExecuteMsg` or `QueryMsg`.
+/// Common messages and queries have typed constructors, and queries return their concrete answer.
+/// Any other variant is sent with the generic `execute` and `query`. Build with
+/// `--features library` to depend on this crate without its entry points
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Snip1155Contract {
+    pub address: Addr,
+    pub code_hash: String,
+}
+
+impl Snip1155Contract {
+    pub fn new(address: Addr, code_hash: String) -> Self {
+        Self { address, code_hash }
+    }
+
+    /// creates a cosmos_msg executing `msg` on this contract
+    pub fn execute(&self, msg: ExecuteMsg) -> StdResult<CosmosMsg> {
+        msg.into_cosmos_msg(self.code_hash.clone(), self.address.clone())
+    }
+
+    /// creates a cosmos_msg executing `msg` on this contract, sending `funds` with it
+    pub fn execute_with_funds(&self, msg: ExecuteMsg, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(msg
+            .into_wasm_msg(self.code_hash.clone(), self.address.clone(), funds)?
+            .into())
+    }
+
+    /// sends `msg` to this contract as a query
+    pub fn query(&self, querier: QuerierWrapper, msg: QueryMsg) -> StdResult<QueryAnswer> {
+        msg.query(querier, self.code_hash.clone(), self.address.clone())
+    }
+
+    /// creates a `Deposit` message, minting shares of a token_id backed by a native coin in
+    /// exchange for `funds`
+    pub fn deposit(
+        &self,
+        token_id: String,
+        memo: Option<String>,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        let msg = ExecuteMsg::Deposit {
+            token_id,
+            memo,
+            padding: None,
+        };
+        self.execute_with_funds(msg, funds)
+    }
+
+    /// creates a `Redeem` message, burning shares of a backed token_id for its collateral
+    pub fn redeem(
+        &self,
+        token_id: String,
+        amount: Uint256,
+        memo: Option<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.execute(ExecuteMsg::Redeem {
+            token_id,
+            amount,
+            memo,
+            padding: None,
+        })
+    }
+
+    /// creates a `CreateSwapOffer` message. The swap_id is in the data of the response
+    pub fn create_swap_offer(
+        &self,
+        give: Vec<TokenAmount>,
+        want: Vec<TokenAmount>,
+        counterparty: Option<Addr>,
+        expiry: Option<Expiration>,
+        public: bool,
+    ) -> StdResult<CosmosMsg> {
+        self.execute(ExecuteMsg::CreateSwapOffer {
+            give,
+            want,
+            counterparty,
+            expiry,
+            public: Some(public),
+            padding: None,
+        })
+    }
+
+    /// creates an `AcceptSwap` message
+    pub fn accept_swap(&self, swap_id: u64) -> StdResult<CosmosMsg> {
+        self.execute(ExecuteMsg::AcceptSwap {
+            swap_id,
+            padding: None,
+        })
+    }
+
+    /// creates a `CancelSwap` message
+    pub fn cancel_swap(&self, swap_id: u64) -> StdResult<CosmosMsg> {
+        self.execute(ExecuteMsg::CancelSwap {
+            swap_id,
+            padding: None,
+        })
+    }
+
+    /// creates a `ClaimRewards` message
+    pub fn claim_rewards(&self, token_id: String) -> StdResult<CosmosMsg> {
+        self.execute(ExecuteMsg::ClaimRewards {
+            token_id,
+            padding: None,
+        })
+    }
+
+    /// queries the balance of `owner`, with the viewing key of `viewer`
+    pub fn balance(
+        &self,
+        querier: QuerierWrapper,
+        owner: Addr,
+        viewer: Addr,
+        key: String,
+        token_id: String,
+    ) -> StdResult<Uint256> {
+        let msg = QueryMsg::Balance {
+            owner,
+            viewer,
+            key,
+            token_id,
+        };
+        match self.query(querier, msg)? {
+            QueryAnswer::Balance { amount } => Ok(amount),
+            answer => Err(unexpected_answer(answer)),
+        }
+    }
+
+    /// queries the balances of every (owner, token_id) pair, with the viewing key of `viewer`
+    pub fn balance_of_batch(
+        &self,
+        querier: QuerierWrapper,
+        viewer: Addr,
+        key: String,
+        owners: Vec<Addr>,
+        token_ids: Vec<String>,
+    ) -> StdResult<Vec<BatchBalance>> {
+        let msg = QueryMsg::BalanceOfBatch {
+            viewer,
+            key,
+            owners,
+            token_ids,
+        };
+        match self.query(querier, msg)? {
+            QueryAnswer::BalanceOfBatch { balances } => Ok(balances),
+            answer => Err(unexpected_answer(answer)),
+        }
+    }
+
+    /// queries the balance of `owner` at the end of block `height`, with the viewing key of
+    /// `viewer`
+    pub fn balance_at(
+        &self,
+        querier: QuerierWrapper,
+        owner: Addr,
+        viewer: Addr,
+        key: String,
+        token_id: String,
+        height: u64,
+    ) -> StdResult<Uint256> {
+        let msg = QueryMsg::BalanceAt {
+            owner,
+            viewer,
+            key,
+            token_id,
+            height,
+        };
+        match self.query(querier, msg)? {
+            QueryAnswer::BalanceAt { amount, .. } => Ok(amount),
+            answer => Err(unexpected_answer(answer)),
+        }
+    }
+
+    /// queries a page of the holders of a token_id with `public_holders`
+    pub fn holders(
+        &self,
+        querier: QuerierWrapper,
+        token_id: String,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    ) -> StdResult<HoldersPage> {
+        let msg = QueryMsg::Holders {
+            token_id,
+            start_after,
+            limit,
+        };
+        match self.query(querier, msg)? {
+            QueryAnswer::Holders {
+                holders,
+                holder_count,
+                next_start_after,
+            } => Ok(HoldersPage {
+                holders,
+                holder_count,
+                next_start_after,
+            }),
+            answer => Err(unexpected_answer(answer)),
+        }
+    }
+}
+
+/// a page of the holders of a token_id, returned by [Snip1155Contract::holders]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HoldersPage {
+    pub holders: Vec<TokenIdBalance>,
+    pub holder_count: u32,
+    /// `start_after` of the next page, or `None` if this is the last page
+    pub next_start_after: Option<Addr>,
+}
+
+/// the error returned by a typed query when the contract answers with another variant, such as a
+/// viewing key error
+fn unexpected_answer(answer: QueryAnswer) -> StdError {
+    match answer {
+        QueryAnswer::ViewingKeyError { msg } => StdError::generic_err(msg),
+        _ => StdError::generic_err("unexpected query answer"),
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////
+// Message constructors
+/////////////////////////////////////////////////////////////////////////////////
+
+impl ExecuteMsg {
+    /// serializes the message, and pads it to 256 bytes
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let mut data = to_binary(&self)?;
+        space_pad(RESPONSE_BLOCK_SIZE, &mut data.0);
+        Ok(data)
+    }
+
+    /// creates a `WasmMsg` sending this message and `funds` to the named contract
+    pub fn into_wasm_msg(
+        self,
+        code_hash: String,
+        contract_addr: Addr,
+        funds: Vec<Coin>,
+    ) -> StdResult<WasmMsg> {
+        Ok(WasmMsg::Execute {
+            msg: self.into_binary()?,
+            code_hash,
+            contract_addr: contract_addr.to_string(),
+            funds,
+        })
+    }
+
+    /// creates a cosmos_msg sending this message to the named contract
+    pub fn into_cosmos_msg(self, code_hash: String, contract_addr: Addr) -> StdResult<CosmosMsg> {
+        Ok(self.into_wasm_msg(code_hash, contract_addr, vec![])?.into())
+    }
+}
+
+impl QueryMsg {
+    /// serializes the query, and pads it to 256 bytes
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let mut data = to_binary(&self)?;
+        space_pad(RESPONSE_BLOCK_SIZE, &mut data.0);
+        Ok(data)
+    }
+
+    /// creates a `WasmQuery` sending this query to the named contract
+    pub fn into_wasm_query(self, code_hash: String, contract_addr: Addr) -> StdResult<WasmQuery> {
+        Ok(WasmQuery::Smart {
+            contract_addr: contract_addr.to_string(),
+            code_hash,
+            msg: self.into_binary()?,
+        })
+    }
+
+    /// sends this query to the named contract
+    pub fn query(
+        self,
+        querier: QuerierWrapper,
+        code_hash: String,
+        contract_addr: Addr,
+    ) -> StdResult<QueryAnswer> {
+        querier.query(&QueryRequest::Wasm(
+            self.into_wasm_query(code_hash, contract_addr)?,
+        ))
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////
+// Builders
+/////////////////////////////////////////////////////////////////////////////////
+
+// builders only exist for `Transfer`, `Send` and `GivePermission`, which have many optional
+// fields. Other messages have typed constructors on `Snip1155Contract`, or are created as
+// `ExecuteMsg` and `QueryMsg` variants and sent with the generic constructors above
+
+/// builds a `Transfer` message, or a `TransferAction` of a `BatchTransfer`
+#[derive(Clone, Debug)]
+pub struct TransferBuilder {
+    action: TransferAction,
+    entropy: Option<String>,
+    padding: Option<String>,
+}
+
+impl TransferBuilder {
+    pub fn new(token_id: String, from: Addr, recipient: Addr, amount: Uint256) -> Self {
+        Self {
+            action: TransferAction {
+                token_id,
+                from,
+                recipient,
+                amount,
+                memo: None,
+                decoys: None,
+            },
+            entropy: None,
+            padding: None,
+        }
+    }
+
+    pub fn memo(mut self, memo: String) -> Self {
+        self.action.memo = Some(memo);
+        self
+    }
+
+    pub fn decoys(mut self, decoys: Vec<Addr>, entropy: Option<String>) -> Self {
+        self.action.decoys = Some(decoys);
+        self.entropy = entropy;
+        self
+    }
+
+    pub fn padding(mut self, padding: String) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    /// the action of a `BatchTransfer`. Entropy and padding are set on the `BatchTransfer` instead
+    pub fn into_action(self) -> TransferAction {
+        self.action
+    }
+
+    pub fn build(self) -> ExecuteMsg {
+        let action = self.action;
+        ExecuteMsg::Transfer {
+            token_id: action.token_id,
+            from: action.from,
+            recipient: action.recipient,
+            amount: action.amount,
+            memo: action.memo,
+            decoys: action.decoys,
+            entropy: self.entropy,
+            padding: self.padding,
+        }
+    }
+}
+
+/// builds a `Send` message, or a `SendAction` of a `BatchSend`
+#[derive(Clone, Debug)]
+pub struct SendBuilder {
+    action: SendAction,
+    entropy: Option<String>,
+    padding: Option<String>,
+}
+
+impl SendBuilder {
+    pub fn new(token_id: String, from: Addr, recipient: Addr, amount: Uint256) -> Self {
+        Self {
+            action: SendAction {
+                token_id,
+                from,
+                recipient,
+                recipient_code_hash: None,
+                amount,
+                msg: None,
+                memo: None,
+                decoys: None,
+                send_mode: None,
+            },
+            entropy: None,
+            padding: None,
+        }
+    }
+
+    pub fn recipient_code_hash(mut self, recipient_code_hash: String) -> Self {
+        self.action.recipient_code_hash = Some(recipient_code_hash);
+        self
+    }
+
+    pub fn msg(mut self, msg: Binary) -> Self {
+        self.action.msg = Some(msg);
+        self
+    }
+
+    pub fn memo(mut self, memo: String) -> Self {
+        self.action.memo = Some(memo);
+        self
+    }
+
+    pub fn decoys(mut self, decoys: Vec<Addr>, entropy: Option<String>) -> Self {
+        self.action.decoys = Some(decoys);
+        self.entropy = entropy;
+        self
+    }
+
+    pub fn send_mode(mut self, send_mode: SendMode) -> Self {
+        self.action.send_mode = Some(send_mode);
+        self
+    }
+
+    pub fn padding(mut self, padding: String) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    /// the action of a `BatchSend`. Entropy and padding are set on the `BatchSend` instead
+    pub fn into_action(self) -> SendAction {
+        self.action
+    }
+
+    pub fn build(self) -> ExecuteMsg {
+        let action = self.action;
+        ExecuteMsg::Send {
+            token_id: action.token_id,
+            from: action.from,
+            recipient: action.recipient,
+            recipient_code_hash: action.recipient_code_hash,
+            amount: action.amount,
+            msg: action.msg,
+            memo: action.memo,
+            decoys: action.decoys,
+            send_mode: action.send_mode,
+            entropy: self.entropy,
+            padding: self.padding,
+        }
+    }
+}
+
+/// builds a `GivePermission` message. Permissions that are not set are left unchanged
+#[derive(Clone, Debug)]
+pub struct GivePermissionBuilder {
+    allowed_address: Addr,
+    token_id: String,
+    view_balance: Option<bool>,
+    view_balance_expiry: Option<Expiration>,
+    view_private_metadata: Option<bool>,
+    view_private_metadata_expiry: Option<Expiration>,
+    transfer: Option<Uint256>,
+    transfer_expiry: Option<Expiration>,
+    padding: Option<String>,
+}
+
+impl GivePermissionBuilder {
+    pub fn new(allowed_address: Addr, token_id: String) -> Self {
+        Self {
+            allowed_address,
+            token_id,
+            view_balance: None,
+            view_balance_expiry: None,
+            view_private_metadata: None,
+            view_private_metadata_expiry: None,
+            transfer: None,
+            transfer_expiry: None,
+            padding: None,
+        }
+    }
+
+    pub fn view_balance(mut self, view_balance: bool, expiry: Option<Expiration>) -> Self {
+        self.view_balance = Some(view_balance);
+        self.view_balance_expiry = expiry;
+        self
+    }
+
+    pub fn view_private_metadata(
+        mut self,
+        view_private_metadata: bool,
+        expiry: Option<Expiration>,
+    ) -> Self {
+        self.view_private_metadata = Some(view_private_metadata);
+        self.view_private_metadata_expiry = expiry;
+        self
+    }
+
+    pub fn transfer(mut self, allowance: Uint256, expiry: Option<Expiration>) -> Self {
+        self.transfer = Some(allowance);
+        self.transfer_expiry = expiry;
+        self
+    }
+
+    pub fn padding(mut self, padding: String) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    pub fn build(self) -> ExecuteMsg {
+        ExecuteMsg::GivePermission {
+            allowed_address: self.allowed_address,
+            token_id: self.token_id,
+            view_balance: self.view_balance,
+            view_balance_expiry: self.view_balance_expiry,
+            view_private_metadata: self.view_private_metadata,
+            view_private_metadata_expiry: self.view_private_metadata_expiry,
+            transfer: self.transfer,
+            transfer_expiry: self.transfer_expiry,
+            padding: self.padding,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+#![allow(clippy::field_reassign_with_default)] // This is triggered in `#[derive(JsonSchema)]`
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Addr, Binary, CosmosMsg, StdResult, Uint256, WasmMsg};
+
+use crate::{msg::space_pad, state::RESPONSE_BLOCK_SIZE};
+
+/// message sent to the controller contract before and after a token_id with transfer hooks
+/// enabled is minted, burned or transferred
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct TransferHookMsg {
+    /// the address on whose behalf the balances changed: the sender of the message, or the signer
+    /// of a transfer permit
+    pub sender: Addr,
+    /// unique token_id `String`
+    pub token_id: String,
+    /// the previous owner of the tokens. `None` when tokens are minted
+    pub from: Option<Addr>,
+    /// the new owner of the tokens. `None` when tokens are burned
+    pub to: Option<Addr>,
+    /// amount of tokens being moved
+    pub amount: Uint256,
+    /// balance of `from`: before the change in `BeforeTransfer`, and after it in `AfterTransfer`.
+    /// `None` when tokens are minted
+    pub from_balance: Option<Uint256>,
+    /// balance of `to`: before the change in `BeforeTransfer`, and after it in `AfterTransfer`.
+    /// `None` when tokens are burned
+    pub to_balance: Option<Uint256>,
+}
+
+impl TransferHookMsg {
+    pub fn new(
+        sender: Addr,
+        token_id: String,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Uint256,
+        from_balance: Option<Uint256>,
+        to_balance: Option<Uint256>,
+    ) -> Self {
+        Self {
+            sender,
+            token_id,
+            from,
+            to,
+            amount,
+            from_balance,
+            to_balance,
+        }
+    }
+
+    /// creates a cosmos_msg sending this struct as a `BeforeTransfer` hook to the named contract
+    pub fn into_before_msg(self, code_hash: String, contract_addr: Addr) -> StdResult<CosmosMsg> {
+        ControllerHandleMsg::BeforeTransfer(self).into_cosmos_msg(code_hash, contract_addr)
+    }
+
+    /// creates a cosmos_msg sending this struct as an `AfterTransfer` hook to the named contract
+    pub fn into_after_msg(self, code_hash: String, contract_addr: Addr) -> StdResult<CosmosMsg> {
+        ControllerHandleMsg::AfterTransfer(self).into_cosmos_msg(code_hash, contract_addr)
+    }
+}
+
+/// handle messages a controller contract must implement. `BeforeTransfer` carries the balances
+/// from before the change, and `AfterTransfer` the balances from after it. Both are executed once
+/// the handle has finished, so the controller vetoes a transfer by returning an error on
+/// `BeforeTransfer`, which reverts the whole transaction, including the balance change
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ControllerHandleMsg {
+    BeforeTransfer(TransferHookMsg),
+    AfterTransfer(TransferHookMsg),
+}
+
+impl ControllerHandleMsg {
+    /// serializes the message, and pads it to 256 bytes
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let mut data = to_binary(&self)?;
+        space_pad(RESPONSE_BLOCK_SIZE, &mut data.0);
+        Ok(data)
+    }
+
+    /// creates a cosmos_msg sending this message to the named contract
+    pub fn into_cosmos_msg(self, code_hash: String, contract_addr: Addr) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            msg,
+            code_hash,
+            contract_addr: contract_addr.to_string(),
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
@@ -1,49 +1,82 @@
 // use base64::{engine::general_purpose, Engine as _};
 use cosmwasm_std::{
-    entry_point,
     // debug_print,
+    from_binary,
     to_binary,
     Addr,
+    BankMsg,
     Binary,
+    BlockInfo,
+    Coin,
     CosmosMsg,
     Deps,
     DepsMut,
     Env,
     MessageInfo,
+    Reply,
     Response,
     StdError,
     StdResult,
     Storage,
+    SubMsg,
+    SubMsgResponse,
+    SubMsgResult,
+    Uint128,
     Uint256,
 };
 use secret_toolkit::{
     crypto::sha_256,
-    permit::RevokedPermits,
+    permit::{validate, Permissions, Permit, RevokedPermits},
     utils::space_pad,
     viewing_key::{ViewingKey, ViewingKeyStore},
 };
 
 use crate::{
+    controller::TransferHookMsg,
     msg::{
-        ExecuteAnswer, ExecuteMsg, InstantiateMsg, ResponseStatus::Success, SendAction,
-        TransferAction,
+        ExecuteAnswer, ExecuteMsg, GivePermissionPermitPayload, InstantiateMsg,
+        ResponseStatus::Success, SendAction, TransferAction, TransferPermitPayload,
     },
-    receiver::Snip1155ReceiveMsg,
+    receiver::{
+        parse_execute_response_data, ReceiverHandleAnswer, Snip1155ReceiveMsg, MINT_FROM_MARKER,
+    },
+    snip20::{Snip20HandleMsg, Snip20QueryMsg, Snip20ReceiveAction},
     state::{
-        balances_r, balances_w, blockinfo_w, contr_conf_r, contr_conf_w,
+        accumulators::{write_balance_accumulator, write_supply_accumulator},
+        balances_r, balances_w, blockinfo_w,
+        checkpoints::{write_balance_checkpoint, write_supply_checkpoint},
+        compliance::{
+            append_compliance_event, check_compliance, ComplianceAction, TransferListMode,
+        },
+        contr_conf_r, contr_conf_w,
         expiration::Expiration,
-        get_receiver_hash,
+        frozen_w, get_receiver_hash,
+        holders::update_holder,
         metadata::Metadata,
+        nft_users_w,
         permissions::{may_load_any_permission, new_permission, update_permission, Permission},
-        set_receiver_hash,
+        permit_nonces_r, permit_nonces_w,
+        rewards::{add_rewards, settle_rewards, take_rewards},
+        scoped_vks_r, scoped_vks_w, series_info_r, series_info_w, set_receiver_hash,
         state_structs::{
-            ContractConfig, CurateTokenId, StoredTokenInfo, TknConfig, TokenAmount, TokenInfoMsg,
+            AutoCurateTemplate, BackingAsset, ContractConfig, Controller, CurateTokenId, LbPair,
+            MintTokenAmount, MintTokenIdBalance, NftUser, PendingTransferHook,
+            ScopedViewingKeyInfo, SendMode, SeriesInfoMsg, StoredTokenInfo, SwapOffer, TokenAmount,
+            TokenBacking, TokenIdBalance, ViewingKeyScope,
         },
-        tkn_info_r, tkn_info_w, tkn_tot_supply_r, tkn_tot_supply_w,
+        swaps_r, swaps_w, tkn_backing_r, tkn_backing_w, tkn_frozen_w, tkn_info_r, tkn_info_w,
+        tkn_reserve_r, tkn_reserve_w, tkn_tot_supply_r, tkn_tot_supply_w, transfer_hooks_r,
+        transfer_hooks_w, transfer_list_mode_w, transfer_list_w,
         txhistory::{
             append_new_owner, may_get_current_owner, store_burn, store_mint, store_transfer,
+            touch_tx_ids,
         },
-        PREFIX_REVOKED_PERMITS, RESPONSE_BLOCK_SIZE,
+        vesting::{
+            add_vesting_grant, prune_vesting_grants, revoke_vesting_grants, VestingGrant,
+            VestingSchedule,
+        },
+        ScopedViewingKey, DEFAULT_MAX_BATCH_QUERIES, PREFIX_REVOKED_PERMITS, RECEIVE_REPLY_ID,
+        RESPONSE_BLOCK_SIZE,
     },
 };
 
@@ -52,7 +85,7 @@ use crate::{
 /////////////////////////////////////////////////////////////////////////////////
 
 /// instantiation function. See [InitMsg](crate::msg::InitMsg) for the api
-#[entry_point]
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
 pub fn instantiate(
     mut deps: DepsMut,
     env: Env,
@@ -82,6 +115,23 @@ pub fn instantiate(
     // );
 
     ViewingKey::set_seed(deps.storage, &prng_seed);
+    ScopedViewingKey::set_seed(deps.storage, &prng_seed);
+
+    if let Some(uri_template) = &msg.uri_template {
+        verify_uri_template(uri_template)?;
+    }
+    let auto_curate_template = match msg.auto_curate_template {
+        Some(template) => {
+            verify_auto_curate_template(&template, &msg.lb_pair_info)?;
+            template
+        }
+        // the default template is disabled if the lb pair does not render a valid name and symbol
+        None => {
+            let mut template = AutoCurateTemplate::from_lb_pair(&msg.lb_pair_info);
+            template.enabled = verify_auto_curate_template(&template, &msg.lb_pair_info).is_ok();
+            template
+        }
+    };
 
     let mut config = ContractConfig {
         admin,
@@ -90,18 +140,35 @@ pub fn instantiate(
         tx_cnt: 0u64,
         prng_seed: prng_seed.to_vec(),
         contract_address: env.contract.address.clone(),
+        auto_curate_template,
         lb_pair_info: msg.lb_pair_info,
+        uri_template: msg.uri_template,
+        max_batch_queries: msg.max_batch_queries.unwrap_or(DEFAULT_MAX_BATCH_QUERIES),
+        controller: None,
+        swap_cnt: 0,
+        send_mode: SendMode::default(),
     };
 
-    // set initial balances
+    // set initial balances. No controller is set yet, so there are no transfer hooks to send
+    let mut messages = vec![];
+    let mut hooks = vec![];
     for initial_token in msg.initial_tokens {
-        exec_curate_token_id(&mut deps, &env, &info, &mut config, initial_token, None)?;
+        exec_curate_token_id(
+            &mut deps,
+            &env,
+            &info,
+            &mut config,
+            &mut messages,
+            &mut hooks,
+            initial_token,
+            None,
+        )?;
     }
 
     // save contract config -- where tx_cnt would have increased post initial balances
     contr_conf_w(deps.storage).save(&config)?;
 
-    Ok(Response::default())
+    Ok(Response::new().add_submessages(messages))
 }
 
 /////////////////////////////////////////////////////////////////////////////////
@@ -110,13 +177,36 @@ pub fn instantiate(
 
 /// contract handle function. See [ExecuteMsg](crate::msg::ExecuteMsg) and
 /// [ExecuteAnswer](crate::msg::ExecuteAnswer) for the api
-#[entry_point]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn execute(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> StdResult<Response> {
     // allows approx latest block info to be available for queries. Important to enforce
     // allowance expiration. Remove this after BlockInfo becomes available to queries
     blockinfo_w(deps.storage).save(&env.block)?;
 
-    let response = match msg {
+    let response = dispatch_execute(deps.branch(), env, info, msg);
+    pad_response(response)
+}
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+    match msg.id {
+        RECEIVE_REPLY_ID => try_receive_reply(msg.result),
+        id => Err(StdError::generic_err(format!("unknown reply id {}", id))),
+    }
+}
+
+fn dispatch_execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
         ExecuteMsg::CurateTokenIds {
             initial_tokens,
             memo,
@@ -150,12 +240,28 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             recipient,
             amount,
             memo,
+            decoys,
+            entropy,
             padding: _,
-        } => try_transfer(deps, env, info, token_id, from, recipient, amount, memo),
+        } => try_transfer(
+            deps,
+            env,
+            info,
+            TransferAction {
+                token_id,
+                from,
+                recipient,
+                amount,
+                memo,
+                decoys,
+            },
+            entropy,
+        ),
         ExecuteMsg::BatchTransfer {
             actions,
+            entropy,
             padding: _,
-        } => try_batch_transfer(deps, env, info, actions),
+        } => try_batch_transfer(deps, env, info, actions, entropy),
         ExecuteMsg::Send {
             token_id,
             from,
@@ -164,6 +270,9 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             amount,
             msg,
             memo,
+            decoys,
+            send_mode,
+            entropy,
             padding: _,
         } => try_send(
             deps,
@@ -177,12 +286,16 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 amount,
                 msg,
                 memo,
+                decoys,
+                send_mode,
             },
+            entropy,
         ),
         ExecuteMsg::BatchSend {
             actions,
+            entropy,
             padding: _,
-        } => try_batch_send(deps, env, info, actions),
+        } => try_batch_send(deps, env, info, actions, entropy),
         ExecuteMsg::GivePermission {
             allowed_address,
             token_id,
@@ -212,11 +325,27 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             allowed_address,
             padding: _,
         } => try_revoke_permission(deps, env, info, token_id, owner, allowed_address),
+        ExecuteMsg::TransferWithPermit { permit, padding: _ } => {
+            try_transfer_with_permit(deps, env, permit)
+        }
+        ExecuteMsg::GivePermissionWithPermit { permit, padding: _ } => {
+            try_give_permission_with_permit(deps, env, permit)
+        }
         ExecuteMsg::CreateViewingKey {
             entropy,
             padding: _,
         } => try_create_viewing_key(deps, env, info, entropy),
         ExecuteMsg::SetViewingKey { key, padding: _ } => try_set_viewing_key(deps, env, info, key),
+        ExecuteMsg::CreateScopedViewingKey {
+            label,
+            scopes,
+            expiration,
+            entropy,
+            padding: _,
+        } => try_create_scoped_viewing_key(deps, env, info, label, scopes, expiration, entropy),
+        ExecuteMsg::RevokeScopedViewingKey { label, padding: _ } => {
+            try_revoke_scoped_viewing_key(deps, env, info, label)
+        }
         ExecuteMsg::RevokePermit {
             permit_name,
             padding: _,
@@ -252,8 +381,130 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             code_hash,
             padding: _,
         } => try_register_receive(deps, env, info, code_hash),
-    };
-    pad_response(response)
+        ExecuteMsg::SetUriTemplate {
+            uri_template,
+            padding: _,
+        } => try_set_uri_template(deps, env, info, uri_template),
+        ExecuteMsg::SetMaxBatchQueries {
+            max_batch_queries,
+            padding: _,
+        } => try_set_max_batch_queries(deps, env, info, max_batch_queries),
+        ExecuteMsg::SetAutoCurateTemplate {
+            template,
+            padding: _,
+        } => try_set_auto_curate_template(deps, env, info, *template),
+        ExecuteMsg::LowerMaxSupply {
+            token_id,
+            max_supply,
+            padding: _,
+        } => try_lower_max_supply(deps, env, info, token_id, max_supply),
+        ExecuteMsg::CurateSeries { series, padding: _ } => {
+            try_curate_series(deps, env, info, series)
+        }
+        ExecuteMsg::MintEditions {
+            series_id,
+            recipients,
+            memo,
+            padding: _,
+        } => try_mint_editions(deps, env, info, series_id, recipients, memo),
+        ExecuteMsg::Deposit {
+            token_id,
+            memo,
+            padding: _,
+        } => try_deposit(deps, env, info, token_id, memo),
+        ExecuteMsg::Receive {
+            sender: _,
+            from,
+            amount,
+            memo,
+            msg,
+        } => try_receive(deps, env, info, from, amount, memo, msg),
+        ExecuteMsg::Redeem {
+            token_id,
+            amount,
+            memo,
+            padding: _,
+        } => try_redeem(deps, env, info, token_id, amount, memo),
+        ExecuteMsg::DistributeRewards {
+            token_id,
+            asset,
+            amount,
+            padding: _,
+        } => try_distribute_rewards(deps, env, info, token_id, asset, amount),
+        ExecuteMsg::ClaimRewards {
+            token_id,
+            padding: _,
+        } => try_claim_rewards(deps, env, info, token_id),
+        ExecuteMsg::SetController {
+            controller,
+            padding: _,
+        } => try_set_controller(deps, env, info, controller),
+        ExecuteMsg::SetTransferHooks {
+            token_ids,
+            enabled,
+            padding: _,
+        } => try_set_transfer_hooks(deps, env, info, token_ids, enabled),
+        ExecuteMsg::SetSendMode {
+            send_mode,
+            padding: _,
+        } => try_set_send_mode(deps, env, info, send_mode),
+        ExecuteMsg::SetFrozen {
+            address,
+            token_id,
+            frozen,
+            padding: _,
+        } => try_set_frozen(deps, env, info, address, token_id, frozen),
+        ExecuteMsg::SetTransferListMode {
+            token_id,
+            mode,
+            padding: _,
+        } => try_set_transfer_list_mode(deps, env, info, token_id, mode),
+        ExecuteMsg::UpdateTransferList {
+            token_id,
+            add,
+            remove,
+            padding: _,
+        } => try_update_transfer_list(deps, env, info, token_id, add, remove),
+        ExecuteMsg::MintVested {
+            token_id,
+            recipient,
+            amount,
+            schedule,
+            revocable,
+            memo,
+            padding: _,
+        } => try_mint_vested(
+            deps, env, info, token_id, recipient, amount, schedule, revocable, memo,
+        ),
+        ExecuteMsg::RevokeVesting {
+            token_id,
+            holder,
+            memo,
+            padding: _,
+        } => try_revoke_vesting(deps, env, info, token_id, holder, memo),
+        ExecuteMsg::CreateSwapOffer {
+            give,
+            want,
+            counterparty,
+            expiry,
+            public,
+            padding: _,
+        } => try_create_swap_offer(deps, env, info, give, want, counterparty, expiry, public),
+        ExecuteMsg::AcceptSwap {
+            swap_id,
+            padding: _,
+        } => try_accept_swap(deps, env, info, swap_id),
+        ExecuteMsg::CancelSwap {
+            swap_id,
+            padding: _,
+        } => try_cancel_swap(deps, env, info, swap_id),
+        ExecuteMsg::SetUser {
+            token_id,
+            user,
+            expires,
+            padding: _,
+        } => try_set_user(deps, env, info, token_id, user, expires),
+    }
 }
 
 fn try_curate_token_ids(
@@ -268,12 +519,17 @@ fn try_curate_token_ids(
     verify_curator(&config, &info)?;
 
     // curate new token_ids
+    let mut messages = vec![];
+    let mut hooks = vec![];
     for initial_token in initial_tokens {
+        verify_not_edition_id(deps.storage, &initial_token.token_info.token_id)?;
         exec_curate_token_id(
             &mut deps,
             &env,
             &info,
             &mut config,
+            &mut messages,
+            &mut hooks,
             initial_token,
             memo.clone(),
         )?;
@@ -281,18 +537,19 @@ fn try_curate_token_ids(
 
     contr_conf_w(deps.storage).save(&config)?;
 
-    Ok(
-        Response::new().set_data(to_binary(&ExecuteAnswer::CurateTokenIds {
+    let response = Response::new()
+        .add_submessages(messages)
+        .set_data(to_binary(&ExecuteAnswer::CurateTokenIds {
             status: Success,
-        })?),
-    )
+        })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
 }
 
 fn try_mint_tokens(
     mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    mint_tokens: Vec<TokenAmount>,
+    mint_tokens: Vec<MintTokenAmount>,
     memo: Option<String>,
 ) -> StdResult<Response> {
     let mut config = contr_conf_r(deps.storage).load()?;
@@ -300,28 +557,27 @@ fn try_mint_tokens(
     verify_curator(&config, &info)?;
 
     // mint tokens
+    let mut messages = vec![];
+    let mut hooks = vec![];
     for mint_token in mint_tokens {
         let token_info_op = tkn_info_r(deps.storage).may_load(mint_token.token_id.as_bytes())?;
 
-        // check if token_id exists
+        // check if token_id exists. If not, curate it from the auto curate template
         if token_info_op.is_none() {
+            if !config.auto_curate_template.enabled {
+                return Err(StdError::generic_err(format!(
+                    "token_id {} does not exist and automatic curation is disabled",
+                    mint_token.token_id
+                )));
+            }
+            verify_not_edition_id(deps.storage, &mint_token.token_id)?;
             let curate_token = CurateTokenId {
-                token_info: TokenInfoMsg {
-                    token_id: mint_token.token_id.clone(),
-                    name: format!("{}-{}", &config.lb_pair_info.name, mint_token.token_id),
-                    symbol: format!("{}", &config.lb_pair_info.symbol),
-                    token_config: TknConfig::Fungible {
-                        minters: Vec::new(), // No need for minter curator will be the minter
-                        decimals: config.lb_pair_info.decimals,
-                        public_total_supply: true,
-                        enable_mint: true,
-                        enable_burn: true,
-                        minter_may_update_metadata: false,
-                    },
-                    public_metadata: None,
-                    private_metadata: None,
-                },
+                token_info: config
+                    .auto_curate_template
+                    .to_token_info_msg(&config.lb_pair_info, &mint_token.token_id),
                 balances: mint_token.balances,
+                backing: None,
+                transfer_hooks: config.auto_curate_template.transfer_hooks,
             };
 
             exec_curate_token_id(
@@ -329,6 +585,8 @@ fn try_mint_tokens(
                 &env,
                 &info,
                 &mut config,
+                &mut messages,
+                &mut hooks,
                 curate_token,
                 memo.clone(),
             )?;
@@ -354,13 +612,24 @@ fn try_mint_tokens(
 
         // add balances
         for add_balance in mint_token.balances {
+            try_add_mint_callback(
+                deps.storage,
+                &config,
+                &mut messages,
+                &info.sender,
+                &mint_token.token_id,
+                &add_balance,
+                memo.clone(),
+            )?;
             exec_change_balance(
                 deps.storage,
+                &env.block,
                 &mint_token.token_id,
                 None,
                 Some(&add_balance.address),
                 &add_balance.amount,
                 &token_info_op.clone().unwrap(),
+                &mut hooks,
             )?;
 
             // store mint_token
@@ -379,103 +648,519 @@ fn try_mint_tokens(
 
     contr_conf_w(deps.storage).save(&config)?;
 
-    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::MintTokens { status: Success })?))
+    let response = Response::new()
+        .add_submessages(messages)
+        .set_data(to_binary(&ExecuteAnswer::MintTokens { status: Success })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
 }
 
-// in the base specifications, this function can be performed by token owner only
-fn try_burn_tokens(
+#[allow(clippy::too_many_arguments)]
+fn try_mint_vested(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    burn_tokens: Vec<TokenAmount>,
+    token_id: String,
+    recipient: Addr,
+    amount: Uint256,
+    schedule: VestingSchedule,
+    revocable: bool,
     memo: Option<String>,
 ) -> StdResult<Response> {
     let mut config = contr_conf_r(deps.storage).load()?;
 
-    // burn tokens
-    for burn_token in burn_tokens {
-        let token_info_op = tkn_info_r(deps.storage).may_load(burn_token.token_id.as_bytes())?;
+    verify_curator(&config, &info)?;
 
-        if token_info_op.is_none() {
-            return Err(StdError::generic_err(
-                "token_id does not exist. Cannot burn non-existent `token_ids`. Use `curate_token_ids` to create tokens on new `token_ids`"
-            ));
-        }
+    let token_info = tkn_info_r(deps.storage)
+        .may_load(token_id.as_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("token_id {} does not exist", token_id)))?;
+    if !token_info.token_config.flatten().enable_mint {
+        return Err(StdError::generic_err(
+            "minting is not enabled for this token_id",
+        ));
+    }
+    schedule.validate(amount)?;
 
-        let token_info = token_info_op.clone().unwrap();
+    let recipient = deps.api.addr_validate(recipient.as_str())?;
+    let mut hooks = vec![];
+    exec_change_balance(
+        deps.storage,
+        &env.block,
+        &token_id,
+        None,
+        Some(&recipient),
+        &amount,
+        &token_info,
+        &mut hooks,
+    )?;
+    add_vesting_grant(
+        deps.storage,
+        &token_id,
+        &recipient,
+        VestingGrant {
+            amount,
+            schedule,
+            minter: info.sender.clone(),
+            revocable,
+        },
+    )?;
 
-        if !token_info.token_config.flatten().enable_burn {
+    store_mint(
+        deps.storage,
+        &mut config,
+        &env.block,
+        &token_id,
+        deps.api.addr_canonicalize(info.sender.as_str())?,
+        deps.api.addr_canonicalize(recipient.as_str())?,
+        amount,
+        memo,
+    )?;
+    contr_conf_w(deps.storage).save(&config)?;
+
+    let response =
+        Response::new().set_data(to_binary(&ExecuteAnswer::MintVested { status: Success })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
+}
+
+fn try_revoke_vesting(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    holder: Addr,
+    memo: Option<String>,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
+    let token_info = tkn_info_r(deps.storage)
+        .may_load(token_id.as_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("token_id {} does not exist", token_id)))?;
+
+    // the revoked grants are removed first, so their locked tokens can be burned
+    let holder = deps.api.addr_validate(holder.as_str())?;
+    let locked = revoke_vesting_grants(deps.storage, &env.block, &token_id, &holder, &info.sender)?;
+    let mut hooks = vec![];
+    if !locked.is_zero() {
+        exec_change_balance(
+            deps.storage,
+            &env.block,
+            &token_id,
+            Some(&holder),
+            None,
+            &locked,
+            &token_info,
+            &mut hooks,
+        )?;
+        store_burn(
+            deps.storage,
+            &mut config,
+            &env.block,
+            &token_id,
+            Some(deps.api.addr_canonicalize(info.sender.as_str())?),
+            deps.api.addr_canonicalize(holder.as_str())?,
+            locked,
+            memo,
+        )?;
+        contr_conf_w(deps.storage).save(&config)?;
+    }
+
+    let response = Response::new().set_data(to_binary(&ExecuteAnswer::RevokeVesting {
+        status: Success,
+    })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn try_create_swap_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    give: Vec<TokenAmount>,
+    want: Vec<TokenAmount>,
+    counterparty: Option<Addr>,
+    expiry: Option<Expiration>,
+    public: Option<bool>,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
+
+    if give.is_empty() || want.is_empty() {
+        return Err(StdError::generic_err(
+            "a swap offer must give and want at least one balance",
+        ));
+    }
+    let expiry = expiry.unwrap_or_default();
+    if expiry.is_expired(&env.block) {
+        return Err(StdError::generic_err(
+            "swap offer expiry has already passed",
+        ));
+    }
+    let counterparty = counterparty
+        .map(|address| deps.api.addr_validate(address.as_str()))
+        .transpose()?;
+
+    // check both legs before any balance is locked
+    for token_amount in &give {
+        load_swap_token_info(deps.storage, &token_amount.token_id)?;
+        if token_amount
+            .balances
+            .iter()
+            .any(|balance| balance.address != info.sender)
+        {
             return Err(StdError::generic_err(
-                "burning is not enabled for this token_id",
+                "a swap offer can only give the sender's own balances",
             ));
         }
-
-        // remove balances
-        for rem_balance in burn_token.balances {
-            // in base specification, burner MUST be the owner
-            if rem_balance.address != info.sender {
-                return Err(StdError::generic_err(format!(
-                    "you do not have permission to burn {} tokens from address {}",
-                    rem_balance.amount, rem_balance.address
-                )));
+    }
+    for token_amount in &want {
+        load_swap_token_info(deps.storage, &token_amount.token_id)?;
+        for balance in &token_amount.balances {
+            deps.api.addr_validate(balance.address.as_str())?;
+            if balance.address == config.contract_address {
+                return Err(StdError::generic_err(
+                    "the contract cannot receive the wanted balances of a swap offer",
+                ));
             }
+        }
+    }
 
+    // lock the offered balances in the contract
+    let mut hooks = vec![];
+    let sender_canon = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let contract_canon = deps
+        .api
+        .addr_canonicalize(config.contract_address.as_str())?;
+    for token_amount in &give {
+        let token_info = load_swap_token_info(deps.storage, &token_amount.token_id)?;
+        for balance in &token_amount.balances {
             exec_change_balance(
                 deps.storage,
-                &burn_token.token_id,
-                Some(&rem_balance.address),
-                None,
-                &rem_balance.amount,
+                &env.block,
+                &token_amount.token_id,
+                Some(&info.sender),
+                Some(&config.contract_address),
+                &balance.amount,
                 &token_info,
+                &mut hooks,
             )?;
-
-            // store burn_token
-            store_burn(
+            store_transfer(
                 deps.storage,
                 &mut config,
                 &env.block,
-                &burn_token.token_id,
-                // in base specification, burner MUST be the owner
+                &token_amount.token_id,
+                sender_canon.clone(),
+                None,
+                contract_canon.clone(),
+                balance.amount,
                 None,
-                deps.api.addr_canonicalize(rem_balance.address.as_str())?,
-                rem_balance.amount,
-                memo.clone(),
             )?;
         }
     }
 
+    let swap_id = config.swap_cnt;
+    let offer = SwapOffer {
+        swap_id,
+        creator: info.sender.clone(),
+        give,
+        want,
+        counterparty,
+        expiry,
+        public: public.unwrap_or(false),
+    };
+    swaps_w(deps.storage).save(&swap_id.to_be_bytes(), &offer)?;
+    config.swap_cnt += 1;
     contr_conf_w(deps.storage).save(&config)?;
 
-    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::BurnTokens { status: Success })?))
+    let response =
+        Response::new().set_data(to_binary(&ExecuteAnswer::CreateSwapOffer { swap_id })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
 }
 
-fn try_change_metadata(
+fn try_accept_swap(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    token_id: String,
-    public_metadata: Option<Metadata>,
-    private_metadata: Option<Metadata>,
+    swap_id: u64,
 ) -> StdResult<Response> {
-    let tkn_info_op = tkn_info_r(deps.storage).may_load(token_id.as_bytes())?;
-    let tkn_conf = match tkn_info_op.clone() {
-        None => {
+    let mut config = contr_conf_r(deps.storage).load()?;
+    let offer = load_swap_offer(deps.storage, swap_id)?;
+    if offer.creator == info.sender {
+        return Err(StdError::generic_err(format!(
+            "the creator cannot accept swap offer {}",
+            swap_id
+        )));
+    }
+    if let Some(counterparty) = &offer.counterparty {
+        if counterparty != &info.sender {
             return Err(StdError::generic_err(format!(
-                "token_id {} does not exist",
-                token_id
-            )))
+                "only {} can accept swap offer {}",
+                counterparty, swap_id
+            )));
         }
-        Some(i) => i.token_config.flatten(),
-    };
-
-    // define variables for control flow
-    let owner = may_get_current_owner(deps.storage, &token_id)?;
-    let is_owner = match owner {
-        Some(owner_addr) => owner_addr == info.sender,
-        None => false,
-    };
-
-    let is_minter = verify_minter(tkn_info_op.as_ref().unwrap(), &info).is_ok();
+    }
+    if offer.expiry.is_expired(&env.block) {
+        return Err(StdError::generic_err(format!(
+            "swap offer {} has expired",
+            swap_id
+        )));
+    }
+    swaps_w(deps.storage).remove(&swap_id.to_be_bytes());
+
+    // the sender pays the wanted balances
+    let mut hooks = vec![];
+    for token_amount in &offer.want {
+        let token_info = load_swap_token_info(deps.storage, &token_amount.token_id)?;
+        for balance in &token_amount.balances {
+            exec_change_balance(
+                deps.storage,
+                &env.block,
+                &token_amount.token_id,
+                Some(&info.sender),
+                Some(&balance.address),
+                &balance.amount,
+                &token_info,
+                &mut hooks,
+            )?;
+            store_transfer(
+                deps.storage,
+                &mut config,
+                &env.block,
+                &token_amount.token_id,
+                deps.api.addr_canonicalize(info.sender.as_str())?,
+                None,
+                deps.api.addr_canonicalize(balance.address.as_str())?,
+                balance.amount,
+                None,
+            )?;
+        }
+    }
+
+    // and receives the balances held in escrow
+    let contract_canon = deps
+        .api
+        .addr_canonicalize(config.contract_address.as_str())?;
+    for token_amount in &offer.give {
+        let token_info = load_swap_token_info(deps.storage, &token_amount.token_id)?;
+        for balance in &token_amount.balances {
+            exec_change_balance(
+                deps.storage,
+                &env.block,
+                &token_amount.token_id,
+                Some(&config.contract_address),
+                Some(&info.sender),
+                &balance.amount,
+                &token_info,
+                &mut hooks,
+            )?;
+            store_transfer(
+                deps.storage,
+                &mut config,
+                &env.block,
+                &token_amount.token_id,
+                contract_canon.clone(),
+                None,
+                deps.api.addr_canonicalize(info.sender.as_str())?,
+                balance.amount,
+                None,
+            )?;
+        }
+    }
+    contr_conf_w(deps.storage).save(&config)?;
+
+    let response =
+        Response::new().set_data(to_binary(&ExecuteAnswer::AcceptSwap { status: Success })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
+}
+
+fn try_cancel_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_id: u64,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
+    let offer = load_swap_offer(deps.storage, swap_id)?;
+    if offer.creator != info.sender {
+        return Err(StdError::generic_err(format!(
+            "only the creator can cancel swap offer {}",
+            swap_id
+        )));
+    }
+    swaps_w(deps.storage).remove(&swap_id.to_be_bytes());
+
+    // return the balances held in escrow
+    let mut hooks = vec![];
+    let creator_canon = deps.api.addr_canonicalize(offer.creator.as_str())?;
+    let contract_canon = deps
+        .api
+        .addr_canonicalize(config.contract_address.as_str())?;
+    for token_amount in &offer.give {
+        let token_info = load_swap_token_info(deps.storage, &token_amount.token_id)?;
+        for balance in &token_amount.balances {
+            exec_change_balance(
+                deps.storage,
+                &env.block,
+                &token_amount.token_id,
+                Some(&config.contract_address),
+                Some(&offer.creator),
+                &balance.amount,
+                &token_info,
+                &mut hooks,
+            )?;
+            store_transfer(
+                deps.storage,
+                &mut config,
+                &env.block,
+                &token_amount.token_id,
+                contract_canon.clone(),
+                None,
+                creator_canon.clone(),
+                balance.amount,
+                None,
+            )?;
+        }
+    }
+    contr_conf_w(deps.storage).save(&config)?;
+
+    let response =
+        Response::new().set_data(to_binary(&ExecuteAnswer::CancelSwap { status: Success })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
+}
+
+fn try_set_user(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    user: Option<Addr>,
+    expires: Expiration,
+) -> StdResult<Response> {
+    let token_info = tkn_info_r(deps.storage)
+        .may_load(token_id.as_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("token_id {} does not exist", token_id)))?;
+    if !token_info.token_config.flatten().is_nft {
+        return Err(StdError::generic_err("only NFTs can have a user"));
+    }
+    if may_get_current_owner(deps.storage, &token_id)?.as_ref() != Some(&info.sender) {
+        return Err(StdError::generic_err(format!(
+            "only the owner of token_id {} can set its user",
+            token_id
+        )));
+    }
+
+    match user {
+        None => nft_users_w(deps.storage).remove(token_id.as_bytes()),
+        Some(user) => {
+            if expires.is_expired(&env.block) {
+                return Err(StdError::generic_err("user role expiry has already passed"));
+            }
+            let nft_user = NftUser {
+                user: deps.api.addr_validate(user.as_str())?,
+                expires,
+            };
+            nft_users_w(deps.storage).save(token_id.as_bytes(), &nft_user)?;
+        }
+    }
+
+    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::SetUser { status: Success })?))
+}
+
+// in the base specifications, this function can be performed by token owner only
+fn try_burn_tokens(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    burn_tokens: Vec<TokenAmount>,
+    memo: Option<String>,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
+
+    // burn tokens
+    let mut hooks = vec![];
+    for burn_token in burn_tokens {
+        let token_info_op = tkn_info_r(deps.storage).may_load(burn_token.token_id.as_bytes())?;
+
+        if token_info_op.is_none() {
+            return Err(StdError::generic_err(
+                "token_id does not exist. Cannot burn non-existent `token_ids`. Use `curate_token_ids` to create tokens on new `token_ids`"
+            ));
+        }
+
+        let token_info = token_info_op.clone().unwrap();
+
+        if !token_info.token_config.flatten().enable_burn {
+            return Err(StdError::generic_err(
+                "burning is not enabled for this token_id",
+            ));
+        }
+
+        // remove balances
+        for rem_balance in burn_token.balances {
+            // in base specification, burner MUST be the owner
+            if rem_balance.address != info.sender {
+                return Err(StdError::generic_err(format!(
+                    "you do not have permission to burn {} tokens from address {}",
+                    rem_balance.amount, rem_balance.address
+                )));
+            }
+
+            exec_change_balance(
+                deps.storage,
+                &env.block,
+                &burn_token.token_id,
+                Some(&rem_balance.address),
+                None,
+                &rem_balance.amount,
+                &token_info,
+                &mut hooks,
+            )?;
+
+            // store burn_token
+            store_burn(
+                deps.storage,
+                &mut config,
+                &env.block,
+                &burn_token.token_id,
+                // in base specification, burner MUST be the owner
+                None,
+                deps.api.addr_canonicalize(rem_balance.address.as_str())?,
+                rem_balance.amount,
+                memo.clone(),
+            )?;
+        }
+    }
+
+    contr_conf_w(deps.storage).save(&config)?;
+
+    let response =
+        Response::new().set_data(to_binary(&ExecuteAnswer::BurnTokens { status: Success })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
+}
+
+fn try_change_metadata(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    public_metadata: Option<Metadata>,
+    private_metadata: Option<Metadata>,
+) -> StdResult<Response> {
+    let tkn_info_op = tkn_info_r(deps.storage).may_load(token_id.as_bytes())?;
+    let tkn_conf = match tkn_info_op.clone() {
+        None => {
+            return Err(StdError::generic_err(format!(
+                "token_id {} does not exist",
+                token_id
+            )))
+        }
+        Some(i) => i.token_config.flatten(),
+    };
+
+    // define variables for control flow
+    let owner = may_get_current_owner(deps.storage, &token_id)?;
+    let is_owner = match owner {
+        Some(owner_addr) => owner_addr == info.sender,
+        None => false,
+    };
+
+    let is_minter = verify_minter(tkn_info_op.as_ref().unwrap(), &info).is_ok();
 
     // can sender change metadata? based on i) sender is minter or owner, ii) token_id config allows it or not
     let allow_update = is_owner && tkn_conf.owner_may_update_metadata
@@ -508,22 +1193,31 @@ fn try_change_metadata(
     )
 }
 
-#[allow(clippy::too_many_arguments)]
 fn try_transfer(
     mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    token_id: String,
-    from: Addr,
-    recipient: Addr,
-    amount: Uint256,
-    memo: Option<String>,
+    action: TransferAction,
+    entropy: Option<String>,
 ) -> StdResult<Response> {
+    let mut hooks = vec![];
     impl_transfer(
-        &mut deps, &env, &info, &token_id, &from, &recipient, amount, memo,
+        &mut deps,
+        &env,
+        &info,
+        &mut hooks,
+        &action.token_id,
+        &action.from,
+        &action.recipient,
+        action.amount,
+        action.memo,
+        action.decoys,
+        entropy.as_deref(),
     )?;
 
-    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::Transfer { status: Success })?))
+    let response =
+        Response::new().set_data(to_binary(&ExecuteAnswer::Transfer { status: Success })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
 }
 
 fn try_batch_transfer(
@@ -531,7 +1225,9 @@ fn try_batch_transfer(
     env: Env,
     info: MessageInfo,
     actions: Vec<TransferAction>,
+    entropy: Option<String>,
 ) -> StdResult<Response> {
+    let mut hooks = vec![];
     for action in actions {
         let from = deps.api.addr_validate(action.from.as_str())?;
         let recipient = deps.api.addr_validate(action.recipient.as_str())?;
@@ -539,19 +1235,21 @@ fn try_batch_transfer(
             &mut deps,
             &env,
             &info,
+            &mut hooks,
             &action.token_id,
             &from,
             &recipient,
             action.amount,
             action.memo,
+            action.decoys,
+            entropy.as_deref(),
         )?;
     }
 
-    Ok(
-        Response::new().set_data(to_binary(&ExecuteAnswer::BatchTransfer {
-            status: Success,
-        })?),
-    )
+    let response = Response::new().set_data(to_binary(&ExecuteAnswer::BatchTransfer {
+        status: Success,
+    })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
 }
 
 fn try_send(
@@ -559,15 +1257,28 @@ fn try_send(
     env: Env,
     info: MessageInfo,
     action: SendAction,
+    entropy: Option<String>,
 ) -> StdResult<Response> {
+    let contract_send_mode = contr_conf_r(deps.storage).load()?.send_mode;
+
     // set up cosmos messages
     let mut messages = vec![];
+    let mut hooks = vec![];
 
-    impl_send(&mut deps, &env, &info, &mut messages, action)?;
+    impl_send(
+        &mut deps,
+        &env,
+        &info,
+        &mut messages,
+        &mut hooks,
+        action,
+        contract_send_mode,
+        entropy.as_deref(),
+    )?;
 
     let data = to_binary(&ExecuteAnswer::Send { status: Success })?;
-    let res = Response::new().add_messages(messages).set_data(data);
-    Ok(res)
+    let res = Response::new().add_submessages(messages).set_data(data);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, res)
 }
 
 fn try_batch_send(
@@ -575,17 +1286,30 @@ fn try_batch_send(
     env: Env,
     info: MessageInfo,
     actions: Vec<SendAction>,
+    entropy: Option<String>,
 ) -> StdResult<Response> {
+    let contract_send_mode = contr_conf_r(deps.storage).load()?.send_mode;
+
     // declare vector for cosmos messages
     let mut messages = vec![];
+    let mut hooks = vec![];
 
     for action in actions {
-        impl_send(&mut deps, &env, &info, &mut messages, action)?;
+        impl_send(
+            &mut deps,
+            &env,
+            &info,
+            &mut messages,
+            &mut hooks,
+            action,
+            contract_send_mode,
+            entropy.as_deref(),
+        )?;
     }
 
     let data = to_binary(&ExecuteAnswer::BatchSend { status: Success })?;
-    let res = Response::new().add_messages(messages).set_data(data);
-    Ok(res)
+    let res = Response::new().add_submessages(messages).set_data(data);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, res)
 }
 
 /// does not check if `token_id` exists so attacker cannot easily figure out if
@@ -604,6 +1328,18 @@ fn try_give_permission(
     transfer: Option<Uint256>,
     transfer_expiry: Option<Expiration>,
 ) -> StdResult<Response> {
+    // non-transferable tokens cannot have transfer allowances
+    if transfer.is_some_and(|amount| !amount.is_zero()) {
+        if let Some(token_info) = tkn_info_r(deps.storage).may_load(token_id.as_bytes())? {
+            if !token_info.token_config.flatten().transferable {
+                return Err(StdError::generic_err(format!(
+                    "token_id {} is not transferable, so transfer allowances cannot be given",
+                    token_id
+                )));
+            }
+        }
+    }
+
     // may_load current permission
     let permission_op =
         may_load_any_permission(deps.storage, &info.sender, &token_id, &allowed_address)?;
@@ -757,70 +1493,211 @@ fn try_set_viewing_key(
     )
 }
 
-fn try_revoke_permit(
-    deps: DepsMut,
-    _env: Env,
-    info: MessageInfo,
-    permit_name: String,
-) -> StdResult<Response> {
-    RevokedPermits::revoke_permit(
-        deps.storage,
-        PREFIX_REVOKED_PERMITS,
-        info.sender.as_ref(),
-        &permit_name,
-    );
-
-    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::RevokePermit { status: Success })?))
-}
-
-fn try_add_curators(
+fn try_create_scoped_viewing_key(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    add_curators: Vec<Addr>,
+    label: String,
+    scopes: Vec<ViewingKeyScope>,
+    expiration: Option<Expiration>,
+    entropy: String,
 ) -> StdResult<Response> {
-    let mut config = contr_conf_r(deps.storage).load()?;
-
-    // verify admin
-    verify_admin(&config, &info)?;
-
-    // add curators
-    for curator in add_curators {
-        config.curators.push(curator);
+    let mut keys = scoped_vks_r(deps.storage)
+        .may_load(info.sender.as_bytes())?
+        .unwrap_or_default();
+    if label.is_empty() || keys.iter().any(|i| i.label == label) {
+        return Err(StdError::generic_err(format!(
+            "scoped viewing key label `{}` is empty or already in use",
+            label
+        )));
+    }
+    if scopes.is_empty() {
+        return Err(StdError::generic_err(
+            "scoped viewing key must have at least one scope",
+        ));
     }
-    contr_conf_w(deps.storage).save(&config)?;
 
-    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::AddCurators { status: Success })?))
+    let key = ScopedViewingKey::create(
+        deps.storage,
+        &info,
+        &env,
+        &ScopedViewingKey::account(&info.sender, &label),
+        entropy.as_ref(),
+    );
+    keys.push(ScopedViewingKeyInfo {
+        label,
+        scopes,
+        expiration: expiration.unwrap_or_default(),
+    });
+    scoped_vks_w(deps.storage).save(info.sender.as_bytes(), &keys)?;
+
+    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::CreateScopedViewingKey { key })?))
 }
 
-fn try_remove_curators(
+fn try_revoke_scoped_viewing_key(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    remove_curators: Vec<Addr>,
+    label: String,
 ) -> StdResult<Response> {
-    let mut config = contr_conf_r(deps.storage).load()?;
-
-    // verify admin
-    verify_admin(&config, &info)?;
-
-    // remove curators
-    for curator in remove_curators {
-        config.curators.retain(|x| x != &curator);
+    let mut keys = scoped_vks_r(deps.storage)
+        .may_load(info.sender.as_bytes())?
+        .unwrap_or_default();
+    let len = keys.len();
+    keys.retain(|i| i.label != label);
+    if keys.len() == len {
+        return Err(StdError::generic_err(format!(
+            "no scoped viewing key with label `{}`",
+            label
+        )));
     }
-    contr_conf_w(deps.storage).save(&config)?;
+    scoped_vks_w(deps.storage).save(info.sender.as_bytes(), &keys)?;
 
     Ok(
-        Response::new().set_data(to_binary(&ExecuteAnswer::RemoveCurators {
+        Response::new().set_data(to_binary(&ExecuteAnswer::RevokeScopedViewingKey {
             status: Success,
         })?),
     )
 }
 
-// fn try_add_minters(
-//     deps: DepsMut,
-//     _env: Env,
-//     info: MessageInfo,
+fn try_transfer_with_permit(
+    mut deps: DepsMut,
+    env: Env,
+    permit: Permit<TransferPermitPayload>,
+) -> StdResult<Response> {
+    let (signer, payload) = validate_execute_permit(deps.as_ref(), &env, &permit)?;
+    let (nonce, expiration) = (payload.nonce, payload.expiration);
+    check_permit_nonce(deps.as_ref(), &env, &signer, nonce, &expiration)?;
+
+    // transfer as if the signer had sent the message
+    let signer_info = MessageInfo {
+        sender: signer.clone(),
+        funds: vec![],
+    };
+    let recipient = deps.api.addr_validate(payload.recipient.as_str())?;
+    let mut hooks = vec![];
+    impl_transfer(
+        &mut deps,
+        &env,
+        &signer_info,
+        &mut hooks,
+        &payload.token_id,
+        &signer,
+        &recipient,
+        payload.amount,
+        payload.memo,
+        None,
+        None,
+    )?;
+    permit_nonces_w(deps.storage).save(signer.as_bytes(), &(nonce + 1))?;
+
+    let response = Response::new().set_data(to_binary(&ExecuteAnswer::TransferWithPermit {
+        status: Success,
+    })?);
+    add_transfer_hooks(deps.storage, &signer, hooks, response)
+}
+
+fn try_give_permission_with_permit(
+    deps: DepsMut,
+    env: Env,
+    permit: Permit<GivePermissionPermitPayload>,
+) -> StdResult<Response> {
+    let (signer, payload) = validate_execute_permit(deps.as_ref(), &env, &permit)?;
+    let (nonce, expiration) = (payload.nonce, payload.expiration);
+    check_permit_nonce(deps.as_ref(), &env, &signer, nonce, &expiration)?;
+    permit_nonces_w(deps.storage).save(signer.as_bytes(), &(nonce + 1))?;
+
+    // give permission as if the signer had sent the message
+    let signer_info = MessageInfo {
+        sender: signer.clone(),
+        funds: vec![],
+    };
+    let allowed_address = deps.api.addr_validate(payload.allowed_address.as_str())?;
+    let response = try_give_permission(
+        deps,
+        env,
+        signer_info,
+        allowed_address,
+        payload.token_id,
+        payload.view_balance,
+        payload.view_balance_expiry,
+        payload.view_private_metadata,
+        payload.view_private_metadata_expiry,
+        payload.transfer,
+        payload.transfer_expiry,
+    )?;
+
+    Ok(
+        response.set_data(to_binary(&ExecuteAnswer::GivePermissionWithPermit {
+            status: Success,
+        })?),
+    )
+}
+
+fn try_revoke_permit(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    permit_name: String,
+) -> StdResult<Response> {
+    RevokedPermits::revoke_permit(
+        deps.storage,
+        PREFIX_REVOKED_PERMITS,
+        info.sender.as_ref(),
+        &permit_name,
+    );
+
+    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::RevokePermit { status: Success })?))
+}
+
+fn try_add_curators(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    add_curators: Vec<Addr>,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
+
+    // verify admin
+    verify_admin(&config, &info)?;
+
+    // add curators
+    for curator in add_curators {
+        config.curators.push(curator);
+    }
+    contr_conf_w(deps.storage).save(&config)?;
+
+    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::AddCurators { status: Success })?))
+}
+
+fn try_remove_curators(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    remove_curators: Vec<Addr>,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
+
+    // verify admin
+    verify_admin(&config, &info)?;
+
+    // remove curators
+    for curator in remove_curators {
+        config.curators.retain(|x| x != &curator);
+    }
+    contr_conf_w(deps.storage).save(&config)?;
+
+    Ok(
+        Response::new().set_data(to_binary(&ExecuteAnswer::RemoveCurators {
+            status: Success,
+        })?),
+    )
+}
+
+// fn try_add_minters(
+//     deps: DepsMut,
+//     _env: Env,
+//     info: MessageInfo,
 //     token_id: String,
 //     add_minters: Vec<Addr>,
 // ) -> StdResult<Response> {
@@ -962,75 +1839,1083 @@ fn try_register_receive(
         .set_data(data))
 }
 
-/////////////////////////////////////////////////////////////////////////////////
-// Private functions
-/////////////////////////////////////////////////////////////////////////////////
+fn try_set_uri_template(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    uri_template: Option<String>,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
 
-fn pad_response(response: StdResult<Response>) -> StdResult<Response> {
-    response.map(|mut response| {
-        response.data = response.data.map(|mut data| {
-            space_pad(&mut data.0, RESPONSE_BLOCK_SIZE);
-            data
-        });
-        response
-    })
+    // verify admin or curator
+    if verify_admin(&config, &info).is_err() && verify_curator(&config, &info).is_err() {
+        return Err(StdError::generic_err(
+            "Only the admin or curators can change the uri template",
+        ));
+    }
+
+    if let Some(template) = &uri_template {
+        verify_uri_template(template)?;
+    }
+
+    config.uri_template = uri_template;
+    contr_conf_w(deps.storage).save(&config)?;
+
+    Ok(
+        Response::new().set_data(to_binary(&ExecuteAnswer::SetUriTemplate {
+            status: Success,
+        })?),
+    )
 }
 
-fn is_valid_name(name: &str) -> bool {
-    let len = name.len();
-    (3..=30).contains(&len)
+fn try_set_max_batch_queries(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    max_batch_queries: u32,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
+
+    // verify admin
+    verify_admin(&config, &info)?;
+
+    config.max_batch_queries = max_batch_queries;
+    contr_conf_w(deps.storage).save(&config)?;
+
+    Ok(
+        Response::new().set_data(to_binary(&ExecuteAnswer::SetMaxBatchQueries {
+            status: Success,
+        })?),
+    )
+}
+
+fn try_set_controller(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    controller: Option<Controller>,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
+
+    // verify admin
+    verify_admin(&config, &info)?;
+
+    config.controller = match controller {
+        Some(controller) => Some(Controller {
+            address: deps.api.addr_validate(controller.address.as_str())?,
+            code_hash: controller.code_hash,
+        }),
+        None => None,
+    };
+    contr_conf_w(deps.storage).save(&config)?;
+
+    Ok(
+        Response::new().set_data(to_binary(&ExecuteAnswer::SetController {
+            status: Success,
+        })?),
+    )
+}
+
+fn try_set_transfer_hooks(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_ids: Vec<String>,
+    enabled: bool,
+) -> StdResult<Response> {
+    let config = contr_conf_r(deps.storage).load()?;
+
+    // verify admin
+    verify_admin(&config, &info)?;
+
+    // check all token_ids or series_ids exist before changing any of them
+    for token_id in &token_ids {
+        if series_info_r(deps.storage)
+            .may_load(token_id.as_bytes())?
+            .is_none()
+        {
+            verify_token_id_exists(&config, token_id)?;
+        }
+    }
+    for token_id in token_ids {
+        transfer_hooks_w(deps.storage).save(token_id.as_bytes(), &enabled)?;
+    }
+
+    Ok(
+        Response::new().set_data(to_binary(&ExecuteAnswer::SetTransferHooks {
+            status: Success,
+        })?),
+    )
+}
+
+fn try_set_send_mode(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    send_mode: SendMode,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
+
+    // verify admin
+    verify_admin(&config, &info)?;
+
+    config.send_mode = send_mode;
+    contr_conf_w(deps.storage).save(&config)?;
+
+    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::SetSendMode { status: Success })?))
+}
+
+fn try_set_frozen(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: Addr,
+    token_id: Option<String>,
+    frozen: bool,
+) -> StdResult<Response> {
+    let config = contr_conf_r(deps.storage).load()?;
+
+    // verify admin
+    verify_admin(&config, &info)?;
+
+    let address = deps.api.addr_validate(address.as_str())?;
+    let address_key = to_binary(&address)?;
+    match &token_id {
+        Some(token_id) => {
+            verify_token_id_exists(&config, token_id)?;
+            tkn_frozen_w(deps.storage, token_id).save(address_key.as_slice(), &frozen)?;
+        }
+        None => frozen_w(deps.storage).save(address_key.as_slice(), &frozen)?,
+    }
+
+    append_compliance_event(
+        deps.storage,
+        &env.block,
+        &info.sender,
+        ComplianceAction::SetFrozen {
+            address,
+            token_id,
+            frozen,
+        },
+    )?;
+
+    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::SetFrozen { status: Success })?))
+}
+
+fn try_set_transfer_list_mode(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    mode: TransferListMode,
+) -> StdResult<Response> {
+    let config = contr_conf_r(deps.storage).load()?;
+
+    // verify admin
+    verify_admin(&config, &info)?;
+    verify_token_id_exists(&config, &token_id)?;
+
+    transfer_list_mode_w(deps.storage).save(token_id.as_bytes(), &mode)?;
+    append_compliance_event(
+        deps.storage,
+        &env.block,
+        &info.sender,
+        ComplianceAction::SetTransferListMode { token_id, mode },
+    )?;
+
+    Ok(
+        Response::new().set_data(to_binary(&ExecuteAnswer::SetTransferListMode {
+            status: Success,
+        })?),
+    )
+}
+
+fn try_update_transfer_list(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    add: Vec<Addr>,
+    remove: Vec<Addr>,
+) -> StdResult<Response> {
+    let config = contr_conf_r(deps.storage).load()?;
+
+    // verify admin
+    verify_admin(&config, &info)?;
+    verify_token_id_exists(&config, &token_id)?;
+
+    let add = add
+        .iter()
+        .map(|address| deps.api.addr_validate(address.as_str()))
+        .collect::<StdResult<Vec<Addr>>>()?;
+    let remove = remove
+        .iter()
+        .map(|address| deps.api.addr_validate(address.as_str()))
+        .collect::<StdResult<Vec<Addr>>>()?;
+    for address in &add {
+        transfer_list_w(deps.storage, &token_id).save(to_binary(address)?.as_slice(), &true)?;
+    }
+    for address in &remove {
+        transfer_list_w(deps.storage, &token_id).remove(to_binary(address)?.as_slice());
+    }
+
+    append_compliance_event(
+        deps.storage,
+        &env.block,
+        &info.sender,
+        ComplianceAction::UpdateTransferList {
+            token_id,
+            add,
+            remove,
+        },
+    )?;
+
+    Ok(
+        Response::new().set_data(to_binary(&ExecuteAnswer::UpdateTransferList {
+            status: Success,
+        })?),
+    )
+}
+
+fn try_set_auto_curate_template(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    template: AutoCurateTemplate,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
+
+    // verify admin
+    verify_admin(&config, &info)?;
+
+    verify_auto_curate_template(&template, &config.lb_pair_info)?;
+
+    config.auto_curate_template = template;
+    contr_conf_w(deps.storage).save(&config)?;
+
+    Ok(
+        Response::new().set_data(to_binary(&ExecuteAnswer::SetAutoCurateTemplate {
+            status: Success,
+        })?),
+    )
+}
+
+fn try_lower_max_supply(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    max_supply: Option<Uint256>,
+) -> StdResult<Response> {
+    let config = contr_conf_r(deps.storage).load()?;
+    let mut tkn_info = match tkn_info_r(deps.storage).may_load(token_id.as_bytes())? {
+        None => {
+            return Err(StdError::generic_err(format!(
+                "token_id {} does not exist",
+                token_id
+            )))
+        }
+        Some(i) => i,
+    };
+
+    // verify admin, curator or minter of token_id
+    if verify_admin(&config, &info).is_err()
+        && verify_curator(&config, &info).is_err()
+        && verify_minter(&tkn_info, &info).is_err()
+    {
+        return Err(StdError::generic_err(
+            "Only the admin, curators or minters can lower the max supply",
+        ));
+    }
+
+    let mut flattened_token_config = tkn_info.token_config.flatten();
+    if flattened_token_config.is_nft {
+        return Err(StdError::generic_err("cannot change max supply of an NFT"));
+    }
+
+    let total_supply = tkn_tot_supply_r(deps.storage).load(token_id.as_bytes())?;
+    let new_max_supply = match max_supply {
+        Some(i) => i,
+        // lock total supply permanently
+        None => {
+            flattened_token_config.enable_mint = false;
+            total_supply
+        }
+    };
+
+    // one-way: cannot raise the cap, or set it below the current total supply
+    if let Some(old_max_supply) = flattened_token_config.max_supply {
+        if new_max_supply > old_max_supply {
+            return Err(StdError::generic_err(format!(
+                "max supply can only be lowered. Current max supply: {}",
+                old_max_supply
+            )));
+        }
+    }
+    if new_max_supply < total_supply {
+        return Err(StdError::generic_err(format!(
+            "max supply cannot be lower than the current total supply of {}",
+            total_supply
+        )));
+    }
+
+    // save token info with new max supply
+    flattened_token_config.max_supply = Some(new_max_supply);
+    tkn_info.token_config = flattened_token_config.to_enum();
+    tkn_info_w(deps.storage).save(token_id.as_bytes(), &tkn_info)?;
+
+    Ok(
+        Response::new().set_data(to_binary(&ExecuteAnswer::LowerMaxSupply {
+            status: Success,
+        })?),
+    )
+}
+
+fn try_curate_series(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    series: Vec<SeriesInfoMsg>,
+) -> StdResult<Response> {
+    let config = contr_conf_r(deps.storage).load()?;
+    // check if sender is a curator
+    verify_curator(&config, &info)?;
+
+    for series_info in series {
+        // check: series_id is not already used by a series or token_id
+        if series_info.series_id.contains('#') {
+            return Err(StdError::generic_err("series_id cannot contain `#`"));
+        }
+        if series_info_r(deps.storage)
+            .may_load(series_info.series_id.as_bytes())?
+            .is_some()
+            || tkn_info_r(deps.storage)
+                .may_load(series_info.series_id.as_bytes())?
+                .is_some()
+        {
+            return Err(StdError::generic_err(
+                "series_id already exists. Try a different id String",
+            ));
+        }
+        // check: no existing token_id takes the id of one of the series' editions
+        if config
+            .token_id_list
+            .iter()
+            .any(|token_id| edition_of(token_id).map(|(id, _)| id) == Some(&series_info.series_id))
+        {
+            return Err(StdError::generic_err(format!(
+                "token_ids of the form {}#<edition> already exist",
+                series_info.series_id
+            )));
+        }
+
+        // check: series is an NFT configuration with at least one edition
+        if !series_info.token_config.flatten().is_nft {
+            return Err(StdError::generic_err(format!(
+                "series {} must have an NFT token_config",
+                series_info.series_id
+            )));
+        }
+        if series_info.max_editions == 0 {
+            return Err(StdError::generic_err("max_editions must be greater than 0"));
+        }
+
+        // Check name, symbol
+        if !is_valid_name(&series_info.name) {
+            return Err(StdError::generic_err(
+                "Name is not in the expected format (3-30 UTF-8 bytes)",
+            ));
+        }
+        if !is_valid_symbol(&series_info.symbol) {
+            return Err(StdError::generic_err(
+                "Ticker symbol is not in expected format [A-Z]{3,6}",
+            ));
+        }
+
+        series_info_w(deps.storage).save(
+            series_info.series_id.as_bytes(),
+            &series_info.to_store(&info.sender),
+        )?;
+    }
+
+    Ok(Response::new().set_data(to_binary(&ExecuteAnswer::CurateSeries { status: Success })?))
+}
+
+fn try_mint_editions(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    series_id: String,
+    recipients: Vec<Addr>,
+    memo: Option<String>,
+) -> StdResult<Response> {
+    let mut config = contr_conf_r(deps.storage).load()?;
+    let mut series_info = match series_info_r(deps.storage).may_load(series_id.as_bytes())? {
+        None => {
+            return Err(StdError::generic_err(format!(
+                "series {} does not exist",
+                series_id
+            )))
+        }
+        Some(i) => i,
+    };
+
+    // check if sender is a minter of the series
+    if !series_info
+        .token_config
+        .flatten()
+        .minters
+        .contains(&info.sender)
+    {
+        return Err(StdError::generic_err(format!(
+            "Only minters are allowed to mint editions of series {}",
+            series_id
+        )));
+    }
+
+    // check: enough editions remain to mint one to each recipient
+    let remaining_editions = series_info.max_editions - series_info.minted_editions;
+    if recipients.len() > remaining_editions as usize {
+        return Err(StdError::generic_err(format!(
+            "cannot mint {} editions of series {}. Only {} of {} editions remain",
+            recipients.len(),
+            series_id,
+            remaining_editions,
+            series_info.max_editions
+        )));
+    }
+
+    let transfer_hooks = transfer_hooks_r(deps.storage)
+        .may_load(series_id.as_bytes())?
+        .unwrap_or(false);
+    let mut token_ids: Vec<String> = vec![];
+    let mut messages = vec![];
+    let mut hooks = vec![];
+    for recipient in recipients {
+        series_info.minted_editions += 1;
+
+        let curate_token = CurateTokenId {
+            token_info: series_info.edition_token_info(series_info.minted_editions),
+            balances: vec![TokenIdBalance {
+                address: recipient,
+                amount: Uint256::from(1_u64),
+            }
+            .into()],
+            backing: None,
+            transfer_hooks,
+        };
+        token_ids.push(curate_token.token_info.token_id.clone());
+        exec_curate_token_id(
+            &mut deps,
+            &env,
+            &info,
+            &mut config,
+            &mut messages,
+            &mut hooks,
+            curate_token,
+            memo.clone(),
+        )?;
+    }
+
+    series_info_w(deps.storage).save(series_id.as_bytes(), &series_info)?;
+    contr_conf_w(deps.storage).save(&config)?;
+
+    let response = Response::new()
+        .add_submessages(messages)
+        .set_data(to_binary(&ExecuteAnswer::MintEditions { token_ids })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
+}
+
+fn try_deposit(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    memo: Option<String>,
+) -> StdResult<Response> {
+    let (token_info, backing) = load_backed_token(deps.storage, &token_id)?;
+
+    // check that only the backing coin is deposited
+    let deposit = match &backing.asset {
+        BackingAsset::Native { denom } => {
+            if info.funds.len() != 1
+                || &info.funds[0].denom != denom
+                || info.funds[0].amount.is_zero()
+            {
+                return Err(StdError::generic_err(format!(
+                    "Deposit requires a non-zero amount of {} and no other coins",
+                    denom
+                )));
+            }
+            Uint256::from(info.funds[0].amount)
+        }
+        BackingAsset::Snip20 { .. } => {
+            return Err(StdError::generic_err(format!(
+                "token_id {} is backed by a SNIP20 token. Use SNIP20 `Send` to deposit",
+                token_id
+            )))
+        }
+    };
+
+    let mut hooks = vec![];
+    impl_deposit(
+        &mut deps,
+        &env,
+        &mut hooks,
+        &token_info,
+        &backing,
+        &info.sender,
+        deposit,
+        memo,
+    )?;
+
+    let response =
+        Response::new().set_data(to_binary(&ExecuteAnswer::Deposit { status: Success })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
+}
+
+/// SNIP20 `Receive` callback. `info.sender` is the SNIP20 contract and `from` is the owner of the
+/// SNIP20 tokens sent
+#[allow(clippy::too_many_arguments)]
+fn try_receive(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from: Addr,
+    amount: Uint128,
+    memo: Option<String>,
+    msg: Option<Binary>,
+) -> StdResult<Response> {
+    let action: Snip20ReceiveAction = match msg {
+        Some(i) => from_binary(&i)?,
+        None => {
+            return Err(StdError::generic_err(
+                "SNIP20 `Send` to this contract requires a `msg`",
+            ))
+        }
+    };
+
+    let mut hooks = vec![];
+    match action {
+        Snip20ReceiveAction::Deposit {
+            token_id,
+            memo: deposit_memo,
+        } => {
+            let (token_info, backing) = load_backed_token(deps.storage, &token_id)?;
+
+            // check that the SNIP20 tokens received are the ones backing token_id
+            match &backing.asset {
+                BackingAsset::Snip20 { contract, .. } if contract == &info.sender => (),
+                _ => {
+                    return Err(StdError::generic_err(format!(
+                        "token_id {} is not backed by SNIP20 contract {}",
+                        token_id, info.sender
+                    )))
+                }
+            }
+            if amount.is_zero() {
+                return Err(StdError::generic_err(
+                    "Deposit amount must be greater than 0",
+                ));
+            }
+
+            impl_deposit(
+                &mut deps,
+                &env,
+                &mut hooks,
+                &token_info,
+                &backing,
+                &from,
+                Uint256::from(amount),
+                deposit_memo.or(memo),
+            )?;
+        }
+    }
+
+    // the hooks' sender is the owner of the SNIP20 tokens, rather than the SNIP20 contract
+    let response =
+        Response::new().set_data(to_binary(&ExecuteAnswer::Receive { status: Success })?);
+    add_transfer_hooks(deps.storage, &from, hooks, response)
+}
+
+fn try_redeem(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    amount: Uint256,
+    memo: Option<String>,
+) -> StdResult<Response> {
+    let (token_info, backing) = load_backed_token(deps.storage, &token_id)?;
+
+    // check that shares can be redeemed for a whole amount of collateral
+    if amount.is_zero() || !(amount % backing.shares_per_unit).is_zero() {
+        return Err(StdError::generic_err(format!(
+            "redeem amount must be a non-zero multiple of {}",
+            backing.shares_per_unit
+        )));
+    }
+    let redeemed = amount / backing.shares_per_unit;
+
+    // burn shares of redeemer
+    let mut hooks = vec![];
+    exec_change_balance(
+        deps.storage,
+        &env.block,
+        &token_id,
+        Some(&info.sender),
+        None,
+        &amount,
+        &token_info,
+        &mut hooks,
+    )?;
+    let reserve = tkn_reserve_r(deps.storage).load(token_id.as_bytes())?;
+    let new_reserve = reserve
+        .checked_sub(redeemed)
+        .map_err(|_e| StdError::generic_err("insufficient reserve"))?;
+    tkn_reserve_w(deps.storage).save(token_id.as_bytes(), &new_reserve)?;
+
+    // store burn
+    let mut config = contr_conf_r(deps.storage).load()?;
+    store_burn(
+        deps.storage,
+        &mut config,
+        &env.block,
+        &token_id,
+        None,
+        deps.api.addr_canonicalize(info.sender.as_str())?,
+        amount,
+        memo,
+    )?;
+    contr_conf_w(deps.storage).save(&config)?;
+
+    // return collateral
+    let message = asset_transfer_msg(backing.asset, &info.sender, Uint128::try_from(redeemed)?)?;
+
+    let response = Response::new()
+        .add_message(message)
+        .set_data(to_binary(&ExecuteAnswer::Redeem { status: Success })?);
+    add_transfer_hooks(deps.storage, &info.sender, hooks, response)
+}
+
+fn try_distribute_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    asset: BackingAsset,
+    amount: Uint128,
+) -> StdResult<Response> {
+    if tkn_info_r(deps.storage)
+        .may_load(token_id.as_bytes())?
+        .is_none()
+    {
+        return Err(StdError::generic_err(format!(
+            "token_id {} does not exist",
+            token_id
+        )));
+    }
+    if amount.is_zero() {
+        return Err(StdError::generic_err(
+            "reward amount must be greater than 0",
+        ));
+    }
+
+    // collect the rewards
+    let mut messages: Vec<CosmosMsg> = vec![];
+    match &asset {
+        BackingAsset::Native { denom } => {
+            if info.funds.len() != 1
+                || &info.funds[0].denom != denom
+                || info.funds[0].amount != amount
+            {
+                return Err(StdError::generic_err(format!(
+                    "DistributeRewards requires {}{} attached and no other coins",
+                    amount, denom
+                )));
+            }
+        }
+        BackingAsset::Snip20 {
+            contract,
+            code_hash,
+        } => messages.push(
+            Snip20HandleMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount,
+                memo: None,
+                padding: None,
+            }
+            .into_cosmos_msg(code_hash.clone(), contract.clone())?,
+        ),
+    }
+
+    let total_supply = tkn_tot_supply_r(deps.storage).load(token_id.as_bytes())?;
+    add_rewards(
+        deps.storage,
+        &token_id,
+        &asset,
+        Uint256::from(amount),
+        total_supply,
+    )?;
+
+    Ok(Response::new().add_messages(messages).set_data(to_binary(
+        &ExecuteAnswer::DistributeRewards { status: Success },
+    )?))
+}
+
+fn try_claim_rewards(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> StdResult<Response> {
+    let balance = balances_r(deps.storage, &token_id)
+        .may_load(to_binary(&info.sender)?.as_slice())?
+        .unwrap_or_default();
+    let rewards = take_rewards(deps.storage, &token_id, &info.sender, balance)?;
+    if rewards.is_empty() {
+        return Err(StdError::generic_err(format!(
+            "no rewards to claim for token_id {}",
+            token_id
+        )));
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for reward in rewards {
+        messages.push(asset_transfer_msg(
+            reward.asset,
+            &info.sender,
+            Uint128::try_from(reward.amount)?,
+        )?);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .set_data(to_binary(&ExecuteAnswer::ClaimRewards { status: Success })?))
+}
+
+/////////////////////////////////////////////////////////////////////////////////
+// Private functions
+/////////////////////////////////////////////////////////////////////////////////
+
+fn pad_response(response: StdResult<Response>) -> StdResult<Response> {
+    response.map(|mut response| {
+        response.data = response.data.map(|mut data| {
+            space_pad(&mut data.0, RESPONSE_BLOCK_SIZE);
+            data
+        });
+        response
+    })
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let len = name.len();
+    (3..=30).contains(&len)
+}
+
+fn is_valid_symbol(symbol: &str) -> bool {
+    let len = symbol.len();
+    let len_is_valid = (3..=6).contains(&len);
+
+    // len_is_valid && symbol.bytes().all(|byte| (b'A'..=b'Z').contains(&byte))
+    len_is_valid && symbol.bytes().all(|byte| byte.is_ascii_uppercase())
+}
+
+/// splits a token_id of the form `<series_id>#<edition>` into its series_id and edition
+fn edition_of(token_id: &str) -> Option<(&str, u32)> {
+    let (series_id, edition) = token_id.rsplit_once('#')?;
+    Some((series_id, edition.parse().ok()?))
+}
+
+/// checks that a token_id curated outside `MintEditions` does not take the id of an edition of an
+/// existing series
+fn verify_not_edition_id(storage: &dyn Storage, token_id: &str) -> StdResult<()> {
+    if let Some((series_id, edition)) = edition_of(token_id) {
+        if series_info_r(storage)
+            .may_load(series_id.as_bytes())?
+            .is_some()
+        {
+            return Err(StdError::generic_err(format!(
+                "token_id {} is reserved for edition {} of series {}",
+                token_id, edition, series_id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// checks that an enabled auto curate template creates fungible token_ids with a valid name, symbol
+/// and decimals. The name and symbol are checked as rendered for a sample token_id
+fn verify_auto_curate_template(template: &AutoCurateTemplate, lb_pair: &LbPair) -> StdResult<()> {
+    if !template.enabled {
+        return Ok(());
+    }
+
+    let sample = template.to_token_info_msg(lb_pair, "0");
+    if sample.token_config.flatten().is_nft {
+        return Err(StdError::generic_err(
+            "the auto curate template cannot create NFTs",
+        ));
+    }
+    if !is_valid_name(&sample.name) {
+        return Err(StdError::generic_err(format!(
+            "auto curate template name {} is not in the expected format (3-30 UTF-8 bytes)",
+            sample.name
+        )));
+    }
+    if !is_valid_symbol(&sample.symbol) {
+        return Err(StdError::generic_err(format!(
+            "auto curate template symbol {} is not in the expected format [A-Z]{{3,6}}",
+            sample.symbol
+        )));
+    }
+    if sample.token_config.flatten().decimals > 18 {
+        return Err(StdError::generic_err("Decimals must not exceed 18"));
+    }
+
+    Ok(())
+}
+
+/// checks that a uri template has an `{id}` placeholder to substitute
+fn verify_uri_template(uri_template: &str) -> StdResult<()> {
+    if !uri_template.contains("{id}") {
+        return Err(StdError::generic_err(
+            "uri template must contain the `{id}` placeholder",
+        ));
+    }
+    Ok(())
+}
+
+fn verify_admin(contract_config: &ContractConfig, info: &MessageInfo) -> StdResult<()> {
+    let admin_op = &contract_config.admin;
+    match admin_op {
+        Some(admin) => {
+            if admin != &info.sender {
+                return Err(StdError::generic_err("This is an admin function"));
+            }
+        }
+        None => return Err(StdError::generic_err("This contract has no admin")),
+    }
+
+    Ok(())
+}
+
+/// loads the token info of a token_id that is part of a swap offer. Non-transferable token_ids
+/// cannot be swapped
+fn load_swap_token_info(storage: &dyn Storage, token_id: &str) -> StdResult<StoredTokenInfo> {
+    let token_info = tkn_info_r(storage)
+        .may_load(token_id.as_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("token_id {} does not exist", token_id)))?;
+    if !token_info.token_config.flatten().transferable {
+        return Err(StdError::generic_err(format!(
+            "token_id {} is not transferable",
+            token_id
+        )));
+    }
+
+    Ok(token_info)
+}
+
+fn load_swap_offer(storage: &dyn Storage, swap_id: u64) -> StdResult<SwapOffer> {
+    swaps_r(storage)
+        .may_load(&swap_id.to_be_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("swap offer {} does not exist", swap_id)))
+}
+
+/// verifies that a token_id has been curated
+fn verify_token_id_exists(contract_config: &ContractConfig, token_id: &str) -> StdResult<()> {
+    if !contract_config
+        .token_id_list
+        .iter()
+        .any(|existing| existing == token_id)
+    {
+        return Err(StdError::generic_err(format!(
+            "token_id {} does not exist",
+            token_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// verifies if sender is a curator
+fn verify_curator(contract_config: &ContractConfig, info: &MessageInfo) -> StdResult<()> {
+    let curators = &contract_config.curators;
+    if !curators.contains(&info.sender) {
+        return Err(StdError::generic_err(
+            "Only curators are allowed to curate token_ids",
+        ));
+    }
+    Ok(())
+}
+
+// /// verifies if sender is the address that curated the token_id.
+// /// Not part of base specifications, but function left here for potential use.
+// /// If this additional feature is implemented, it is important to ensure that the instantiator
+// /// still has the ability to set initial balances without later being able to change minters.
+// fn verify_curator_of_token_id(
+//     token_info: &StoredTokenInfo,
+//     env: &Env
+// ) -> StdResult<()> {
+//     let curator = &token_info.curator;
+//     if curator != &env.message.sender {
+//         return Err(StdError::generic_err(
+//             "You are not the curator of this token_id",
+//         ));
+//     }
+//     Ok(())
+// }
+
+/// mints shares of a backed token_id to the depositor and adds the deposit to the reserve
+#[allow(clippy::too_many_arguments)]
+fn impl_deposit(
+    deps: &mut DepsMut,
+    env: &Env,
+    hooks: &mut Vec<PendingTransferHook>,
+    token_info: &StoredTokenInfo,
+    backing: &TokenBacking,
+    depositor: &Addr,
+    deposit: Uint256,
+    memo: Option<String>,
+) -> StdResult<()> {
+    let token_id = &token_info.token_id;
+    let shares = deposit.checked_mul(backing.shares_per_unit)?;
+
+    // mint shares to depositor
+    exec_change_balance(
+        deps.storage,
+        &env.block,
+        token_id,
+        None,
+        Some(depositor),
+        &shares,
+        token_info,
+        hooks,
+    )?;
+    let reserve = tkn_reserve_r(deps.storage).load(token_id.as_bytes())?;
+    tkn_reserve_w(deps.storage).save(token_id.as_bytes(), &reserve.checked_add(deposit)?)?;
+
+    // store mint
+    let mut config = contr_conf_r(deps.storage).load()?;
+    store_mint(
+        deps.storage,
+        &mut config,
+        &env.block,
+        token_id,
+        deps.api.addr_canonicalize(depositor.as_str())?,
+        deps.api.addr_canonicalize(depositor.as_str())?,
+        shares,
+        memo,
+    )?;
+    contr_conf_w(deps.storage).save(&config)?;
+
+    Ok(())
+}
+
+/// validates the signature of a permit used to sign an execute message, and returns the signer
+/// and the signed payload, which must be the only entry in the permit's `permissions`
+fn validate_execute_permit<P: Permissions>(
+    deps: Deps,
+    env: &Env,
+    permit: &Permit<P>,
+) -> StdResult<(Addr, P)> {
+    let contract_address = contr_conf_r(deps.storage).load()?.contract_address;
+    let account = validate(
+        deps,
+        PREFIX_REVOKED_PERMITS,
+        permit,
+        contract_address.to_string(),
+        None,
+    )?;
+    if permit.params.chain_id != env.block.chain_id {
+        return Err(StdError::generic_err(format!(
+            "permit is for chain {}, not {}",
+            permit.params.chain_id, env.block.chain_id
+        )));
+    }
+    if permit.params.permissions.len() != 1 {
+        return Err(StdError::generic_err(
+            "permit must contain exactly one signed payload",
+        ));
+    }
+
+    Ok((
+        deps.api.addr_validate(&account)?,
+        permit.params.permissions[0].clone(),
+    ))
 }
 
-fn is_valid_symbol(symbol: &str) -> bool {
-    let len = symbol.len();
-    let len_is_valid = (3..=6).contains(&len);
+/// checks that a signed payload has not expired and uses the signer's current nonce
+fn check_permit_nonce(
+    deps: Deps,
+    env: &Env,
+    signer: &Addr,
+    nonce: u64,
+    expiration: &Expiration,
+) -> StdResult<()> {
+    if expiration.is_expired(&env.block) {
+        return Err(StdError::generic_err(format!(
+            "signed payload has expired: {}",
+            expiration
+        )));
+    }
+    let current_nonce = permit_nonces_r(deps.storage)
+        .may_load(signer.as_bytes())?
+        .unwrap_or_default();
+    if nonce != current_nonce {
+        return Err(StdError::generic_err(format!(
+            "invalid nonce {}, expected nonce {}",
+            nonce, current_nonce
+        )));
+    }
 
-    // len_is_valid && symbol.bytes().all(|byte| (b'A'..=b'Z').contains(&byte))
-    len_is_valid && symbol.bytes().all(|byte| byte.is_ascii_uppercase())
+    Ok(())
 }
 
-fn verify_admin(contract_config: &ContractConfig, info: &MessageInfo) -> StdResult<()> {
-    let admin_op = &contract_config.admin;
-    match admin_op {
-        Some(admin) => {
-            if admin != &info.sender {
-                return Err(StdError::generic_err("This is an admin function"));
-            }
+/// creates a message sending an amount of a native coin or SNIP20 token from this contract
+fn asset_transfer_msg(
+    asset: BackingAsset,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    let message: CosmosMsg = match asset {
+        BackingAsset::Native { denom } => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin { denom, amount }],
         }
-        None => return Err(StdError::generic_err("This contract has no admin")),
-    }
+        .into(),
+        BackingAsset::Snip20 {
+            contract,
+            code_hash,
+        } => Snip20HandleMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount,
+            memo: None,
+            padding: None,
+        }
+        .into_cosmos_msg(code_hash, contract)?,
+    };
 
-    Ok(())
+    Ok(message)
 }
 
-/// verifies if sender is a curator
-fn verify_curator(contract_config: &ContractConfig, info: &MessageInfo) -> StdResult<()> {
-    let curators = &contract_config.curators;
-    if !curators.contains(&info.sender) {
-        return Err(StdError::generic_err(
-            "Only curators are allowed to curate token_ids",
-        ));
+/// loads token info and backing of a token_id, returning an error if the token_id is not backed
+fn load_backed_token(
+    storage: &dyn Storage,
+    token_id: &str,
+) -> StdResult<(StoredTokenInfo, TokenBacking)> {
+    let token_info_op = tkn_info_r(storage).may_load(token_id.as_bytes())?;
+    let backing_op = tkn_backing_r(storage).may_load(token_id.as_bytes())?;
+    match (token_info_op, backing_op) {
+        (Some(token_info), Some(backing)) => Ok((token_info, backing)),
+        _ => Err(StdError::generic_err(format!(
+            "token_id {} does not exist or is not backed by collateral",
+            token_id
+        ))),
     }
-    Ok(())
 }
 
-// /// verifies if sender is the address that curated the token_id.
-// /// Not part of base specifications, but function left here for potential use.
-// /// If this additional feature is implemented, it is important to ensure that the instantiator
-// /// still has the ability to set initial balances without later being able to change minters.
-// fn verify_curator_of_token_id(
-//     token_info: &StoredTokenInfo,
-//     env: &Env
-// ) -> StdResult<()> {
-//     let curator = &token_info.curator;
-//     if curator != &env.message.sender {
-//         return Err(StdError::generic_err(
-//             "You are not the curator of this token_id",
-//         ));
-//     }
-//     Ok(())
-// }
-
 /// verifies if sender is a minter of the specific token_id
 fn verify_minter(token_info: &StoredTokenInfo, info: &MessageInfo) -> StdResult<()> {
     let minters = &token_info.token_config.flatten().minters;
@@ -1049,6 +2934,8 @@ fn exec_curate_token_id(
     env: &Env,
     info: &MessageInfo,
     config: &mut ContractConfig,
+    messages: &mut Vec<SubMsg>,
+    hooks: &mut Vec<PendingTransferHook>,
     initial_token: CurateTokenId,
     memo: Option<String>,
 ) -> StdResult<()> {
@@ -1064,7 +2951,7 @@ fn exec_curate_token_id(
 
     // check: token_id is an NFT => cannot create more than one
     if initial_token.token_info.token_config.flatten().is_nft {
-        if initial_token.balances.len() > 1 {
+        if initial_token.balances.len() != 1 {
             return Err(StdError::generic_err(format!(
                 "token_id {} is an NFT; there can only be one NFT. Balances should only have one address",
                 initial_token.token_info.token_id
@@ -1092,29 +2979,124 @@ fn exec_curate_token_id(
         return Err(StdError::generic_err("Decimals must not exceed 18"));
     }
 
-    // create and save new token info
-    tkn_info_w(deps.storage).save(
-        initial_token.token_info.token_id.as_bytes(),
-        &initial_token.token_info.to_store(&info.sender),
+    // check: shares of a backed token_id can only be minted by depositing collateral, and only
+    // burnt by redeeming it, so that the reserve always matches the total supply
+    if let Some(backing) = &initial_token.backing {
+        let flattened_token_config = initial_token.token_info.token_config.flatten();
+        if flattened_token_config.is_nft
+            || flattened_token_config.enable_mint
+            || flattened_token_config.enable_burn
+        {
+            return Err(StdError::generic_err(format!(
+                "backed token_id {} must be fungible, with enable_mint and enable_burn == false",
+                initial_token.token_info.token_id
+            )));
+        }
+        if !initial_token.balances.is_empty() {
+            return Err(StdError::generic_err(format!(
+                "backed token_id {} cannot have initial balances",
+                initial_token.token_info.token_id
+            )));
+        }
+        if backing.shares_per_unit.is_zero() {
+            return Err(StdError::generic_err(
+                "shares_per_unit must be greater than 0",
+            ));
+        }
+        // if backed by a SNIP20 token, check that the contract is a SNIP20 and register this
+        // contract to receive it
+        if let BackingAsset::Snip20 {
+            contract,
+            code_hash,
+        } = &backing.asset
+        {
+            Snip20QueryMsg::query_token_info(deps.querier, code_hash.clone(), contract.clone())
+                .map_err(|_e| {
+                    StdError::generic_err(format!(
+                        "unable to query SNIP20 token info of contract {}",
+                        contract
+                    ))
+                })?;
+            messages.push(SubMsg::new(
+                Snip20HandleMsg::RegisterReceive {
+                    code_hash: env.contract.code_hash.clone(),
+                    padding: None,
+                }
+                .into_cosmos_msg(code_hash.clone(), contract.clone())?,
+            ));
+        }
+    }
+
+    // check: initial balances do not exceed max supply
+    let mut total_supply = Uint256::from(0_u64);
+    for balance in initial_token.balances.iter() {
+        total_supply = total_supply
+            .checked_add(balance.amount)
+            .map_err(|_e| StdError::generic_err("total supply exceeds max allowed of 2^128"))?;
+    }
+    if let Some(max_supply) = initial_token.token_info.token_config.flatten().max_supply {
+        if total_supply > max_supply {
+            return Err(StdError::generic_err(format!(
+                "initial balances of token_id {} exceed the max supply of {}",
+                initial_token.token_info.token_id, max_supply
+            )));
+        }
+    }
+
+    // create receiver callbacks of the initial balances
+    for balance in initial_token.balances.iter() {
+        try_add_mint_callback(
+            deps.storage,
+            config,
+            messages,
+            &info.sender,
+            &initial_token.token_info.token_id,
+            balance,
+            memo.clone(),
+        )?;
+    }
+
+    // frozen addresses, and addresses failing the token_id's transfer list, cannot receive the
+    // initial balances. Checked before anything is saved
+    for balance in initial_token.balances.iter() {
+        check_compliance(
+            deps.storage,
+            &initial_token.token_info.token_id,
+            &balance.address,
+        )?;
+    }
+
+    // create and save new token info, and initiate total token supply
+    let token_info = initial_token.token_info.to_store(&info.sender);
+    tkn_info_w(deps.storage).save(token_info.token_id.as_bytes(), &token_info)?;
+    if initial_token.transfer_hooks {
+        transfer_hooks_w(deps.storage).save(token_info.token_id.as_bytes(), &true)?;
+    }
+    tkn_tot_supply_w(deps.storage).save(token_info.token_id.as_bytes(), &Uint256::zero())?;
+    write_supply_checkpoint(
+        deps.storage,
+        &token_info.token_id,
+        env.block.height,
+        Uint256::zero(),
+    )?;
+    write_supply_accumulator(
+        deps.storage,
+        &token_info.token_id,
+        env.block.time.seconds(),
+        Uint256::zero(),
     )?;
 
     // set initial balances and store mint history
     for balance in initial_token.balances {
-        // save new balances
-        balances_w(deps.storage, &initial_token.token_info.token_id)
-            .save(to_binary(&balance.address)?.as_slice(), &balance.amount)?;
-        // if is_nft == true, store owner of NFT
-        if initial_token.token_info.token_config.flatten().is_nft {
-            append_new_owner(
-                deps.storage,
-                &initial_token.token_info.token_id,
-                &balance.address,
-            )?;
-        }
-        // initiate total token supply
-        tkn_tot_supply_w(deps.storage).save(
-            initial_token.token_info.token_id.as_bytes(),
+        exec_change_balance(
+            deps.storage,
+            &env.block,
+            &token_info.token_id,
+            None,
+            Some(&balance.address),
             &balance.amount,
+            &token_info,
+            hooks,
         )?;
 
         // store mint_token_id
@@ -1130,6 +3112,15 @@ fn exec_curate_token_id(
         )?;
     }
 
+    // save backing and initiate reserve
+    if let Some(backing) = &initial_token.backing {
+        tkn_backing_w(deps.storage).save(initial_token.token_info.token_id.as_bytes(), backing)?;
+        tkn_reserve_w(deps.storage).save(
+            initial_token.token_info.token_id.as_bytes(),
+            &Uint256::from(0_u64),
+        )?;
+    }
+
     // push token_id to token_id_list
     config.token_id_list.push(initial_token.token_info.token_id);
 
@@ -1142,8 +3133,11 @@ fn impl_send(
     deps: &mut DepsMut,
     env: &Env,
     info: &MessageInfo,
-    messages: &mut Vec<CosmosMsg>,
+    messages: &mut Vec<SubMsg>,
+    hooks: &mut Vec<PendingTransferHook>,
     action: SendAction,
+    contract_send_mode: SendMode,
+    entropy: Option<&str>,
 ) -> StdResult<()> {
     // action variables from SendAction
     let token_id = action.token_id;
@@ -1153,31 +3147,37 @@ fn impl_send(
     let recipient_code_hash = action.recipient_code_hash;
     let msg = action.msg;
     let memo = action.memo;
+    let send_mode = contract_send_mode.max(action.send_mode.unwrap_or_default());
+
+    // create cosmos message first, so a send that cannot notify its recipient fails before any
+    // balance changes
+    try_add_receiver_api_callback(
+        deps.storage,
+        messages,
+        recipient.clone(),
+        recipient_code_hash,
+        send_mode,
+        msg,
+        info.sender.clone(),
+        token_id.clone(),
+        from.to_owned(),
+        amount,
+        memo.clone(),
+    )?;
 
     // implements transfer of tokens
     impl_transfer(
         deps,
         env,
         info,
+        hooks,
         &token_id,
         &from,
         &recipient,
         amount,
-        memo.clone(),
-    )?;
-
-    // create cosmos message
-    try_add_receiver_api_callback(
-        deps.storage,
-        messages,
-        recipient,
-        recipient_code_hash,
-        msg,
-        info.sender.clone(),
-        token_id,
-        from.to_owned(),
-        amount,
         memo,
+        action.decoys,
+        entropy,
     )?;
 
     Ok(())
@@ -1191,16 +3191,33 @@ fn impl_transfer(
     deps: &mut DepsMut,
     env: &Env,
     info: &MessageInfo,
+    hooks: &mut Vec<PendingTransferHook>,
     token_id: &str,
     from: &Addr,
     recipient: &Addr,
     amount: Uint256,
     memo: Option<String>,
+    decoys: Option<Vec<Addr>>,
+    entropy: Option<&str>,
 ) -> StdResult<()> {
+    let token_info_op = tkn_info_r(deps.storage).may_load(token_id.as_bytes())?;
+    let mut config = contr_conf_r(deps.storage).load()?;
+
+    // the admin may move non-transferable tokens without an allowance, if the token_id allows it
+    let transferable = match &token_info_op {
+        Some(token_info) => token_info.token_config.flatten().transferable,
+        None => true,
+    };
+    let admin_move = token_info_op.as_ref().is_some_and(|token_info| {
+        !transferable
+            && token_info.token_config.flatten().admin_may_transfer
+            && config.admin.as_ref() == Some(&info.sender)
+    });
+
     // check if `from` == message sender || has enough allowance to send tokens
     // perform allowance check, and may reduce allowance
     let mut throw_err = false;
-    if from != &info.sender {
+    if from != &info.sender && !admin_move {
         // may_load_active_permission() or may_load_any_permission() both work. The former performs redundancy checks, which are
         // more relevant for authenticated queries (because transfer simply won't work if there is no balance)
         let permission_op = may_load_any_permission(deps.storage, from, token_id, &info.sender)?;
@@ -1237,7 +3254,6 @@ fn impl_transfer(
     }
 
     // check that token_id exists
-    let token_info_op = tkn_info_r(deps.storage).may_load(token_id.as_bytes())?;
     if token_info_op.is_none() {
         throw_err = true
     }
@@ -1252,18 +3268,42 @@ fn impl_transfer(
         false => (),
     }
 
+    if !transferable && !admin_move {
+        return Err(StdError::generic_err(format!(
+            "token_id {} is not transferable",
+            token_id
+        )));
+    }
+
+    // access decoys before and after the real recipient, split at a random position
+    let token_info = token_info_op.unwrap();
+    let decoys = decoys
+        .unwrap_or_default()
+        .iter()
+        .map(|decoy| deps.api.addr_validate(decoy.as_str()))
+        .collect::<StdResult<Vec<Addr>>>()?;
+    let split = decoy_split(&config, env, entropy, decoys.len());
+    exec_touch_decoys(
+        deps,
+        &env.block,
+        &info.sender,
+        &token_info,
+        &decoys[..split],
+    )?;
+
     // transfer tokens
     exec_change_balance(
         deps.storage,
+        &env.block,
         token_id,
         Some(from),
         Some(recipient),
         &amount,
-        &token_info_op.unwrap(),
+        &token_info,
+        hooks,
     )?;
 
     // store transaction
-    let mut config = contr_conf_r(deps.storage).load()?;
     store_transfer(
         deps.storage,
         &mut config,
@@ -1276,10 +3316,127 @@ fn impl_transfer(
         memo,
     )?;
     contr_conf_w(deps.storage).save(&config)?;
+    exec_touch_decoys(
+        deps,
+        &env.block,
+        &info.sender,
+        &token_info,
+        &decoys[split..],
+    )?;
+
+    Ok(())
+}
+
+/// returns the position at which the real recipient is accessed among `decoy_count` decoys.
+/// Derived from the contract's prng seed, the tx count, the block's randomness and the
+/// user-provided entropy
+fn decoy_split(
+    config: &ContractConfig,
+    env: &Env,
+    entropy: Option<&str>,
+    decoy_count: usize,
+) -> usize {
+    if decoy_count == 0 {
+        return 0;
+    }
+    let mut seed = config.prng_seed.clone();
+    seed.extend_from_slice(&config.tx_cnt.to_le_bytes());
+    if let Some(random) = &env.block.random {
+        seed.extend_from_slice(random.as_slice());
+    }
+    if let Some(entropy) = entropy {
+        seed.extend_from_slice(entropy.as_bytes());
+    }
+    let hash = sha_256(&seed);
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash[..8]);
+
+    (u64::from_le_bytes(bytes) % (decoy_count as u64 + 1)) as usize
+}
+
+/// accesses the storage of decoy addresses the same way as a transfer from them, without changing
+/// any value: the transfer permission read, a zero-amount `exec_update_balance`, and the
+/// transaction history
+fn exec_touch_decoys(
+    deps: &mut DepsMut,
+    block: &BlockInfo,
+    sender: &Addr,
+    token_info: &StoredTokenInfo,
+    decoys: &[Addr],
+) -> StdResult<()> {
+    let token_id = token_info.token_id.as_str();
+    for decoy in decoys {
+        if decoy != sender {
+            may_load_any_permission(deps.storage, decoy, token_id, sender)?;
+        }
+        exec_update_balance(
+            deps.storage,
+            block,
+            token_id,
+            decoy,
+            Uint256::zero(),
+            Uint256::zero(),
+            token_info,
+        )?;
+        touch_tx_ids(deps.storage, &deps.api.addr_canonicalize(decoy.as_str())?)?;
+    }
 
     Ok(())
 }
 
+/// adds `add` to and removes `remove` from the balance of `address`, and writes every store that
+/// follows its balance. Returns the new balance. Decoys also go through this function, with zero
+/// amounts, so that they read and write the same storage as real senders and recipients
+fn exec_update_balance(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    token_id: &str,
+    address: &Addr,
+    add: Uint256,
+    remove: Uint256,
+    token_info: &StoredTokenInfo,
+) -> StdResult<Uint256> {
+    // frozen addresses, and addresses failing the token_id's transfer list, cannot send or receive
+    check_compliance(storage, token_id, address)?;
+
+    let key = to_binary(address)?;
+    let existing_bal = balances_r(storage, token_id)
+        .may_load(key.as_slice())?
+        .unwrap_or_default();
+    // tokens still locked in vesting grants cannot leave the balance
+    let locked = prune_vesting_grants(storage, block, token_id, address)?;
+    if !locked.is_zero() && existing_bal.saturating_sub(locked) < remove {
+        return Err(StdError::generic_err(format!(
+            "insufficient unlocked balance: {} tokens are still vesting",
+            locked
+        )));
+    }
+    settle_rewards(storage, token_id, address, existing_bal)?;
+    let new_bal = existing_bal
+        .checked_sub(remove)
+        .map_err(|_e| StdError::generic_err("insufficient funds"))?
+        .checked_add(add)
+        .map_err(|_e| {
+            StdError::generic_err("recipient will become too rich. Total tokens exceeds 2^128")
+        })?;
+
+    balances_w(storage, token_id).save(key.as_slice(), &new_bal)?;
+    // the contract's own balance is escrowed in swap offers: it is not a holder, and its balance
+    // history is not recorded. The config is not saved yet while instantiating
+    let is_contract = contr_conf_r(storage)
+        .may_load()?
+        .is_some_and(|config| &config.contract_address == address);
+    if !is_contract {
+        write_balance_checkpoint(storage, token_id, address, block.height, new_bal)?;
+        write_balance_accumulator(storage, token_id, address, block.time.seconds(), new_bal)?;
+        if token_info.token_config.flatten().public_holders {
+            update_holder(storage, token_id, address, new_bal)?;
+        }
+    }
+
+    Ok(new_bal)
+}
+
 /// change token balance of an existing `token_id`.
 ///
 /// Should check that `token_id` already exists before calling this function, which is not done
@@ -1291,16 +3448,21 @@ fn impl_transfer(
 /// * If is_nft == true, stores new owner of NFT
 fn exec_change_balance(
     storage: &mut dyn Storage,
+    block: &BlockInfo,
     token_id: &str,
     remove_from: Option<&Addr>,
     add_to: Option<&Addr>,
     amount: &Uint256,
     token_info: &StoredTokenInfo,
+    hooks: &mut Vec<PendingTransferHook>,
 ) -> StdResult<()> {
-    // check whether token_id is an NFT => cannot mint. This should not be reachable in standard implementation,
-    // as the calling function would have checked that enable_mint == false, which needs to be true for NFTs.
-    // This is a redundancy check to make sure
-    if token_info.token_config.flatten().is_nft && remove_from.is_none() {
+    // check whether token_id is an NFT that has already been minted => cannot mint. This should not be
+    // reachable in standard implementation, as the calling function would have checked that enable_mint == false,
+    // which needs to be true for NFTs. This is a redundancy check to make sure
+    if token_info.token_config.flatten().is_nft
+        && remove_from.is_none()
+        && may_get_current_owner(storage, token_id)?.is_some()
+    {
         return Err(StdError::generic_err(
             "NFTs can only be minted once using `mint_token_ids`",
         ));
@@ -1311,16 +3473,40 @@ fn exec_change_balance(
         return Err(StdError::generic_err("NFT amount must == 1"));
     }
 
+    // check whether minting would exceed max supply, before any balance is changed
+    if let (None, Some(max_supply)) = (remove_from, token_info.token_config.flatten().max_supply) {
+        let total_supply = tkn_tot_supply_r(storage).load(token_info.token_id.as_bytes())?;
+        if total_supply
+            .checked_add(*amount)
+            .map_or(true, |i| i > max_supply)
+        {
+            return Err(StdError::generic_err(format!(
+                "minting would exceed the max supply of {} for token_id {}",
+                max_supply, token_info.token_id
+            )));
+        }
+    }
+
+    // balances before the change, sent to the controller if the token_id has transfer hooks
+    let balances_before = if transfer_hooks_r(storage)
+        .may_load(token_id.as_bytes())?
+        .unwrap_or(false)
+    {
+        Some(load_hook_balances(storage, token_id, remove_from, add_to)?)
+    } else {
+        None
+    };
+
     // remove balance
     if let Some(from) = remove_from {
-        let from_existing_bal = balances_r(storage, token_id).load(to_binary(&from)?.as_slice())?;
-        let from_new_amount_op = from_existing_bal.checked_sub(*amount);
-        if from_new_amount_op.is_err() {
-            return Err(StdError::generic_err("insufficient funds"));
-        }
-        balances_w(storage, token_id).save(
-            to_binary(&from)?.as_slice(),
-            &Uint256::from(from_new_amount_op.unwrap()),
+        exec_update_balance(
+            storage,
+            block,
+            token_id,
+            from,
+            Uint256::zero(),
+            *amount,
+            token_info,
         )?;
 
         // NOTE: if nft, the ownership history remains in storage. Any existing viewing permissions of last owner
@@ -1329,24 +3515,14 @@ fn exec_change_balance(
 
     // add balance
     if let Some(to) = add_to {
-        let to_existing_bal_op =
-            balances_r(storage, token_id).may_load(to_binary(&to)?.as_slice())?;
-        let to_existing_bal = match to_existing_bal_op {
-            Some(i) => i,
-            // if `to` address has no balance yet, initiate zero balance
-            None => Uint256::from(0_u64),
-        };
-        let to_new_amount_op = to_existing_bal.checked_add(*amount);
-        if to_new_amount_op.is_err() {
-            return Err(StdError::generic_err(
-                "recipient will become too rich. Total tokens exceeds 2^128",
-            ));
-        }
-
-        // save new balances
-        balances_w(storage, token_id).save(
-            to_binary(&to)?.as_slice(),
-            &Uint256::from(to_new_amount_op.unwrap()),
+        exec_update_balance(
+            storage,
+            block,
+            token_id,
+            to,
+            *amount,
+            Uint256::zero(),
+            token_info,
         )?;
 
         // if is_nft == true, store new owner of NFT
@@ -1371,6 +3547,13 @@ fn exec_change_balance(
                 }
             };
             tkn_tot_supply_w(storage).save(token_info.token_id.as_bytes(), &new_amount)?;
+            write_supply_checkpoint(storage, &token_info.token_id, block.height, new_amount)?;
+            write_supply_accumulator(
+                storage,
+                &token_info.token_id,
+                block.time.seconds(),
+                new_amount,
+            )?;
         }
         (Some(_), None) => {
             let old_amount = tkn_tot_supply_r(storage).load(token_info.token_id.as_bytes())?;
@@ -1380,18 +3563,142 @@ fn exec_change_balance(
                 Err(_e) => return Err(StdError::generic_err("total supply drops below zero")),
             };
             tkn_tot_supply_w(storage).save(token_info.token_id.as_bytes(), &new_amount)?;
+            write_supply_checkpoint(storage, &token_info.token_id, block.height, new_amount)?;
+            write_supply_accumulator(
+                storage,
+                &token_info.token_id,
+                block.time.seconds(),
+                new_amount,
+            )?;
         }
     }
 
+    // the user of an nft does not carry over to its new owner
+    if token_info.token_config.flatten().is_nft && remove_from.is_some() {
+        nft_users_w(storage).remove(token_id.as_bytes());
+    }
+
+    if let Some(balances_before) = balances_before {
+        queue_transfer_hook(
+            storage,
+            hooks,
+            token_id,
+            remove_from,
+            add_to,
+            amount,
+            balances_before,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// queues a transfer hook for a balance change that has just been made, with the balances of
+/// `from` and `to` before and after the change. Queued hooks are added to the handle's response
+/// by `add_transfer_hooks`
+fn queue_transfer_hook(
+    storage: &dyn Storage,
+    hooks: &mut Vec<PendingTransferHook>,
+    token_id: &str,
+    from: Option<&Addr>,
+    to: Option<&Addr>,
+    amount: &Uint256,
+    balances_before: (Option<Uint256>, Option<Uint256>),
+) -> StdResult<()> {
+    let (from_balance_after, to_balance_after) = load_hook_balances(storage, token_id, from, to)?;
+    hooks.push(PendingTransferHook {
+        token_id: token_id.to_string(),
+        from: from.cloned(),
+        to: to.cloned(),
+        amount: *amount,
+        from_balance_before: balances_before.0,
+        to_balance_before: balances_before.1,
+        from_balance_after,
+        to_balance_after,
+    });
     Ok(())
 }
 
+/// loads the current balances of `from` and `to` for a transfer hook. The balance of an absent
+/// address is `None`
+fn load_hook_balances(
+    storage: &dyn Storage,
+    token_id: &str,
+    from: Option<&Addr>,
+    to: Option<&Addr>,
+) -> StdResult<(Option<Uint256>, Option<Uint256>)> {
+    let balance = |address: Option<&Addr>| -> StdResult<Option<Uint256>> {
+        match address {
+            Some(address) => Ok(Some(
+                balances_r(storage, token_id)
+                    .may_load(to_binary(address)?.as_slice())?
+                    .unwrap_or_default(),
+            )),
+            None => Ok(None),
+        }
+    };
+    Ok((balance(from)?, balance(to)?))
+}
+
+/// adds the transfer hooks queued during the handle to its response: a `BeforeTransfer` message to
+/// the controller ahead of all other messages of the response, with the balances from before the
+/// change, and an `AfterTransfer` message after them, with the balances from after the change.
+/// As messages are executed atomically with the handle, a failing `BeforeTransfer` reverts the
+/// balance changes. `sender` is the address on whose behalf the balances were changed
+fn add_transfer_hooks(
+    storage: &dyn Storage,
+    sender: &Addr,
+    hooks: Vec<PendingTransferHook>,
+    mut response: Response,
+) -> StdResult<Response> {
+    if hooks.is_empty() {
+        return Ok(response);
+    }
+    let controller = match contr_conf_r(storage).load()?.controller {
+        Some(controller) => controller,
+        None => return Ok(response),
+    };
+
+    let mut before_msgs = vec![];
+    for hook in hooks {
+        let before_msg = TransferHookMsg::new(
+            sender.clone(),
+            hook.token_id.clone(),
+            hook.from.clone(),
+            hook.to.clone(),
+            hook.amount,
+            hook.from_balance_before,
+            hook.to_balance_before,
+        );
+        before_msgs.push(SubMsg::new(before_msg.into_before_msg(
+            controller.code_hash.clone(),
+            controller.address.clone(),
+        )?));
+        let after_msg = TransferHookMsg::new(
+            sender.clone(),
+            hook.token_id,
+            hook.from,
+            hook.to,
+            hook.amount,
+            hook.from_balance_after,
+            hook.to_balance_after,
+        );
+        response = response.add_message(
+            after_msg.into_after_msg(controller.code_hash.clone(), controller.address.clone())?,
+        );
+    }
+    response.messages.splice(0..0, before_msgs);
+
+    Ok(response)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn try_add_receiver_api_callback(
     storage: &dyn Storage,
-    messages: &mut Vec<CosmosMsg>,
+    messages: &mut Vec<SubMsg>,
     recipient: Addr,
     recipient_code_hash: Option<String>,
+    send_mode: SendMode,
     msg: Option<Binary>,
     sender: Addr,
     token_id: String,
@@ -1399,22 +3706,88 @@ fn try_add_receiver_api_callback(
     amount: Uint256,
     memo: Option<String>,
 ) -> StdResult<()> {
-    if let Some(receiver_hash) = recipient_code_hash {
-        let receiver_msg = Snip1155ReceiveMsg::new(sender, token_id, from, amount, memo, msg);
-        let callback_msg = receiver_msg.into_cosmos_msg(receiver_hash, recipient)?;
+    let receiver_hash = match recipient_code_hash {
+        Some(receiver_hash) => receiver_hash,
+        None => match get_receiver_hash(storage, &recipient) {
+            Some(receiver_hash) => receiver_hash?,
+            None if send_mode == SendMode::Lenient => return Ok(()),
+            None => {
+                return Err(StdError::generic_err(format!(
+                    "{} has not registered a receiver, and no recipient_code_hash was given",
+                    recipient
+                )))
+            }
+        },
+    };
+
+    let receiver_msg = Snip1155ReceiveMsg::new(sender, token_id, from, amount, memo, msg);
+    let callback_msg = receiver_msg.into_cosmos_msg(receiver_hash, recipient)?;
+    messages.push(match send_mode {
+        SendMode::SafeWithReply => SubMsg::reply_on_success(callback_msg, RECEIVE_REPLY_ID),
+        SendMode::Lenient | SendMode::Safe => SubMsg::new(callback_msg),
+    });
+
+    Ok(())
+}
 
-        messages.push(callback_msg);
+/// creates the `Snip1155Receive` callback of a minted balance, if the minter asked for one by
+/// setting `recipient_code_hash` or `msg`. The callback's `from` is [MINT_FROM_MARKER], and it
+/// fails if the recipient's code hash is unknown, even if the contract-wide send mode is `lenient`
+fn try_add_mint_callback(
+    storage: &dyn Storage,
+    config: &ContractConfig,
+    messages: &mut Vec<SubMsg>,
+    sender: &Addr,
+    token_id: &str,
+    balance: &MintTokenIdBalance,
+    memo: Option<String>,
+) -> StdResult<()> {
+    if balance.recipient_code_hash.is_none() && balance.msg.is_none() {
         return Ok(());
     }
 
-    let receiver_hash = get_receiver_hash(storage, &recipient);
-    if let Some(receiver_hash) = receiver_hash {
-        let receiver_hash = receiver_hash?;
-        let receiver_msg = Snip1155ReceiveMsg::new(sender, token_id, from, amount, memo, msg);
-        let callback_msg = receiver_msg.into_cosmos_msg(receiver_hash, recipient)?;
+    try_add_receiver_api_callback(
+        storage,
+        messages,
+        balance.address.clone(),
+        balance.recipient_code_hash.clone(),
+        config.send_mode.max(SendMode::Safe),
+        balance.msg.clone(),
+        sender.clone(),
+        token_id.to_string(),
+        Addr::unchecked(MINT_FROM_MARKER),
+        balance.amount,
+        memo,
+    )
+}
 
-        messages.push(callback_msg);
+/// handles the reply to a `Snip1155Receive` callback sent with the `safe_with_reply` send mode.
+/// Errors, rolling back the whole transaction, unless the recipient accepted the tokens
+fn try_receive_reply(result: SubMsgResult) -> StdResult<Response> {
+    let accepted = match result {
+        SubMsgResult::Ok(SubMsgResponse {
+            data: Some(data), ..
+        }) => matches!(
+            parse_receiver_answer(&data),
+            Some(ReceiverHandleAnswer::Snip1155Receive { accepted: true })
+        ),
+        _ => false,
+    };
+    if !accepted {
+        return Err(StdError::generic_err(
+            "the recipient did not accept the tokens",
+        ));
     }
 
-    Ok(())
+    Ok(Response::default())
+}
+
+/// reads the receiver's answer from the data of a reply. The data may be wrapped in a protobuf
+/// `MsgExecuteContractResponse`, whose only field holds the data set by the receiver
+fn parse_receiver_answer(data: &Binary) -> Option<ReceiverHandleAnswer> {
+    if let Ok(answer) = from_binary(data) {
+        return Some(answer);
+    }
+    let inner = parse_execute_response_data(data.as_slice()).ok()??;
+    from_binary(&inner).ok()
 }
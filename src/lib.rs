@@ -1,7 +1,10 @@
+pub mod client;
 pub mod handles;
 pub mod queries;
 pub mod msg;
 pub mod state;
 pub mod receiver;
+pub mod controller;
+pub mod snip20;
 #[cfg(test)]
 pub mod unittest;
\ No newline at end of file
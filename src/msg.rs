@@ -1,13 +1,21 @@
-use cosmwasm_std::{Addr, Binary, StdResult, Uint256};
+use cosmwasm_std::{Addr, Binary, StdError, StdResult, Uint128, Uint256};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::state::{
+    compliance::{ComplianceEvent, TransferListMode},
     expiration::Expiration,
     metadata::Metadata,
     permissions::{Permission, PermissionKey},
-    state_structs::{CurateTokenId, LbPair, OwnerBalance, StoredTokenInfo, TokenAmount},
+    rewards::RewardAmount,
+    state_structs::{
+        AutoCurateTemplate, BackingAsset, BatchBalance, Controller, CurateTokenId, EditionInfo,
+        LbPair, MintTokenAmount, OwnerBalance, ScopedViewingKeyInfo, SendMode, SeriesInfoMsg,
+        StoredSeriesInfo, StoredTokenInfo, SwapOffer, TokenAmount, TokenBacking, TokenIdBalance,
+        ViewingKeyScope,
+    },
     txhistory::Tx,
+    vesting::{VestingGrant, VestingSchedule},
 };
 
 use secret_toolkit::permit::Permit;
@@ -30,6 +38,14 @@ pub struct InstantiateMsg {
     /// for `create_viewing_key` function
     pub entropy: String,
     pub lb_pair_info: LbPair,
+    /// optional ERC-1155-style uri template, eg: `https://meta.example/{id}.json`. Must contain `{id}`
+    pub uri_template: Option<String>,
+    /// template for token_ids curated by `MintTokens`. If `None`, defaults to a fungible token
+    /// named `{name}-{id}` with the lb pair's symbol and decimals
+    pub auto_curate_template: Option<AutoCurateTemplate>,
+    /// maximum number of sub-queries in a `Batch` query. Defaults to
+    /// [DEFAULT_MAX_BATCH_QUERIES](crate::state::DEFAULT_MAX_BATCH_QUERIES)
+    pub max_batch_queries: Option<u32>,
 }
 
 /////////////////////////////////////////////////////////////////////////////////
@@ -50,10 +66,11 @@ pub enum ExecuteMsg {
         padding: Option<String>,
     },
     /// mints additional tokens of existing fungible token_ids, if configuration allows this, ie
-    /// `enable_mint == true`.
+    /// `enable_mint == true`. Token_ids that do not exist yet are curated using the
+    /// [AutoCurateTemplate](crate::state::state_structs::AutoCurateTemplate), if enabled.
     /// Only minters can access this function
     MintTokens {
-        mint_tokens: Vec<TokenAmount>,
+        mint_tokens: Vec<MintTokenAmount>,
         memo: Option<String>,
         padding: Option<String>,
     },
@@ -83,6 +100,10 @@ pub enum ExecuteMsg {
     /// transfers one or more tokens of a single token_id. Other third address can perform this function
     /// if it has permission to transfer. ie: if addr3 can call this function to transfer tokens from addr0
     /// to addr2, if addr0 gives addr3 enough transfer allowance.
+    ///
+    /// `decoys` are addresses whose balance and transaction history storage are also accessed, so
+    /// that storage access patterns do not reveal the real recipient. `entropy` is used to randomize
+    /// the order in which decoys and the recipient are accessed
     Transfer {
         token_id: String,
         // equivalent to `owner` in SNIP20. Tokens are sent from this address.
@@ -90,11 +111,14 @@ pub enum ExecuteMsg {
         recipient: Addr,
         amount: Uint256,
         memo: Option<String>,
+        decoys: Option<Vec<Addr>>,
+        entropy: Option<String>,
         padding: Option<String>,
     },
     /// performs `transfer`s of multiple token_ids in a single transaction
     BatchTransfer {
         actions: Vec<TransferAction>,
+        entropy: Option<String>,
         padding: Option<String>,
     },
     /// similar to transfer, but also sends a cosmos message. The recipient needs to be a contract that
@@ -108,11 +132,16 @@ pub enum ExecuteMsg {
         amount: Uint256,
         msg: Option<Binary>,
         memo: Option<String>,
+        decoys: Option<Vec<Addr>>,
+        /// defaults to `lenient`. The contract-wide send mode applies if it is stricter
+        send_mode: Option<SendMode>,
+        entropy: Option<String>,
         padding: Option<String>,
     },
     /// performs `send` of multiple token_ids in a single transaction
     BatchSend {
         actions: Vec<SendAction>,
+        entropy: Option<String>,
         padding: Option<String>,
     },
     /// allows an owner of token_ids to change transfer or viewership permissions to other addresses.  
@@ -155,6 +184,21 @@ pub enum ExecuteMsg {
         allowed_address: Addr,
         padding: Option<String>,
     },
+    /// transfers tokens on behalf of the signer of `permit`, who is the owner of the tokens. Any
+    /// address can submit this message, allowing owners to transfer without paying gas.
+    /// The permit must be for this contract and the current chain, and is only valid for the
+    /// signer's current nonce and before its expiration. Revoking the permit with `RevokePermit`
+    /// also invalidates it
+    TransferWithPermit {
+        permit: Permit<TransferPermitPayload>,
+        padding: Option<String>,
+    },
+    /// changes permissions on behalf of the signer of `permit`, as if the signer had sent a
+    /// `GivePermission` message. Same validation as `TransferWithPermit`
+    GivePermissionWithPermit {
+        permit: Permit<GivePermissionPermitPayload>,
+        padding: Option<String>,
+    },
     CreateViewingKey {
         entropy: String,
         padding: Option<String>,
@@ -163,6 +207,21 @@ pub enum ExecuteMsg {
         key: String,
         padding: Option<String>,
     },
+    /// creates an additional viewing key with a unique `label`, which can only be used for
+    /// queries within its `scopes`, until `expiration`. The key created by `CreateViewingKey` or
+    /// `SetViewingKey` is unaffected and can still be used for all queries
+    CreateScopedViewingKey {
+        label: String,
+        scopes: Vec<ViewingKeyScope>,
+        expiration: Option<Expiration>,
+        entropy: String,
+        padding: Option<String>,
+    },
+    /// revokes the scoped viewing key with this label
+    RevokeScopedViewingKey {
+        label: String,
+        padding: Option<String>,
+    },
     /// disallow the use of a query permit
     RevokePermit {
         permit_name: String,
@@ -205,6 +264,192 @@ pub enum ExecuteMsg {
         code_hash: String,
         padding: Option<String>,
     },
+    /// sets or removes the contract-level uri template. `{id}` in the template is replaced with
+    /// the token_id in public info queries, for token_ids without public metadata.
+    /// Only the admin or curators can access this function
+    SetUriTemplate {
+        uri_template: Option<String>,
+        padding: Option<String>,
+    },
+    /// changes the template `MintTokens` uses to curate token_ids that do not exist yet, or
+    /// disables implicit curation. Only the admin can access this function
+    SetAutoCurateTemplate {
+        /// used Box<T> to reduce the total size of the enum variant
+        template: Box<AutoCurateTemplate>,
+        padding: Option<String>,
+    },
+    /// changes the maximum number of sub-queries in a `Batch` query. Only the admin can access
+    /// this function
+    SetMaxBatchQueries {
+        max_batch_queries: u32,
+        padding: Option<String>,
+    },
+    /// lowers the `max_supply` of a fungible token_id. The new cap cannot be higher than the current
+    /// cap or lower than the current total supply. If `max_supply` == None, the total supply is locked
+    /// permanently, ie: the cap is set to the current total supply and minting is disabled.
+    /// Only the admin, curators or minters of the token_id can access this function
+    LowerMaxSupply {
+        token_id: String,
+        max_supply: Option<Uint256>,
+        padding: Option<String>,
+    },
+    /// creates series of numbered NFT editions. Editions are minted on demand using `MintEditions`.
+    /// Only curators can access this function
+    CurateSeries {
+        series: Vec<SeriesInfoMsg>,
+        padding: Option<String>,
+    },
+    /// mints the next editions of an NFT series, one edition to each recipient. Each edition is an
+    /// NFT with token_id `{series_id}#{edition}`, which inherits the series' configuration and metadata.
+    /// Only minters of the series can access this function
+    MintEditions {
+        series_id: String,
+        recipients: Vec<Addr>,
+        memo: Option<String>,
+        padding: Option<String>,
+    },
+    /// mints shares of a token_id backed by a native coin, in exchange for the coins attached
+    /// to this message. See [TokenBacking](crate::state::state_structs::TokenBacking)
+    Deposit {
+        token_id: String,
+        memo: Option<String>,
+        padding: Option<String>,
+    },
+    /// SNIP20 `Receive` callback. Mints shares of a token_id backed by the sending SNIP20
+    /// contract, if `msg` is a [Snip20ReceiveAction](crate::snip20::Snip20ReceiveAction)
+    Receive {
+        sender: Addr,
+        from: Addr,
+        amount: Uint128,
+        memo: Option<String>,
+        msg: Option<Binary>,
+    },
+    /// burns shares of a backed token_id and returns the collateral to the sender.
+    /// `amount` must be a multiple of the token_id's `shares_per_unit`
+    Redeem {
+        token_id: String,
+        amount: Uint256,
+        memo: Option<String>,
+        padding: Option<String>,
+    },
+    /// distributes `amount` of an asset to the current holders of a token_id, pro rata to their
+    /// balances. Native coins must be attached to this message. SNIP20 tokens are transferred
+    /// from the sender with `TransferFrom`, so the sender must first give this contract an
+    /// allowance
+    DistributeRewards {
+        token_id: String,
+        asset: BackingAsset,
+        amount: Uint128,
+        padding: Option<String>,
+    },
+    /// sends the sender all its unclaimed rewards for holding a token_id
+    ClaimRewards {
+        token_id: String,
+        padding: Option<String>,
+    },
+    /// sets the contract which receives transfer hooks, or removes it if `controller` == `None`.
+    /// Only the admin can access this function
+    SetController {
+        controller: Option<Controller>,
+        padding: Option<String>,
+    },
+    /// enables or disables transfer hooks for a list of token_ids or series_ids. While enabled,
+    /// every mint, burn and transfer of the token_id sends a `BeforeTransfer` message to the
+    /// controller before any other message of the transaction, and an `AfterTransfer` message after
+    /// all of them. Editions of a series take the series' setting when they are minted.
+    /// Only the admin can access this function
+    SetTransferHooks {
+        token_ids: Vec<String>,
+        enabled: bool,
+        padding: Option<String>,
+    },
+    /// sets the minimum send mode of every `Send` and `BatchSend`. Only the admin can access this
+    /// function
+    SetSendMode {
+        send_mode: SendMode,
+        padding: Option<String>,
+    },
+    /// freezes or unfreezes an address for all token_ids, or only for `token_id` if given. The
+    /// balances of a frozen address cannot change. Only the admin can access this function
+    SetFrozen {
+        address: Addr,
+        token_id: Option<String>,
+        frozen: bool,
+        padding: Option<String>,
+    },
+    /// sets whether a token_id's transfer list is used as an allow list, a deny list, or not at
+    /// all. Only the admin can access this function
+    SetTransferListMode {
+        token_id: String,
+        mode: TransferListMode,
+        padding: Option<String>,
+    },
+    /// adds and removes addresses from a token_id's transfer list. Only the admin can access this
+    /// function
+    UpdateTransferList {
+        token_id: String,
+        add: Vec<Addr>,
+        remove: Vec<Addr>,
+        padding: Option<String>,
+    },
+    /// mints tokens of an existing fungible token_id into a vesting schedule. The tokens count
+    /// toward the recipient's balance, but cannot be transferred or burned until they unlock.
+    /// Only curators can access this function, if `enable_mint` == true
+    MintVested {
+        token_id: String,
+        recipient: Addr,
+        amount: Uint256,
+        schedule: VestingSchedule,
+        /// if true, the sender can later burn the tokens that are still locked with `RevokeVesting`
+        revocable: bool,
+        memo: Option<String>,
+        padding: Option<String>,
+    },
+    /// burns the still locked tokens of the revocable vesting grants that the sender minted to
+    /// `holder`. Tokens that have already unlocked stay with the holder
+    RevokeVesting {
+        token_id: String,
+        holder: Addr,
+        memo: Option<String>,
+        padding: Option<String>,
+    },
+    /// offers to swap balances with another address. The `give` balances must be the sender's
+    /// own, and are held by the contract until the offer is accepted or cancelled. Each `want`
+    /// balance's `address` is the address that receives it when the offer is accepted, and cannot
+    /// be the contract itself
+    CreateSwapOffer {
+        give: Vec<TokenAmount>,
+        want: Vec<TokenAmount>,
+        /// if set, only this address can accept the offer
+        counterparty: Option<Addr>,
+        /// defaults to `Never`
+        expiry: Option<Expiration>,
+        /// if true, any address can view the offer with the public `SwapOffer` query. Defaults to
+        /// false, in which case only the creator and counterparty can view it
+        public: Option<bool>,
+        padding: Option<String>,
+    },
+    /// sends the `want` balances of an unexpired swap offer from the sender, and gives the sender
+    /// the `give` balances held in escrow. The creator of the offer cannot accept it
+    AcceptSwap {
+        swap_id: u64,
+        padding: Option<String>,
+    },
+    /// cancels a swap offer, returning the balances held in escrow. Only the creator of the offer
+    /// can access this function
+    CancelSwap {
+        swap_id: u64,
+        padding: Option<String>,
+    },
+    /// sets or removes (if `user` is `None`) the user of an NFT, who can view its private
+    /// metadata until `expires`. The user is removed when the NFT is transferred. Only the owner
+    /// of the NFT can access this function
+    SetUser {
+        token_id: String,
+        user: Option<Addr>,
+        expires: Expiration,
+        padding: Option<String>,
+    },
 }
 
 /// Handle answers in the `data` field of `HandleResponse`. See
@@ -222,8 +467,12 @@ pub enum ExecuteAnswer {
     BatchSend { status: ResponseStatus },
     GivePermission { status: ResponseStatus },
     RevokePermission { status: ResponseStatus },
+    TransferWithPermit { status: ResponseStatus },
+    GivePermissionWithPermit { status: ResponseStatus },
     CreateViewingKey { key: String },
     SetViewingKey { status: ResponseStatus },
+    CreateScopedViewingKey { key: String },
+    RevokeScopedViewingKey { status: ResponseStatus },
     RevokePermit { status: ResponseStatus },
     AddCurators { status: ResponseStatus },
     RemoveCurators { status: ResponseStatus },
@@ -232,6 +481,29 @@ pub enum ExecuteAnswer {
     ChangeAdmin { status: ResponseStatus },
     RemoveAdmin { status: ResponseStatus },
     RegisterReceive { status: ResponseStatus },
+    SetUriTemplate { status: ResponseStatus },
+    SetAutoCurateTemplate { status: ResponseStatus },
+    SetMaxBatchQueries { status: ResponseStatus },
+    LowerMaxSupply { status: ResponseStatus },
+    CurateSeries { status: ResponseStatus },
+    MintEditions { token_ids: Vec<String> },
+    Deposit { status: ResponseStatus },
+    Receive { status: ResponseStatus },
+    Redeem { status: ResponseStatus },
+    DistributeRewards { status: ResponseStatus },
+    ClaimRewards { status: ResponseStatus },
+    SetController { status: ResponseStatus },
+    SetTransferHooks { status: ResponseStatus },
+    SetSendMode { status: ResponseStatus },
+    SetFrozen { status: ResponseStatus },
+    SetTransferListMode { status: ResponseStatus },
+    UpdateTransferList { status: ResponseStatus },
+    MintVested { status: ResponseStatus },
+    RevokeVesting { status: ResponseStatus },
+    CreateSwapOffer { swap_id: u64 },
+    AcceptSwap { status: ResponseStatus },
+    CancelSwap { status: ResponseStatus },
+    SetUser { status: ResponseStatus },
 }
 
 /////////////////////////////////////////////////////////////////////////////////
@@ -257,6 +529,47 @@ pub enum QueryMsg {
         tx_history_page: Option<u32>,
         tx_history_page_size: Option<u32>,
     },
+    /// returns the balance of every (owner, token_id) pair, for owners that have given the viewer
+    /// permission to view their balance
+    BalanceOfBatch {
+        viewer: Addr,
+        key: String,
+        owners: Vec<Addr>,
+        token_ids: Vec<String>,
+    },
+    /// returns the balance of an owner at the end of block `height`. Requires the same permission
+    /// as `Balance`
+    BalanceAt {
+        owner: Addr,
+        viewer: Addr,
+        key: String,
+        token_id: String,
+        height: u64,
+    },
+    /// returns the rewards an owner can claim for holding a token_id
+    PendingRewards {
+        owner: Addr,
+        key: String,
+        token_id: String,
+    },
+    /// returns how much of an owner's balance is still locked in vesting grants, and the grants
+    /// themselves. Requires the same permission as `Balance`
+    VestingBalance {
+        owner: Addr,
+        viewer: Addr,
+        key: String,
+        token_id: String,
+    },
+    /// returns the sum of an owner's balance×seconds between two timestamps (in seconds since
+    /// 01/01/1970). Requires the same permission as `Balance`
+    TimeWeightedBalance {
+        owner: Addr,
+        viewer: Addr,
+        key: String,
+        token_id: String,
+        start: u64,
+        end: u64,
+    },
     TransactionHistory {
         address: Addr,
         key: String,
@@ -280,18 +593,98 @@ pub enum QueryMsg {
     TokenIdPublicInfo {
         token_id: String,
     },
+    /// returns the total supply of a token_id at the end of block `height`, if the token_id has
+    /// a public total supply
+    TotalSupplyAt {
+        token_id: String,
+        height: u64,
+    },
+    /// returns the sum of a token_id's total supply×seconds between two timestamps (in seconds
+    /// since 01/01/1970), if the token_id has a public total supply
+    TimeWeightedTotalSupply {
+        token_id: String,
+        start: u64,
+        end: u64,
+    },
+    /// lists the holders of a token_id and their balances. Only available for token_ids with
+    /// `public_holders`
+    Holders {
+        token_id: String,
+        /// holder to start after, for pagination. Use `next_start_after` of the previous page
+        start_after: Option<Addr>,
+        /// maximum number of index entries to read, including former holders, which are skipped
+        limit: Option<u32>,
+    },
+    /// returns whether an address is frozen for all token_ids, or for `token_id` if given
+    Frozen {
+        address: Addr,
+        token_id: Option<String>,
+    },
+    /// returns the audit log of freezes and transfer list changes, starting from the latest change.
+    /// `page_size` is capped at 100
+    ComplianceLog {
+        page: Option<u32>,
+        page_size: u32,
+    },
+    /// returns an open swap offer that was created with `public`
+    SwapOffer {
+        swap_id: u64,
+    },
+    /// returns an open swap offer. Only available to its creator and counterparty, or to any
+    /// address if the offer has no counterparty
+    PrivateSwapOffer {
+        address: Addr,
+        key: String,
+        swap_id: u64,
+    },
+    /// returns the unexpired user of an NFT, if any
+    UserOf {
+        token_id: String,
+    },
     TokenIdPrivateInfo {
         address: Addr,
         key: String,
         token_id: String,
     },
+    /// lists the labels, scopes and expirations of an address's scoped viewing keys. Requires the
+    /// address's unscoped viewing key
+    ScopedViewingKeys {
+        address: Addr,
+        key: String,
+    },
     RegisteredCodeHash {
         contract: Addr,
     },
+    /// returns the template `MintTokens` uses to curate token_ids that do not exist yet
+    AutoCurateTemplate {},
+    /// returns the collateral backing a token_id, to check it against the token_id's total supply
+    Reserve {
+        token_id: String,
+    },
+    /// returns public information of an NFT series and its minted editions
+    SeriesInfo {
+        series_id: String,
+        /// edition number to start after, for pagination
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    /// returns the nonce that the next signed `TransferWithPermit` or `GivePermissionWithPermit`
+    /// of an address must use
+    PermitNonce {
+        address: Addr,
+    },
     WithPermit {
-        permit: Permit,
+        permit: Permit<Snip1155Permission>,
         query: QueryWithPermit,
     },
+    /// performs multiple queries, authenticated once with a viewing key or permit. Public
+    /// sub-queries do not need `auth`. Each sub-query returns its own result, so a failing
+    /// sub-query does not fail the batch. The number of sub-queries is limited by the contract's
+    /// `max_batch_queries`
+    Batch {
+        auth: Option<BatchAuth>,
+        queries: Vec<BatchQuery>,
+    },
 }
 
 impl QueryMsg {
@@ -301,6 +694,17 @@ impl QueryMsg {
                 owner, viewer, key, ..
             } => Ok((vec![owner, viewer], key.clone())),
             Self::AllBalances { owner, key, .. } => Ok((vec![owner], key.clone())),
+            Self::BalanceOfBatch { viewer, key, .. } => Ok((vec![viewer], key.clone())),
+            Self::BalanceAt {
+                owner, viewer, key, ..
+            } => Ok((vec![owner, viewer], key.clone())),
+            Self::TimeWeightedBalance {
+                owner, viewer, key, ..
+            } => Ok((vec![owner, viewer], key.clone())),
+            Self::PendingRewards { owner, key, .. } => Ok((vec![owner], key.clone())),
+            Self::VestingBalance {
+                owner, viewer, key, ..
+            } => Ok((vec![owner, viewer], key.clone())),
             Self::TransactionHistory { address, key, .. } => Ok((vec![address], key.clone())),
             Self::Permission {
                 owner,
@@ -310,14 +714,50 @@ impl QueryMsg {
             } => Ok((vec![owner, allowed_address], key.clone())),
             Self::AllPermissions { address, key, .. } => Ok((vec![address], key.clone())),
             Self::TokenIdPrivateInfo { address, key, .. } => Ok((vec![address], key.clone())),
+            Self::PrivateSwapOffer { address, key, .. } => Ok((vec![address], key.clone())),
+            Self::ScopedViewingKeys { address, key } => Ok((vec![address], key.clone())),
             Self::ContractInfo {}
             | Self::TokenIdPublicInfo { .. }
+            | Self::Holders { .. }
+            | Self::Frozen { .. }
+            | Self::ComplianceLog { .. }
+            | Self::SwapOffer { .. }
+            | Self::UserOf { .. }
+            | Self::TotalSupplyAt { .. }
+            | Self::TimeWeightedTotalSupply { .. }
             | Self::RegisteredCodeHash { .. }
-            | Self::WithPermit { .. } => {
+            | Self::AutoCurateTemplate {}
+            | Self::SeriesInfo { .. }
+            | Self::Reserve { .. }
+            | Self::PermitNonce { .. }
+            | Self::WithPermit { .. }
+            | Self::Batch { .. } => {
                 unreachable!("This query type does not require viewing key authentication")
             }
         }
     }
+
+    /// the scope a scoped viewing key needs to authenticate this query. `None` if the query
+    /// requires the unscoped viewing key
+    pub fn required_scope(&self) -> Option<ViewingKeyScope> {
+        match self {
+            Self::Balance { .. }
+            | Self::AllBalances { .. }
+            | Self::BalanceOfBatch { .. }
+            | Self::BalanceAt { .. }
+            | Self::TimeWeightedBalance { .. }
+            | Self::PendingRewards { .. }
+            | Self::VestingBalance { .. } => Some(ViewingKeyScope::Balances),
+            Self::TransactionHistory { .. } => Some(ViewingKeyScope::History),
+            Self::Permission { .. } | Self::AllPermissions { .. } => {
+                Some(ViewingKeyScope::Permissions)
+            }
+            Self::TokenIdPrivateInfo { .. } | Self::PrivateSwapOffer { .. } => {
+                Some(ViewingKeyScope::PrivateInfo)
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -331,6 +771,133 @@ pub enum QueryWithPermit {
         tx_history_page: Option<u32>,
         tx_history_page_size: Option<u32>,
     },
+    BalanceOfBatch {
+        owners: Vec<Addr>,
+        token_ids: Vec<String>,
+    },
+    BalanceAt {
+        owner: Addr,
+        token_id: String,
+        height: u64,
+    },
+    TimeWeightedBalance {
+        owner: Addr,
+        token_id: String,
+        start: u64,
+        end: u64,
+    },
+    PendingRewards {
+        token_id: String,
+    },
+    VestingBalance {
+        owner: Addr,
+        token_id: String,
+    },
+    TransactionHistory {
+        page: Option<u32>,
+        page_size: u32,
+    },
+    Permission {
+        owner: Addr,
+        allowed_address: Addr,
+        token_id: String,
+    },
+    AllPermissions {
+        page: Option<u32>,
+        page_size: u32,
+    },
+    TokenIdPrivateInfo {
+        token_id: String,
+    },
+    PrivateSwapOffer {
+        swap_id: u64,
+    },
+}
+
+impl QueryWithPermit {
+    /// the permit permission required for this query, other than `Owner`
+    pub fn required_permission(&self) -> Snip1155Permission {
+        match self {
+            Self::Balance { .. }
+            | Self::AllBalances { .. }
+            | Self::BalanceOfBatch { .. }
+            | Self::BalanceAt { .. }
+            | Self::TimeWeightedBalance { .. }
+            | Self::PendingRewards { .. }
+            | Self::VestingBalance { .. } => Snip1155Permission::Balance,
+            Self::TransactionHistory { .. } => Snip1155Permission::History,
+            Self::Permission { .. } | Self::AllPermissions { .. } => Snip1155Permission::Allowance,
+            Self::TokenIdPrivateInfo { .. } | Self::PrivateSwapOffer { .. } => {
+                Snip1155Permission::Owner
+            }
+        }
+    }
+
+    /// the scope a scoped viewing key needs for this query
+    pub fn required_scope(&self) -> ViewingKeyScope {
+        match self {
+            Self::Balance { .. }
+            | Self::AllBalances { .. }
+            | Self::BalanceOfBatch { .. }
+            | Self::BalanceAt { .. }
+            | Self::TimeWeightedBalance { .. }
+            | Self::PendingRewards { .. }
+            | Self::VestingBalance { .. } => ViewingKeyScope::Balances,
+            Self::TransactionHistory { .. } => ViewingKeyScope::History,
+            Self::Permission { .. } | Self::AllPermissions { .. } => ViewingKeyScope::Permissions,
+            Self::TokenIdPrivateInfo { .. } | Self::PrivateSwapOffer { .. } => {
+                ViewingKeyScope::PrivateInfo
+            }
+        }
+    }
+}
+
+/// authentication of a `Batch` query
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchAuth {
+    ViewingKey { address: Addr, key: String },
+    Permit(Box<Permit<Snip1155Permission>>),
+}
+
+/// a sub-query of a `Batch` query. Other than `TokenIdPublicInfo`, these are the same as the
+/// [QueryWithPermit](QueryWithPermit) variants, performed on behalf of the authenticated address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchQuery {
+    TokenIdPublicInfo {
+        token_id: String,
+    },
+    Balance {
+        owner: Addr,
+        token_id: String,
+    },
+    AllBalances {
+        tx_history_page: Option<u32>,
+        tx_history_page_size: Option<u32>,
+    },
+    BalanceOfBatch {
+        owners: Vec<Addr>,
+        token_ids: Vec<String>,
+    },
+    BalanceAt {
+        owner: Addr,
+        token_id: String,
+        height: u64,
+    },
+    TimeWeightedBalance {
+        owner: Addr,
+        token_id: String,
+        start: u64,
+        end: u64,
+    },
+    PendingRewards {
+        token_id: String,
+    },
+    VestingBalance {
+        owner: Addr,
+        token_id: String,
+    },
     TransactionHistory {
         page: Option<u32>,
         page_size: u32,
@@ -349,6 +916,134 @@ pub enum QueryWithPermit {
     },
 }
 
+impl BatchQuery {
+    /// converts to the equivalent authenticated query. Errors for `TokenIdPublicInfo`, which
+    /// does not require authentication
+    pub fn into_permit_query(self) -> StdResult<QueryWithPermit> {
+        match self {
+            Self::TokenIdPublicInfo { .. } => Err(StdError::generic_err(
+                "token_id_public_info does not require authentication",
+            )),
+            Self::Balance { owner, token_id } => Ok(QueryWithPermit::Balance { owner, token_id }),
+            Self::AllBalances {
+                tx_history_page,
+                tx_history_page_size,
+            } => Ok(QueryWithPermit::AllBalances {
+                tx_history_page,
+                tx_history_page_size,
+            }),
+            Self::BalanceOfBatch { owners, token_ids } => {
+                Ok(QueryWithPermit::BalanceOfBatch { owners, token_ids })
+            }
+            Self::BalanceAt {
+                owner,
+                token_id,
+                height,
+            } => Ok(QueryWithPermit::BalanceAt {
+                owner,
+                token_id,
+                height,
+            }),
+            Self::TimeWeightedBalance {
+                owner,
+                token_id,
+                start,
+                end,
+            } => Ok(QueryWithPermit::TimeWeightedBalance {
+                owner,
+                token_id,
+                start,
+                end,
+            }),
+            Self::PendingRewards { token_id } => Ok(QueryWithPermit::PendingRewards { token_id }),
+            Self::VestingBalance { owner, token_id } => {
+                Ok(QueryWithPermit::VestingBalance { owner, token_id })
+            }
+            Self::TransactionHistory { page, page_size } => {
+                Ok(QueryWithPermit::TransactionHistory { page, page_size })
+            }
+            Self::Permission {
+                owner,
+                allowed_address,
+                token_id,
+            } => Ok(QueryWithPermit::Permission {
+                owner,
+                allowed_address,
+                token_id,
+            }),
+            Self::AllPermissions { page, page_size } => {
+                Ok(QueryWithPermit::AllPermissions { page, page_size })
+            }
+            Self::TokenIdPrivateInfo { token_id } => {
+                Ok(QueryWithPermit::TokenIdPrivateInfo { token_id })
+            }
+        }
+    }
+}
+
+/// result of a sub-query of a `Batch` query
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchQueryResult {
+    Ok { answer: Box<QueryAnswer> },
+    Err { msg: String },
+}
+
+/// signed payload of a `TransferWithPermit` message. It is the only entry in the permit's
+/// `permissions`. Tokens are transferred from the signer of the permit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TransferPermitPayload {
+    pub token_id: String,
+    pub recipient: Addr,
+    pub amount: Uint256,
+    pub memo: Option<String>,
+    /// must equal the signer's current nonce. See [PermitNonce](QueryMsg::PermitNonce)
+    pub nonce: u64,
+    /// the payload cannot be used at or after this point
+    pub expiration: Expiration,
+}
+
+/// signed payload of a `GivePermissionWithPermit` message. It is the only entry in the permit's
+/// `permissions`. Fields other than `nonce` and `expiration` are the same as `GivePermission`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GivePermissionPermitPayload {
+    pub allowed_address: Addr,
+    pub token_id: String,
+    pub view_balance: Option<bool>,
+    pub view_balance_expiry: Option<Expiration>,
+    pub view_private_metadata: Option<bool>,
+    pub view_private_metadata_expiry: Option<Expiration>,
+    pub transfer: Option<Uint256>,
+    pub transfer_expiry: Option<Expiration>,
+    /// must equal the signer's current nonce. See [PermitNonce](QueryMsg::PermitNonce)
+    pub nonce: u64,
+    /// the payload cannot be used at or after this point
+    pub expiration: Expiration,
+}
+
+/// permissions that a query permit can grant. `Balance`, `History`, `Allowance` and `Owner`
+/// serialize the same way as the standard SNIP20/SNIP721 permit permissions
+///
+/// `Balance` permits can be restricted to a list of token_ids, either with a `token_ids`
+/// permission, or by adding `"{contract_address}/{token_id}"` entries to the permit's
+/// `allowed_tokens`. If neither is present, the permit applies to all token_ids
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Snip1155Permission {
+    /// permission to query `Balance` and `AllBalances`
+    Balance,
+    /// permission to query `TransactionHistory`
+    History,
+    /// permission to query `Permission` and `AllPermissions`
+    Allowance,
+    /// permission to make all permit queries, including `TokenIdPrivateInfo`
+    Owner,
+    /// restricts `Balance` and `AllBalances` queries to these token_ids
+    TokenIds(Vec<String>),
+}
+
 /// the query responses for each [QueryMsg](crate::msg::QueryMsg) variant
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -361,6 +1056,10 @@ pub enum QueryAnswer {
         curators: Vec<Addr>,
         /// the list of all token_ids that have been curated
         all_token_ids: Vec<String>,
+        /// the contract-level uri template, if any
+        uri_template: Option<String>,
+        /// the minimum send mode of every `Send` and `BatchSend`
+        send_mode: SendMode,
     },
     /// returns balance of a specific token_id. Owners can give permission to other addresses to query their balance
     Balance {
@@ -368,6 +1067,41 @@ pub enum QueryAnswer {
     },
     /// returns all token_id balances owned by an address. Only owners can use this query
     AllBalances(Vec<OwnerBalance>),
+    /// balances of each (owner, token_id) pair, in the order of `owners`, then `token_ids`
+    BalanceOfBatch {
+        balances: Vec<BatchBalance>,
+    },
+    BalanceAt {
+        height: u64,
+        amount: Uint256,
+    },
+    TotalSupplyAt {
+        height: u64,
+        amount: Uint256,
+    },
+    TimeWeightedBalance {
+        start: u64,
+        end: u64,
+        /// sum of balance×seconds
+        accumulated: Uint256,
+    },
+    TimeWeightedTotalSupply {
+        start: u64,
+        end: u64,
+        /// sum of total supply×seconds
+        accumulated: Uint256,
+    },
+    PendingRewards {
+        rewards: Vec<RewardAmount>,
+    },
+    VestingBalance {
+        balance: Uint256,
+        /// part of the balance that cannot be transferred yet
+        locked: Uint256,
+        /// part of the balance that can be transferred
+        unlocked: Uint256,
+        grants: Vec<VestingGrant>,
+    },
     /// all permissions related to a particular address. Note that "curation" is not recorded as a transaction per se, but
     /// the tokens minted as part of the initial_balances set by the curator is recorded under `TxAction::Mint`  
     TransactionHistory {
@@ -393,6 +1127,26 @@ pub enum QueryAnswer {
         total_supply: Option<Uint256>,
         /// if owner_is_public == false, total_supply = None
         owner: Option<Addr>,
+        /// number of holders, if public_holders == true
+        holder_count: Option<u32>,
+    },
+    Holders {
+        holders: Vec<TokenIdBalance>,
+        holder_count: u32,
+        /// `start_after` of the next page, or `None` if this is the last page
+        next_start_after: Option<Addr>,
+    },
+    Frozen {
+        frozen: bool,
+    },
+    ComplianceLog {
+        events: Vec<ComplianceEvent>,
+        total: u64,
+    },
+    SwapOffer(SwapOffer),
+    UserOf {
+        user: Option<Addr>,
+        expires: Option<Expiration>,
     },
     TokenIdPrivateInfo {
         token_id_info: StoredTokenInfo,
@@ -405,6 +1159,29 @@ pub enum QueryAnswer {
     RegisteredCodeHash {
         code_hash: Option<String>,
     },
+    AutoCurateTemplate {
+        template: AutoCurateTemplate,
+    },
+    PermitNonce {
+        nonce: u64,
+    },
+    ScopedViewingKeys {
+        keys: Vec<ScopedViewingKeyInfo>,
+    },
+    Batch {
+        results: Vec<BatchQueryResult>,
+    },
+    Reserve {
+        backing: TokenBacking,
+        /// amount of collateral held by the contract for this token_id
+        reserve: Uint256,
+        total_supply: Uint256,
+    },
+    SeriesInfo {
+        /// series_info.private_metadata will always = None
+        series_info: StoredSeriesInfo,
+        editions: Vec<EditionInfo>,
+    },
     /// returned when an viewing_key-specific errors occur during a user's attempt to
     /// perform an authenticated query
     ViewingKeyError {
@@ -432,6 +1209,7 @@ pub struct TransferAction {
     pub recipient: Addr,
     pub amount: Uint256,
     pub memo: Option<String>,
+    pub decoys: Option<Vec<Addr>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, JsonSchema, Debug)]
@@ -445,6 +1223,9 @@ pub struct SendAction {
     pub amount: Uint256,
     pub msg: Option<Binary>,
     pub memo: Option<String>,
+    pub decoys: Option<Vec<Addr>>,
+    /// defaults to `lenient`. The contract-wide send mode applies if it is stricter
+    pub send_mode: Option<SendMode>,
 }
 
 // Take a Vec<u8> and pad it up to a multiple of `block_size`, using spaces at the end.
@@ -1,7 +1,7 @@
 use std::collections::BTreeSet;
 
 use cosmwasm_std::{
-    entry_point,
+    from_binary,
     to_binary,
     Addr,
     Binary,
@@ -15,21 +15,35 @@ use cosmwasm_std::{
     Uint256,
 };
 use secret_toolkit::{
-    permit::{validate, Permit, TokenPermissions},
+    permit::{validate, Permit},
     viewing_key::{ViewingKey, ViewingKeyStore},
 };
 
 use crate::{
-    msg::{QueryAnswer, QueryMsg, QueryWithPermit},
+    msg::{
+        BatchAuth, BatchQuery, BatchQueryResult, QueryAnswer, QueryMsg, QueryWithPermit,
+        Snip1155Permission,
+    },
     state::{
-        balances_r, blockinfo_r, contr_conf_r, get_receiver_hash,
+        accumulators::{accumulated_balance, accumulated_supply},
+        balances_r, blockinfo_r,
+        checkpoints::{balance_at, total_supply_at},
+        compliance::{get_compliance_log, is_frozen},
+        contr_conf_r, get_receiver_hash,
+        holders::{get_holders, holder_count},
+        may_load_nft_user,
+        metadata::Metadata,
         permissions::{
             list_owner_permission_keys, may_load_any_permission, Permission, PermissionKey,
         },
-        state_structs::OwnerBalance,
-        tkn_info_r, tkn_tot_supply_r,
+        permit_nonces_r,
+        rewards::pending_rewards,
+        scoped_vks_r, series_info_r,
+        state_structs::{BatchBalance, EditionInfo, OwnerBalance, TokenIdBalance, ViewingKeyScope},
+        swaps_r, tkn_backing_r, tkn_info_r, tkn_reserve_r, tkn_tot_supply_r,
         txhistory::{get_txs, may_get_current_owner},
-        PREFIX_REVOKED_PERMITS,
+        vesting::{locked_amount, vesting_grants},
+        ScopedViewingKey, DEFAULT_QUERY_LIMIT, MAX_QUERY_LIMIT, PREFIX_REVOKED_PERMITS,
     },
 };
 
@@ -39,60 +53,167 @@ use crate::{
 
 /// contract query function. See [QueryMsg](crate::msg::QueryMsg) and
 /// [QueryAnswer](crate::msg::QueryAnswer) for the api
-#[entry_point]
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::ContractInfo {} => query_contract_info(deps),
         QueryMsg::TokenIdPublicInfo { token_id } => query_token_id_public_info(deps, token_id),
+        QueryMsg::Holders {
+            token_id,
+            start_after,
+            limit,
+        } => query_holders(deps, token_id, start_after, limit),
+        QueryMsg::Frozen { address, token_id } => query_frozen(deps, address, token_id),
+        QueryMsg::ComplianceLog { page, page_size } => {
+            query_compliance_log(deps, page.unwrap_or(0), page_size)
+        }
+        QueryMsg::SwapOffer { swap_id } => query_swap_offer(deps, None, swap_id),
+        QueryMsg::UserOf { token_id } => query_user_of(deps, token_id),
+        QueryMsg::TotalSupplyAt { token_id, height } => {
+            query_total_supply_at(deps, token_id, height)
+        }
+        QueryMsg::TimeWeightedTotalSupply {
+            token_id,
+            start,
+            end,
+        } => query_time_weighted_total_supply(deps, token_id, start, end),
         QueryMsg::RegisteredCodeHash { contract } => query_registered_code_hash(deps, contract),
+        QueryMsg::AutoCurateTemplate {} => query_auto_curate_template(deps),
+        QueryMsg::Reserve { token_id } => query_reserve(deps, token_id),
+        QueryMsg::PermitNonce { address } => query_permit_nonce(deps, address),
+        QueryMsg::SeriesInfo {
+            series_id,
+            start_after,
+            limit,
+        } => query_series_info(deps, series_id, start_after, limit),
         QueryMsg::WithPermit { permit, query } => permit_queries(deps, permit, query),
+        QueryMsg::Batch { auth, queries } => query_batch(deps, auth, queries),
         QueryMsg::Balance { .. }
         | QueryMsg::AllBalances { .. }
+        | QueryMsg::BalanceOfBatch { .. }
+        | QueryMsg::BalanceAt { .. }
+        | QueryMsg::TimeWeightedBalance { .. }
+        | QueryMsg::PendingRewards { .. }
+        | QueryMsg::VestingBalance { .. }
         | QueryMsg::TransactionHistory { .. }
         | QueryMsg::Permission { .. }
         | QueryMsg::AllPermissions { .. }
-        | QueryMsg::TokenIdPrivateInfo { .. } => viewing_keys_queries(deps, msg),
+        | QueryMsg::TokenIdPrivateInfo { .. }
+        | QueryMsg::PrivateSwapOffer { .. }
+        | QueryMsg::ScopedViewingKeys { .. } => viewing_keys_queries(deps, msg),
     }
 }
 
-fn permit_queries(deps: Deps, permit: Permit, query: QueryWithPermit) -> Result<Binary, StdError> {
+fn permit_queries(
+    deps: Deps,
+    permit: Permit<Snip1155Permission>,
+    query: QueryWithPermit,
+) -> Result<Binary, StdError> {
     // Validate permit content
     let contract_address = contr_conf_r(deps.storage).load()?.contract_address;
+    let account = validate_query_permit(deps, &permit, &contract_address)?;
+
+    // check: permit grants the permission required by the query
+    check_permit_permission(&permit, &query)?;
+    let allowed_token_ids = permit_token_ids(&permit, contract_address.as_str());
 
+    // Permit validated! We can now execute the query.
+    authenticated_query(deps, &account, query, allowed_token_ids.as_deref())
+}
+
+/// validates a query permit and returns the address of the signer
+fn validate_query_permit(
+    deps: Deps,
+    permit: &Permit<Snip1155Permission>,
+    contract_address: &Addr,
+) -> StdResult<Addr> {
     let account_str = validate(
         deps,
         PREFIX_REVOKED_PERMITS,
-        &permit,
+        permit,
         contract_address.to_string(),
         None,
     )?;
-    let account = deps.api.addr_validate(&account_str)?;
+    deps.api.addr_validate(&account_str)
+}
 
-    if !permit.check_permission(&TokenPermissions::Owner) {
+/// checks that a permit grants the permission required by the query
+fn check_permit_permission(
+    permit: &Permit<Snip1155Permission>,
+    query: &QueryWithPermit,
+) -> StdResult<()> {
+    let required_permission = query.required_permission();
+    if !permit.check_permission(&Snip1155Permission::Owner)
+        && !permit.check_permission(&required_permission)
+    {
         return Err(StdError::generic_err(format!(
-            "`Owner` permit required for SNIP1155 permit queries, got permissions {:?}",
-            permit.params.permissions
+            "`{:?}` or `Owner` permit required for this query, got permissions {:?}",
+            required_permission, permit.params.permissions
         )));
     }
 
-    // Permit validated! We can now execute the query.
+    Ok(())
+}
+
+/// performs a query on behalf of an address that has been authenticated with a permit or viewing
+/// key
+fn authenticated_query(
+    deps: Deps,
+    account: &Addr,
+    query: QueryWithPermit,
+    allowed_token_ids: Option<&[String]>,
+) -> StdResult<Binary> {
     match query {
         QueryWithPermit::Balance { owner, token_id } => {
-            query_balance(deps, &owner, &account, token_id)
+            query_balance(deps, &owner, account, token_id, allowed_token_ids)
         }
         QueryWithPermit::AllBalances {
             tx_history_page,
             tx_history_page_size,
-        } => query_all_balances(deps, &account, tx_history_page, tx_history_page_size),
+        } => query_all_balances(
+            deps,
+            account,
+            tx_history_page,
+            tx_history_page_size,
+            allowed_token_ids,
+        ),
+        QueryWithPermit::BalanceOfBatch { owners, token_ids } => {
+            query_balance_of_batch(deps, account, owners, token_ids, allowed_token_ids)
+        }
+        QueryWithPermit::BalanceAt {
+            owner,
+            token_id,
+            height,
+        } => query_balance_at(deps, &owner, account, token_id, height, allowed_token_ids),
+        QueryWithPermit::PendingRewards { token_id } => {
+            query_pending_rewards(deps, account, token_id, allowed_token_ids)
+        }
+        QueryWithPermit::VestingBalance { owner, token_id } => {
+            query_vesting_balance(deps, &owner, account, token_id, allowed_token_ids)
+        }
+        QueryWithPermit::TimeWeightedBalance {
+            owner,
+            token_id,
+            start,
+            end,
+        } => query_time_weighted_balance(
+            deps,
+            &owner,
+            account,
+            token_id,
+            start,
+            end,
+            allowed_token_ids,
+        ),
         QueryWithPermit::TransactionHistory { page, page_size } => {
-            query_transactions(deps, &account, page.unwrap_or(0), page_size)
+            query_transactions(deps, account, page.unwrap_or(0), page_size)
         }
         QueryWithPermit::Permission {
             owner,
             allowed_address,
             token_id,
         } => {
-            if account != owner.as_str() && account != allowed_address.as_str() {
+            if account != &owner && account != &allowed_address {
                 return Err(StdError::generic_err(format!(
                     "Cannot query permission. Requires permit for either owner {:?} or viewer||spender {:?}, got permit for {:?}",
                     owner.as_str(), allowed_address.as_str(), account.as_str()
@@ -102,32 +223,252 @@ fn permit_queries(deps: Deps, permit: Permit, query: QueryWithPermit) -> Result<
             query_permission(deps, token_id, owner, allowed_address)
         }
         QueryWithPermit::AllPermissions { page, page_size } => {
-            query_all_permissions(deps, &account, page.unwrap_or(0), page_size)
+            query_all_permissions(deps, account, page.unwrap_or(0), page_size)
         }
         QueryWithPermit::TokenIdPrivateInfo { token_id } => {
-            query_token_id_private_info(deps, &account, token_id)
+            query_token_id_private_info(deps, account, token_id)
+        }
+        QueryWithPermit::PrivateSwapOffer { swap_id } => {
+            query_swap_offer(deps, Some(account), swap_id)
+        }
+    }
+}
+
+/// how the sub-queries of a `Batch` query are authorized, resolved once for the whole batch
+enum BatchAuthorization {
+    Public,
+    /// `scopes` is `None` for the unscoped viewing key
+    ViewingKey {
+        address: Addr,
+        scopes: Option<Vec<ViewingKeyScope>>,
+    },
+    Permit {
+        account: Addr,
+        permit: Box<Permit<Snip1155Permission>>,
+        allowed_token_ids: Option<Vec<String>>,
+    },
+}
+
+/// performs the sub-queries of a `Batch` query, authenticating once for the whole batch
+fn query_batch(deps: Deps, auth: Option<BatchAuth>, queries: Vec<BatchQuery>) -> StdResult<Binary> {
+    let config = contr_conf_r(deps.storage).load()?;
+    if queries.len() > config.max_batch_queries as usize {
+        return Err(StdError::generic_err(format!(
+            "Batch query can contain at most {} queries",
+            config.max_batch_queries
+        )));
+    }
+
+    // authenticate once
+    let authorization = match auth {
+        None => BatchAuthorization::Public,
+        Some(BatchAuth::ViewingKey { address, key }) => {
+            let scopes = match ViewingKey::check(deps.storage, address.as_str(), key.as_str()) {
+                Ok(()) => None,
+                Err(_) => match scoped_viewing_key_scopes(deps, &address, &key)? {
+                    Some(scopes) => Some(scopes),
+                    None => {
+                        return to_binary(&QueryAnswer::ViewingKeyError {
+                            msg: "Wrong viewing key for this address or viewing key not set"
+                                .to_string(),
+                        })
+                    }
+                },
+            };
+            BatchAuthorization::ViewingKey { address, scopes }
+        }
+        Some(BatchAuth::Permit(permit)) => BatchAuthorization::Permit {
+            account: validate_query_permit(deps, &permit, &config.contract_address)?,
+            allowed_token_ids: permit_token_ids(&permit, config.contract_address.as_str()),
+            permit,
+        },
+    };
+
+    // a failing sub-query returns an error result without failing the batch
+    let mut results: Vec<BatchQueryResult> = vec![];
+    for query in queries {
+        let result = match query {
+            BatchQuery::TokenIdPublicInfo { token_id } => {
+                query_token_id_public_info(deps, token_id)
+            }
+            query => query
+                .into_permit_query()
+                .and_then(|query| batch_authenticated_query(deps, &authorization, query)),
+        };
+        results.push(match result.and_then(|i| from_binary::<QueryAnswer>(&i)) {
+            Ok(answer) => BatchQueryResult::Ok {
+                answer: Box::new(answer),
+            },
+            Err(e) => BatchQueryResult::Err { msg: e.to_string() },
+        });
+    }
+
+    let response = QueryAnswer::Batch { results };
+    to_binary(&response)
+}
+
+fn batch_authenticated_query(
+    deps: Deps,
+    authorization: &BatchAuthorization,
+    query: QueryWithPermit,
+) -> StdResult<Binary> {
+    match authorization {
+        BatchAuthorization::Public => Err(StdError::generic_err(
+            "this query requires a viewing key or permit",
+        )),
+        BatchAuthorization::ViewingKey { address, scopes } => {
+            if let Some(scopes) = scopes {
+                if !scopes.contains(&query.required_scope()) {
+                    return Err(StdError::generic_err(format!(
+                        "viewing key does not have the `{:?}` scope",
+                        query.required_scope()
+                    )));
+                }
+            }
+            authenticated_query(deps, address, query, None)
+        }
+        BatchAuthorization::Permit {
+            account,
+            permit,
+            allowed_token_ids,
+        } => {
+            check_permit_permission(permit, &query)?;
+            authenticated_query(deps, account, query, allowed_token_ids.as_deref())
+        }
+    }
+}
+
+/// returns the token_ids that a permit is restricted to, or `None` if it applies to all token_ids
+fn permit_token_ids(
+    permit: &Permit<Snip1155Permission>,
+    contract_address: &str,
+) -> Option<Vec<String>> {
+    let prefix = format!("{}/", contract_address);
+    let mut token_ids: Vec<String> = permit
+        .params
+        .allowed_tokens
+        .iter()
+        .filter_map(|token| token.strip_prefix(&prefix).map(|i| i.to_string()))
+        .collect();
+    for permission in permit.params.permissions.iter() {
+        if let Snip1155Permission::TokenIds(ids) = permission {
+            token_ids.extend(ids.iter().cloned());
         }
     }
+
+    match token_ids.is_empty() {
+        true => None,
+        false => Some(token_ids),
+    }
+}
+
+/// checks whether `key` is an unexpired scoped viewing key of `address` with the required scope
+fn check_scoped_viewing_key(
+    deps: Deps,
+    address: &Addr,
+    key: &str,
+    required_scope: Option<ViewingKeyScope>,
+) -> StdResult<bool> {
+    let required_scope = match required_scope {
+        Some(i) => i,
+        None => return Ok(false),
+    };
+    let scopes = scoped_viewing_key_scopes(deps, address, key)?;
+
+    Ok(scopes.is_some_and(|i| i.contains(&required_scope)))
+}
+
+/// the number of entries of a paginated query, given its optional `limit`
+fn query_limit(limit: Option<u32>) -> u32 {
+    limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT)
+}
+
+/// BlockInfo of the latest handle, as queries do not have access to the current block
+fn latest_block(deps: Deps) -> StdResult<BlockInfo> {
+    Ok(blockinfo_r(deps.storage)
+        .may_load()?
+        .unwrap_or_else(|| BlockInfo {
+            height: 1,
+            time: Timestamp::from_seconds(1),
+            chain_id: "not used".to_string(),
+            random: None,
+        }))
+}
+
+/// returns the scopes of an address's unexpired scoped viewing key, if `key` is one
+fn scoped_viewing_key_scopes(
+    deps: Deps,
+    address: &Addr,
+    key: &str,
+) -> StdResult<Option<Vec<ViewingKeyScope>>> {
+    let keys = scoped_vks_r(deps.storage)
+        .may_load(address.as_bytes())?
+        .unwrap_or_default();
+    let block = latest_block(deps)?;
+
+    Ok(keys
+        .into_iter()
+        .find(|i| {
+            !i.expiration.is_expired(&block)
+                && ScopedViewingKey::check(
+                    deps.storage,
+                    &ScopedViewingKey::account(address, &i.label),
+                    key,
+                )
+                .is_ok()
+        })
+        .map(|i| i.scopes))
 }
 
 fn viewing_keys_queries(deps: Deps, msg: QueryMsg) -> StdResult<Binary> {
     let (addresses, key) = msg.get_validation_params()?;
+    let required_scope = msg.required_scope();
 
     for address in addresses {
         let result = ViewingKey::check(deps.storage, address.as_str(), key.as_str());
-        if result.is_ok() {
+        if result.is_ok() || check_scoped_viewing_key(deps, address, &key, required_scope)? {
             return match msg {
                 QueryMsg::Balance {
                     owner,
                     viewer,
                     token_id,
                     ..
-                } => query_balance(deps, &owner, &viewer, token_id),
+                } => query_balance(deps, &owner, &viewer, token_id, None),
                 QueryMsg::AllBalances {
                     tx_history_page,
                     tx_history_page_size,
                     ..
-                } => query_all_balances(deps, address, tx_history_page, tx_history_page_size),
+                } => query_all_balances(deps, address, tx_history_page, tx_history_page_size, None),
+                QueryMsg::BalanceOfBatch {
+                    viewer,
+                    owners,
+                    token_ids,
+                    ..
+                } => query_balance_of_batch(deps, &viewer, owners, token_ids, None),
+                QueryMsg::BalanceAt {
+                    owner,
+                    viewer,
+                    token_id,
+                    height,
+                    ..
+                } => query_balance_at(deps, &owner, &viewer, token_id, height, None),
+                QueryMsg::PendingRewards {
+                    owner, token_id, ..
+                } => query_pending_rewards(deps, &owner, token_id, None),
+                QueryMsg::VestingBalance {
+                    owner,
+                    viewer,
+                    token_id,
+                    ..
+                } => query_vesting_balance(deps, &owner, &viewer, token_id, None),
+                QueryMsg::TimeWeightedBalance {
+                    owner,
+                    viewer,
+                    token_id,
+                    start,
+                    end,
+                    ..
+                } => query_time_weighted_balance(deps, &owner, &viewer, token_id, start, end, None),
                 QueryMsg::TransactionHistory {
                     page, page_size, ..
                 } => query_transactions(deps, address, page.unwrap_or(0), page_size),
@@ -143,10 +484,28 @@ fn viewing_keys_queries(deps: Deps, msg: QueryMsg) -> StdResult<Binary> {
                 QueryMsg::TokenIdPrivateInfo {
                     address, token_id, ..
                 } => query_token_id_private_info(deps, &address, token_id),
+                QueryMsg::PrivateSwapOffer { swap_id, .. } => {
+                    query_swap_offer(deps, Some(address), swap_id)
+                }
+                QueryMsg::ScopedViewingKeys { address, .. } => {
+                    query_scoped_viewing_keys(deps, address)
+                }
                 QueryMsg::ContractInfo {}
                 | QueryMsg::TokenIdPublicInfo { .. }
+                | QueryMsg::Holders { .. }
+                | QueryMsg::Frozen { .. }
+                | QueryMsg::ComplianceLog { .. }
+                | QueryMsg::SwapOffer { .. }
+                | QueryMsg::UserOf { .. }
+                | QueryMsg::TotalSupplyAt { .. }
+                | QueryMsg::TimeWeightedTotalSupply { .. }
                 | QueryMsg::RegisteredCodeHash { .. }
-                | QueryMsg::WithPermit { .. } => {
+                | QueryMsg::AutoCurateTemplate {}
+                | QueryMsg::SeriesInfo { .. }
+                | QueryMsg::Reserve { .. }
+                | QueryMsg::PermitNonce { .. }
+                | QueryMsg::WithPermit { .. }
+                | QueryMsg::Batch { .. } => {
                     unreachable!("This query type does not require viewing key authentication")
                 }
             };
@@ -164,6 +523,8 @@ fn query_contract_info(deps: Deps) -> StdResult<Binary> {
         admin: contr_conf.admin,
         curators: contr_conf.curators,
         all_token_ids: contr_conf.token_id_list,
+        uri_template: contr_conf.uri_template,
+        send_mode: contr_conf.send_mode,
     };
     to_binary(&response)
 }
@@ -191,18 +552,119 @@ fn query_token_id_public_info(deps: Deps, token_id: String) -> StdResult<Binary>
                     None
                 };
 
+            // add holder count if public_holders == true
+            let holder_count: Option<u32> = if tkn_info.token_config.flatten().public_holders {
+                Some(holder_count(deps.storage, &token_id)?)
+            } else {
+                None
+            };
+
             // private_metadata always == None for public info query
             tkn_info.private_metadata = None;
+            // token_ids without public metadata fall back to the contract-level uri template
+            if tkn_info.public_metadata.is_none() {
+                tkn_info.public_metadata = contr_conf_r(deps.storage)
+                    .load()?
+                    .token_uri(&token_id)
+                    .map(|token_uri| Metadata {
+                        token_uri: Some(token_uri),
+                        extension: None,
+                    });
+            }
             let response = QueryAnswer::TokenIdPublicInfo {
                 token_id_info: tkn_info,
                 total_supply,
                 owner,
+                holder_count,
             };
             to_binary(&response)
         }
     }
 }
 
+fn query_holders(
+    deps: Deps,
+    token_id: String,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let tkn_info = tkn_info_r(deps.storage)
+        .may_load(token_id.as_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("token_id {} does not exist", token_id)))?;
+    if !tkn_info.token_config.flatten().public_holders {
+        return Err(StdError::generic_err(format!(
+            "holders of token_id {} are not public",
+            token_id
+        )));
+    }
+
+    let (holders, next_start_after) = get_holders(
+        deps.storage,
+        &token_id,
+        start_after.as_ref(),
+        query_limit(limit),
+    )?;
+    let holders = holders
+        .into_iter()
+        .map(|address| {
+            Ok(TokenIdBalance {
+                amount: load_balance(deps, &address, &token_id)?,
+                address,
+            })
+        })
+        .collect::<StdResult<Vec<TokenIdBalance>>>()?;
+
+    let response = QueryAnswer::Holders {
+        holders,
+        holder_count: holder_count(deps.storage, &token_id)?,
+        next_start_after,
+    };
+    to_binary(&response)
+}
+
+fn query_frozen(deps: Deps, address: Addr, token_id: Option<String>) -> StdResult<Binary> {
+    let response = QueryAnswer::Frozen {
+        frozen: is_frozen(deps.storage, &address, token_id.as_deref())?,
+    };
+    to_binary(&response)
+}
+
+fn query_compliance_log(deps: Deps, page: u32, page_size: u32) -> StdResult<Binary> {
+    let (events, total) = get_compliance_log(deps.storage, page, query_limit(Some(page_size)))?;
+    let response = QueryAnswer::ComplianceLog { events, total };
+    to_binary(&response)
+}
+
+/// `viewer` is `None` for the public query, which can only view offers created with `public`
+fn query_swap_offer(deps: Deps, viewer: Option<&Addr>, swap_id: u64) -> StdResult<Binary> {
+    let offer = swaps_r(deps.storage)
+        .may_load(&swap_id.to_be_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("swap offer {} does not exist", swap_id)))?;
+    let can_view = offer.public
+        || viewer.is_some_and(|viewer| {
+            viewer == &offer.creator
+                || offer.counterparty.is_none()
+                || offer.counterparty.as_ref() == Some(viewer)
+        });
+    if !can_view {
+        return Err(StdError::generic_err(format!(
+            "you do not have permission to view swap offer {}",
+            swap_id
+        )));
+    }
+    to_binary(&QueryAnswer::SwapOffer(offer))
+}
+
+fn query_user_of(deps: Deps, token_id: String) -> StdResult<Binary> {
+    let block = latest_block(deps)?;
+    let nft_user = may_load_nft_user(deps.storage, &block, &token_id)?;
+    let response = QueryAnswer::UserOf {
+        user: nft_user.as_ref().map(|nft_user| nft_user.user.clone()),
+        expires: nft_user.map(|nft_user| nft_user.expires),
+    };
+    to_binary(&response)
+}
+
 fn query_token_id_private_info(deps: Deps, viewer: &Addr, token_id: String) -> StdResult<Binary> {
     let tkn_info_op = tkn_info_r(deps.storage).may_load(token_id.as_bytes())?;
     if tkn_info_op.is_none() {
@@ -230,9 +692,13 @@ fn query_token_id_private_info(deps: Deps, viewer: &Addr, token_id: String) -> S
             Some(_) => unreachable!("should not reach here"),
         };
 
+    // the unexpired user of an nft can also view private metadata
+    let viewer_is_user = may_load_nft_user(deps.storage, &latest_block(deps)?, &token_id)?
+        .is_some_and(|nft_user| &nft_user.user == viewer);
+
     // If request owns at least 1 token, can view `private_metadata`. Otherwise check viewership permissions (permission only applicable to nfts, as
     // fungible tokens have no current `owner`).
-    if !viewer_owns_some_tokens {
+    if !viewer_owns_some_tokens && !viewer_is_user {
         let permission_op = may_load_any_permission(
             deps.storage,
             // if no owner, = "" ie blank string => will not have any permission
@@ -247,15 +713,7 @@ fn query_token_id_private_info(deps: Deps, viewer: &Addr, token_id: String) -> S
                 ))
             }
             Some(perm) => {
-                let block: BlockInfo =
-                    blockinfo_r(deps.storage)
-                        .may_load()?
-                        .unwrap_or_else(|| BlockInfo {
-                            height: 1,
-                            time: Timestamp::from_seconds(1),
-                            chain_id: "not used".to_string(),
-                            random: None,
-                        });
+                let block = latest_block(deps)?;
                 if !perm.check_view_pr_metadata_perm(&block) {
                     tkn_info.private_metadata = None
                 };
@@ -290,9 +748,236 @@ fn query_registered_code_hash(deps: Deps, contract: Addr) -> StdResult<Binary> {
     to_binary(&response)
 }
 
-fn query_balance(deps: Deps, owner: &Addr, viewer: &Addr, token_id: String) -> StdResult<Binary> {
+fn query_auto_curate_template(deps: Deps) -> StdResult<Binary> {
+    let template = contr_conf_r(deps.storage).load()?.auto_curate_template;
+    let response = QueryAnswer::AutoCurateTemplate { template };
+    to_binary(&response)
+}
+
+fn query_reserve(deps: Deps, token_id: String) -> StdResult<Binary> {
+    let backing = match tkn_backing_r(deps.storage).may_load(token_id.as_bytes())? {
+        None => {
+            return Err(StdError::generic_err(format!(
+                "token_id {} does not exist or is not backed by collateral",
+                token_id
+            )))
+        }
+        Some(i) => i,
+    };
+    let response = QueryAnswer::Reserve {
+        backing,
+        reserve: tkn_reserve_r(deps.storage).load(token_id.as_bytes())?,
+        total_supply: tkn_tot_supply_r(deps.storage).load(token_id.as_bytes())?,
+    };
+    to_binary(&response)
+}
+
+fn query_scoped_viewing_keys(deps: Deps, address: Addr) -> StdResult<Binary> {
+    let keys = scoped_vks_r(deps.storage)
+        .may_load(address.as_bytes())?
+        .unwrap_or_default();
+    let response = QueryAnswer::ScopedViewingKeys { keys };
+    to_binary(&response)
+}
+
+fn query_permit_nonce(deps: Deps, address: Addr) -> StdResult<Binary> {
+    let nonce = permit_nonces_r(deps.storage)
+        .may_load(address.as_bytes())?
+        .unwrap_or_default();
+    let response = QueryAnswer::PermitNonce { nonce };
+    to_binary(&response)
+}
+
+fn query_series_info(
+    deps: Deps,
+    series_id: String,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let mut series_info = match series_info_r(deps.storage).may_load(series_id.as_bytes())? {
+        None => {
+            return Err(StdError::generic_err(format!(
+                "series {} does not exist",
+                series_id
+            )))
+        }
+        Some(i) => i,
+    };
+
+    let owner_is_public = series_info.token_config.flatten().owner_is_public;
+    let start = start_after.unwrap_or(0).saturating_add(1);
+    let limit = query_limit(limit);
+    let mut editions: Vec<EditionInfo> = vec![];
+    for edition in (start..=series_info.minted_editions).take(limit as usize) {
+        let token_id = series_info.edition_token_id(edition);
+        // add owner if owner_is_public == true
+        let owner = if owner_is_public {
+            may_get_current_owner(deps.storage, &token_id)?
+        } else {
+            None
+        };
+        editions.push(EditionInfo {
+            edition,
+            token_id,
+            owner,
+        });
+    }
+
+    // private_metadata always == None for public info query
+    series_info.private_metadata = None;
+    let response = QueryAnswer::SeriesInfo {
+        series_info,
+        editions,
+    };
+    to_binary(&response)
+}
+
+/// `allowed_token_ids` restricts the query to a list of token_ids, if the query is made with a
+/// restricted permit
+fn query_balance(
+    deps: Deps,
+    owner: &Addr,
+    viewer: &Addr,
+    token_id: String,
+    allowed_token_ids: Option<&[String]>,
+) -> StdResult<Binary> {
+    check_view_balance(deps, owner, viewer, &token_id, allowed_token_ids)?;
+
+    let amount = load_balance(deps, owner, &token_id)?;
+    let response = QueryAnswer::Balance { amount };
+    to_binary(&response)
+}
+
+fn query_balance_at(
+    deps: Deps,
+    owner: &Addr,
+    viewer: &Addr,
+    token_id: String,
+    height: u64,
+    allowed_token_ids: Option<&[String]>,
+) -> StdResult<Binary> {
+    check_view_balance(deps, owner, viewer, &token_id, allowed_token_ids)?;
+
+    let amount = balance_at(deps.storage, &token_id, owner, height)?;
+    let response = QueryAnswer::BalanceAt { height, amount };
+    to_binary(&response)
+}
+
+fn query_total_supply_at(deps: Deps, token_id: String, height: u64) -> StdResult<Binary> {
+    check_public_total_supply(deps, &token_id)?;
+
+    let amount = total_supply_at(deps.storage, &token_id, height)?;
+    let response = QueryAnswer::TotalSupplyAt { height, amount };
+    to_binary(&response)
+}
+
+fn query_pending_rewards(
+    deps: Deps,
+    owner: &Addr,
+    token_id: String,
+    allowed_token_ids: Option<&[String]>,
+) -> StdResult<Binary> {
+    check_view_balance(deps, owner, owner, &token_id, allowed_token_ids)?;
+
+    let balance = load_balance(deps, owner, &token_id)?;
+    let rewards = pending_rewards(deps.storage, &token_id, owner, balance)?;
+    let response = QueryAnswer::PendingRewards { rewards };
+    to_binary(&response)
+}
+
+fn query_vesting_balance(
+    deps: Deps,
+    owner: &Addr,
+    viewer: &Addr,
+    token_id: String,
+    allowed_token_ids: Option<&[String]>,
+) -> StdResult<Binary> {
+    check_view_balance(deps, owner, viewer, &token_id, allowed_token_ids)?;
+
+    let block = latest_block(deps)?;
+    let balance = load_balance(deps, owner, &token_id)?;
+    let locked = locked_amount(deps.storage, &block, &token_id, owner)?;
+    let response = QueryAnswer::VestingBalance {
+        balance,
+        locked,
+        unlocked: balance.saturating_sub(locked),
+        grants: vesting_grants(deps.storage, &token_id, owner)?,
+    };
+    to_binary(&response)
+}
+
+fn query_time_weighted_balance(
+    deps: Deps,
+    owner: &Addr,
+    viewer: &Addr,
+    token_id: String,
+    start: u64,
+    end: u64,
+    allowed_token_ids: Option<&[String]>,
+) -> StdResult<Binary> {
+    check_view_balance(deps, owner, viewer, &token_id, allowed_token_ids)?;
+
+    let accumulated = accumulated_balance(deps.storage, &token_id, owner, start, end)?;
+    let response = QueryAnswer::TimeWeightedBalance {
+        start,
+        end,
+        accumulated,
+    };
+    to_binary(&response)
+}
+
+fn query_time_weighted_total_supply(
+    deps: Deps,
+    token_id: String,
+    start: u64,
+    end: u64,
+) -> StdResult<Binary> {
+    check_public_total_supply(deps, &token_id)?;
+
+    let accumulated = accumulated_supply(deps.storage, &token_id, start, end)?;
+    let response = QueryAnswer::TimeWeightedTotalSupply {
+        start,
+        end,
+        accumulated,
+    };
+    to_binary(&response)
+}
+
+/// checks that a token_id exists and has a public total supply
+fn check_public_total_supply(deps: Deps, token_id: &str) -> StdResult<()> {
+    let tkn_info = tkn_info_r(deps.storage)
+        .may_load(token_id.as_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("token_id {} does not exist", token_id)))?;
+    if !tkn_info.token_config.flatten().public_total_supply {
+        return Err(StdError::generic_err(format!(
+            "total supply of token_id {} is not public",
+            token_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// checks that `viewer` may view the balance of `owner`, either as the owner, or with a balance
+/// viewership permission
+fn check_view_balance(
+    deps: Deps,
+    owner: &Addr,
+    viewer: &Addr,
+    token_id: &str,
+    allowed_token_ids: Option<&[String]>,
+) -> StdResult<()> {
+    if let Some(allowed_token_ids) = allowed_token_ids {
+        if !allowed_token_ids.iter().any(|i| i == token_id) {
+            return Err(StdError::generic_err(format!(
+                "permit does not allow viewing balance of token_id {}",
+                token_id
+            )));
+        }
+    }
+
     if owner != viewer {
-        let permission_op = may_load_any_permission(deps.storage, owner, &token_id, viewer)?;
+        let permission_op = may_load_any_permission(deps.storage, owner, token_id, viewer)?;
         match permission_op {
             None => {
                 return Err(StdError::generic_err(
@@ -300,15 +985,7 @@ fn query_balance(deps: Deps, owner: &Addr, viewer: &Addr, token_id: String) -> S
                 ))
             }
             Some(perm) => {
-                let block: BlockInfo =
-                    blockinfo_r(deps.storage)
-                        .may_load()?
-                        .unwrap_or_else(|| BlockInfo {
-                            height: 1,
-                            time: Timestamp::from_seconds(1),
-                            chain_id: "not used".to_string(),
-                            random: None,
-                        });
+                let block = latest_block(deps)?;
                 if !perm.check_view_balance_perm(&block) {
                     return Err(StdError::generic_err(
                         "you do have have permission to view balance",
@@ -319,15 +996,63 @@ fn query_balance(deps: Deps, owner: &Addr, viewer: &Addr, token_id: String) -> S
         }
     }
 
+    Ok(())
+}
+
+fn query_balance_of_batch(
+    deps: Deps,
+    viewer: &Addr,
+    owners: Vec<Addr>,
+    token_ids: Vec<String>,
+    allowed_token_ids: Option<&[String]>,
+) -> StdResult<Binary> {
+    if owners.len().saturating_mul(token_ids.len()) > MAX_QUERY_LIMIT as usize {
+        return Err(StdError::generic_err(format!(
+            "balance_of_batch can query at most {} (owner, token_id) pairs",
+            MAX_QUERY_LIMIT
+        )));
+    }
+    let block = latest_block(deps)?;
+
+    let mut balances: Vec<BatchBalance> = vec![];
+    for owner in owners {
+        for token_id in token_ids.iter() {
+            let mut can_view = match allowed_token_ids {
+                Some(allowed_token_ids) => allowed_token_ids.contains(token_id),
+                None => true,
+            };
+            if can_view && &owner != viewer {
+                can_view = may_load_any_permission(deps.storage, &owner, token_id, viewer)?
+                    .map(|perm| perm.check_view_balance_perm(&block))
+                    .unwrap_or(false);
+            }
+            let amount = match can_view {
+                true => Some(load_balance(deps, &owner, token_id)?),
+                false => None,
+            };
+            balances.push(BatchBalance {
+                owner: owner.clone(),
+                token_id: token_id.clone(),
+                amount,
+            });
+        }
+    }
+
+    let response = QueryAnswer::BalanceOfBatch { balances };
+    to_binary(&response)
+}
+
+/// loads the balance of an owner, which is zero if the owner has never held the token_id
+fn load_balance(deps: Deps, owner: &Addr, token_id: &str) -> StdResult<Uint256> {
     let owner_canon = deps.api.addr_canonicalize(owner.as_str())?;
-    let amount_op = balances_r(deps.storage, &token_id)
+    let amount_op = balances_r(deps.storage, token_id)
         .may_load(to_binary(&deps.api.addr_humanize(&owner_canon)?)?.as_slice())?;
     let amount = match amount_op {
         Some(i) => i,
         None => Uint256::from(0_u64),
     };
-    let response = QueryAnswer::Balance { amount };
-    to_binary(&response)
+
+    Ok(amount)
 }
 
 fn query_all_balances(
@@ -335,6 +1060,7 @@ fn query_all_balances(
     account: &Addr,
     tx_history_page: Option<u32>,
     tx_history_page_size: Option<u32>,
+    allowed_token_ids: Option<&[String]>,
 ) -> StdResult<Binary> {
     let address = deps.api.addr_canonicalize(account.as_str())?;
     let (txs, _total) = get_txs(
@@ -349,6 +1075,10 @@ fn query_all_balances(
     let token_ids = txs
         .into_iter()
         .map(|tx| tx.token_id)
+        .filter(|token_id| match allowed_token_ids {
+            Some(ids) => ids.contains(token_id),
+            None => true,
+        })
         .collect::<BTreeSet<_>>();
 
     // get balances for this list of token_ids, only if balance == Some(_), ie: user has had some balance before
@@ -3,19 +3,24 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{to_binary, Addr, Binary, CosmosMsg, StdResult, Uint256, WasmMsg};
+use cosmwasm_std::{to_binary, Addr, Binary, CosmosMsg, StdError, StdResult, Uint256, WasmMsg};
 
 use crate::{msg::space_pad, state::RESPONSE_BLOCK_SIZE};
 
+/// `from` of the `Snip1155Receive` callback of minted tokens. It is not a valid address, so it
+/// cannot be mistaken for a previous owner
+pub const MINT_FROM_MARKER: &str = "mint";
+
 /// Snip1155ReceiveMsg should be de/serialized under `Snip1155Receive()` variant in a HandleMsg
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct Snip1155ReceiveMsg {
-    /// the address that sent the `Send` or `BatchSend` message
+    /// the address that sent the `Send` or `BatchSend` message, or that minted the tokens
     pub sender: Addr,
     /// unique token_id `String`
     pub token_id: String,
-    /// the previous owner of the tokens being transferred
+    /// the previous owner of the tokens being transferred, or [MINT_FROM_MARKER] if the tokens
+    /// were minted
     pub from: Addr,
     /// amount of tokens being transferred
     pub amount: Uint256,
@@ -66,6 +71,79 @@ impl Snip1155ReceiveMsg {
     }
 }
 
+/// answer a receiver sets as the `data` of its `Snip1155Receive` response. Required when tokens
+/// are sent with the `safe_with_reply` send mode, where the transaction is rolled back unless
+/// `accepted` == true
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiverHandleAnswer {
+    Snip1155Receive { accepted: bool },
+}
+
+/// reads the `data` field of a protobuf `MsgExecuteContractResponse`, which wraps the data set by
+/// an executed contract in the reply to its submessage. Returns `None` if the contract set no
+/// data. Same as `parse_execute_response_data` in cw-utils
+pub fn parse_execute_response_data(data: &[u8]) -> StdResult<Option<Binary>> {
+    let (inner, _) = parse_protobuf_length_prefixed(data, 1)?;
+    if inner.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Binary::from(inner)))
+}
+
+const WIRE_TYPE_LENGTH_DELIMITED: u8 = 2;
+const VARINT_MAX_BYTES: usize = 9;
+
+/// reads a length-delimited protobuf field, returning its bytes and the rest of the message
+fn parse_protobuf_length_prefixed(data: &[u8], field_number: u8) -> StdResult<(&[u8], &[u8])> {
+    let (key, rest) = match data.split_first() {
+        Some(split) => split,
+        None => return Ok((&[], &[])),
+    };
+    if key >> 3 != field_number {
+        return Err(StdError::generic_err(format!(
+            "failed to decode Protobuf message: invalid field #{} for field #{}",
+            key >> 3,
+            field_number
+        )));
+    }
+    if key & 0b111 != WIRE_TYPE_LENGTH_DELIMITED {
+        return Err(StdError::generic_err(format!(
+            "failed to decode Protobuf message: field #{}: invalid wire type {}",
+            field_number,
+            key & 0b111
+        )));
+    }
+    let (len, rest) = parse_protobuf_varint(rest, field_number)?;
+    if rest.len() < len {
+        return Err(StdError::generic_err(format!(
+            "failed to decode Protobuf message: field #{}: message too short",
+            field_number
+        )));
+    }
+    Ok(rest.split_at(len))
+}
+
+/// reads a protobuf varint, returning its value and the rest of the message
+fn parse_protobuf_varint(data: &[u8], field_number: u8) -> StdResult<(usize, &[u8])> {
+    let mut value: u64 = 0;
+    for (i, byte) in data.iter().take(VARINT_MAX_BYTES).enumerate() {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value as usize, &data[i + 1..]));
+        }
+    }
+    let problem = if data.len() < VARINT_MAX_BYTES {
+        "too short"
+    } else {
+        "too long"
+    };
+    Err(StdError::generic_err(format!(
+        "failed to decode Protobuf message: field #{}: varint data {}",
+        field_number, problem
+    )))
+}
+
 // This is just a helper to properly serialize the above message
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -0,0 +1,104 @@
+#![allow(clippy::field_reassign_with_default)] // This is triggered in `#[derive(JsonSchema)]`
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{
+    to_binary, Addr, CosmosMsg, QuerierWrapper, QueryRequest, StdResult, Uint128, WasmMsg,
+    WasmQuery,
+};
+
+use crate::{msg::space_pad, state::RESPONSE_BLOCK_SIZE};
+
+/// message expected in the `msg` field of a SNIP20 `Send` to this contract
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Snip20ReceiveAction {
+    /// mints shares of a token_id backed by the sending SNIP20 contract to the owner of the
+    /// SNIP20 tokens sent
+    Deposit {
+        token_id: String,
+        memo: Option<String>,
+    },
+}
+
+/// handle messages this contract sends to SNIP20 contracts that back token_ids or are distributed
+/// as rewards
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Snip20HandleMsg {
+    RegisterReceive {
+        code_hash: String,
+        padding: Option<String>,
+    },
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+        memo: Option<String>,
+        padding: Option<String>,
+    },
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+        memo: Option<String>,
+        padding: Option<String>,
+    },
+}
+
+impl Snip20HandleMsg {
+    /// creates a cosmos_msg sending this message to the named SNIP20 contract, padded to 256 bytes
+    pub fn into_cosmos_msg(self, code_hash: String, contract_addr: Addr) -> StdResult<CosmosMsg> {
+        let mut msg = to_binary(&self)?;
+        space_pad(RESPONSE_BLOCK_SIZE, &mut msg.0);
+        let execute = WasmMsg::Execute {
+            msg,
+            code_hash,
+            contract_addr: contract_addr.to_string(),
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+/// queries this contract sends to SNIP20 contracts that back token_ids
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Snip20QueryMsg {
+    TokenInfo {},
+}
+
+impl Snip20QueryMsg {
+    /// queries the token info of a SNIP20 contract. Used to check that a contract address and
+    /// code hash belong to a SNIP20 contract before a token_id is bound to it
+    pub fn query_token_info(
+        querier: QuerierWrapper,
+        code_hash: String,
+        contract_addr: Addr,
+    ) -> StdResult<Snip20TokenInfo> {
+        let mut msg = to_binary(&Snip20QueryMsg::TokenInfo {})?;
+        space_pad(RESPONSE_BLOCK_SIZE, &mut msg.0);
+        let response: Snip20TokenInfoResponse =
+            querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: contract_addr.to_string(),
+                code_hash,
+                msg,
+            }))?;
+        Ok(response.token_info)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct Snip20TokenInfoResponse {
+    pub token_info: Snip20TokenInfo,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct Snip20TokenInfo {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub total_supply: Option<Uint128>,
+}
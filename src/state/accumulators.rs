@@ -0,0 +1,146 @@
+use super::*;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Addr, StdError, StdResult, Storage, Uint256};
+
+use secret_toolkit::storage::AppendStore;
+
+use crate::state::checkpoints::latest_at;
+
+pub static BALANCE_ACCUMULATORS: AppendStore<AccumulatorCheckpoint> =
+    AppendStore::new(PREFIX_BALANCE_ACCUMULATORS);
+pub static SUPPLY_ACCUMULATORS: AppendStore<AccumulatorCheckpoint> =
+    AppendStore::new(PREFIX_SUPPLY_ACCUMULATORS);
+
+/////////////////////////////////////////////////////////////////////////////////
+// Time-weighted balance and total supply accumulators
+/////////////////////////////////////////////////////////////////////////////////
+
+/// state of a balance×time accumulator after an amount changes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccumulatorCheckpoint {
+    /// the time (in seconds since 01/01/1970) of the change
+    pub time: u64,
+    /// the amount from `time` onwards
+    pub amount: Uint256,
+    /// sum of amount×seconds up to `time`
+    pub cumulative: Uint256,
+}
+
+/// accumulates the previous balance of an owner up to `time`, and records its new balance
+pub fn write_balance_accumulator(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    owner: &Addr,
+    time: u64,
+    amount: Uint256,
+) -> StdResult<()> {
+    let store = BALANCE_ACCUMULATORS
+        .add_suffix(token_id.as_bytes())
+        .add_suffix(to_binary(owner)?.as_slice());
+    write_accumulator(storage, &store, time, amount)
+}
+
+/// accumulates the previous total supply of a token_id up to `time`, and records its new total
+/// supply
+pub fn write_supply_accumulator(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    time: u64,
+    amount: Uint256,
+) -> StdResult<()> {
+    let store = SUPPLY_ACCUMULATORS.add_suffix(token_id.as_bytes());
+    write_accumulator(storage, &store, time, amount)
+}
+
+/// Returns StdResult<Uint256> of the sum of balance×seconds of an owner between `start` and `end`
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `token_id` - the token_id of the balance
+/// * `owner` - a reference to the owner of the balance
+/// * `start` - the start time (in seconds since 01/01/1970)
+/// * `end` - the end time (in seconds since 01/01/1970)
+pub fn accumulated_balance(
+    storage: &dyn Storage,
+    token_id: &str,
+    owner: &Addr,
+    start: u64,
+    end: u64,
+) -> StdResult<Uint256> {
+    let store = BALANCE_ACCUMULATORS
+        .add_suffix(token_id.as_bytes())
+        .add_suffix(to_binary(owner)?.as_slice());
+    accumulated_between(storage, &store, start, end)
+}
+
+/// Returns StdResult<Uint256> of the sum of total supply×seconds of a token_id between `start`
+/// and `end`
+pub fn accumulated_supply(
+    storage: &dyn Storage,
+    token_id: &str,
+    start: u64,
+    end: u64,
+) -> StdResult<Uint256> {
+    let store = SUPPLY_ACCUMULATORS.add_suffix(token_id.as_bytes());
+    accumulated_between(storage, &store, start, end)
+}
+
+/// a change at the same time as the latest checkpoint replaces its amount
+fn write_accumulator(
+    storage: &mut dyn Storage,
+    store: &AppendStore<AccumulatorCheckpoint>,
+    time: u64,
+    amount: Uint256,
+) -> StdResult<()> {
+    let len = store.get_len(storage)?;
+    let last_op = match len {
+        0 => None,
+        _ => Some(store.get_at(storage, len - 1)?),
+    };
+    let checkpoint = AccumulatorCheckpoint {
+        time,
+        amount,
+        cumulative: match &last_op {
+            Some(last) => cumulative_at(last, time)?,
+            None => Uint256::zero(),
+        },
+    };
+
+    match last_op {
+        Some(last) if last.time == time => store.set_at(storage, len - 1, &checkpoint),
+        _ => store.push(storage, &checkpoint),
+    }
+}
+
+fn accumulated_between(
+    storage: &dyn Storage,
+    store: &AppendStore<AccumulatorCheckpoint>,
+    start: u64,
+    end: u64,
+) -> StdResult<Uint256> {
+    if start > end {
+        return Err(StdError::generic_err(
+            "start time must not be after end time",
+        ));
+    }
+    let cumulative = |time: u64| -> StdResult<Uint256> {
+        match latest_at(storage, store, |i| i.time, time)? {
+            Some(checkpoint) => cumulative_at(&checkpoint, time),
+            None => Ok(Uint256::zero()),
+        }
+    };
+
+    Ok(cumulative(end)? - cumulative(start)?)
+}
+
+/// sum of amount×seconds up to `time`, which must not be before the checkpoint's time. Assumes
+/// the amount is unchanged after the checkpoint
+fn cumulative_at(checkpoint: &AccumulatorCheckpoint, time: u64) -> StdResult<Uint256> {
+    let elapsed = Uint256::from(time - checkpoint.time);
+    let accrued = checkpoint.amount.checked_mul(elapsed)?;
+    Ok(checkpoint.cumulative.checked_add(accrued)?)
+}
@@ -0,0 +1,129 @@
+use super::*;
+
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Addr, StdResult, Storage, Uint256};
+
+use secret_toolkit::storage::AppendStore;
+
+pub static BALANCE_CHECKPOINTS: AppendStore<Checkpoint> =
+    AppendStore::new(PREFIX_BALANCE_CHECKPOINTS);
+pub static SUPPLY_CHECKPOINTS: AppendStore<Checkpoint> =
+    AppendStore::new(PREFIX_SUPPLY_CHECKPOINTS);
+
+/////////////////////////////////////////////////////////////////////////////////
+// Balance and total supply checkpoints
+/////////////////////////////////////////////////////////////////////////////////
+
+/// an amount as of the end of a block
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Checkpoint {
+    pub height: u64,
+    pub amount: Uint256,
+}
+
+/// records the new balance of an owner at the current block height
+pub fn write_balance_checkpoint(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    owner: &Addr,
+    height: u64,
+    amount: Uint256,
+) -> StdResult<()> {
+    let store = BALANCE_CHECKPOINTS
+        .add_suffix(token_id.as_bytes())
+        .add_suffix(to_binary(owner)?.as_slice());
+    write_checkpoint(storage, &store, height, amount)
+}
+
+/// records the new total supply of a token_id at the current block height
+pub fn write_supply_checkpoint(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    height: u64,
+    amount: Uint256,
+) -> StdResult<()> {
+    let store = SUPPLY_CHECKPOINTS.add_suffix(token_id.as_bytes());
+    write_checkpoint(storage, &store, height, amount)
+}
+
+/// Returns StdResult<Uint256> of the balance of an owner at the end of block `height`
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `token_id` - the token_id of the balance
+/// * `owner` - a reference to the owner of the balance
+/// * `height` - the block height
+pub fn balance_at(
+    storage: &dyn Storage,
+    token_id: &str,
+    owner: &Addr,
+    height: u64,
+) -> StdResult<Uint256> {
+    let store = BALANCE_CHECKPOINTS
+        .add_suffix(token_id.as_bytes())
+        .add_suffix(to_binary(owner)?.as_slice());
+    amount_at(storage, &store, height)
+}
+
+/// Returns StdResult<Uint256> of the total supply of a token_id at the end of block `height`
+pub fn total_supply_at(storage: &dyn Storage, token_id: &str, height: u64) -> StdResult<Uint256> {
+    let store = SUPPLY_CHECKPOINTS.add_suffix(token_id.as_bytes());
+    amount_at(storage, &store, height)
+}
+
+/// a checkpoint written in the same block as the latest one replaces it, so there is at most one
+/// checkpoint per block
+fn write_checkpoint(
+    storage: &mut dyn Storage,
+    store: &AppendStore<Checkpoint>,
+    height: u64,
+    amount: Uint256,
+) -> StdResult<()> {
+    let checkpoint = Checkpoint { height, amount };
+    let len = store.get_len(storage)?;
+    if len > 0 && store.get_at(storage, len - 1)?.height == height {
+        return store.set_at(storage, len - 1, &checkpoint);
+    }
+
+    store.push(storage, &checkpoint)
+}
+
+/// binary search for the latest checkpoint at or before `height`. The amount is zero before the
+/// first checkpoint
+fn amount_at(
+    storage: &dyn Storage,
+    store: &AppendStore<Checkpoint>,
+    height: u64,
+) -> StdResult<Uint256> {
+    let checkpoint_op = latest_at(storage, store, |i| i.height, height)?;
+    Ok(checkpoint_op.map_or(Uint256::zero(), |i| i.amount))
+}
+
+/// binary search for the latest entry of an append store, sorted by `position`, with a position
+/// at or before `at`. Returns `None` if there is no such entry
+pub fn latest_at<T: Serialize + DeserializeOwned>(
+    storage: &dyn Storage,
+    store: &AppendStore<T>,
+    position: impl Fn(&T) -> u64,
+    at: u64,
+) -> StdResult<Option<T>> {
+    // number of entries at or before `at`
+    let mut low = 0_u32;
+    let mut high = store.get_len(storage)?;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if position(&store.get_at(storage, mid)?) <= at {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    match low {
+        0 => Ok(None),
+        i => Ok(Some(store.get_at(storage, i - 1)?)),
+    }
+}
@@ -0,0 +1,153 @@
+use super::*;
+
+use cosmwasm_std::{to_binary, Addr, BlockInfo, StdError, StdResult, Storage};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use secret_toolkit::storage::AppendStore;
+
+pub static COMPLIANCE_LOG: AppendStore<ComplianceEvent> = AppendStore::new(PREFIX_COMPLIANCE_LOG);
+
+/////////////////////////////////////////////////////////////////////////////////
+// Structs and enums
+/////////////////////////////////////////////////////////////////////////////////
+
+/// how a token_id's transfer list is applied to the addresses whose balances change
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferListMode {
+    /// the transfer list is ignored
+    #[default]
+    Disabled,
+    /// only addresses on the transfer list can send or receive tokens
+    AllowList,
+    /// addresses on the transfer list cannot send or receive tokens
+    DenyList,
+}
+
+/// compliance change made by the admin
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ComplianceAction {
+    SetFrozen {
+        address: Addr,
+        /// `None` if the address was frozen or unfrozen for all token_ids
+        token_id: Option<String>,
+        frozen: bool,
+    },
+    SetTransferListMode {
+        token_id: String,
+        mode: TransferListMode,
+    },
+    UpdateTransferList {
+        token_id: String,
+        add: Vec<Addr>,
+        remove: Vec<Addr>,
+    },
+}
+
+/// entry of the compliance audit log
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ComplianceEvent {
+    pub action: ComplianceAction,
+    pub admin: Addr,
+    pub block_height: u64,
+    /// seconds since 01/01/1970
+    pub block_time: u64,
+}
+
+/////////////////////////////////////////////////////////////////////////////////
+// Functions
+/////////////////////////////////////////////////////////////////////////////////
+
+/// returns true if `address` is frozen for all token_ids, or for `token_id` if given
+pub fn is_frozen(storage: &dyn Storage, address: &Addr, token_id: Option<&str>) -> StdResult<bool> {
+    let address_key = to_binary(address)?;
+    if frozen_r(storage)
+        .may_load(address_key.as_slice())?
+        .unwrap_or(false)
+    {
+        return Ok(true);
+    }
+    match token_id {
+        Some(token_id) => Ok(tkn_frozen_r(storage, token_id)
+            .may_load(address_key.as_slice())?
+            .unwrap_or(false)),
+        None => Ok(false),
+    }
+}
+
+/// verifies that the balance of `address` in a token_id may change: the address must not be
+/// frozen, and must pass the token_id's allow list or deny list. The contract itself, which holds
+/// balances in escrow for swap offers, does not need to be on an allow list
+pub fn check_compliance(storage: &dyn Storage, token_id: &str, address: &Addr) -> StdResult<()> {
+    if is_frozen(storage, address, Some(token_id))? {
+        return Err(StdError::generic_err(format!(
+            "address {} is frozen",
+            address
+        )));
+    }
+
+    let mode = transfer_list_mode_r(storage)
+        .may_load(token_id.as_bytes())?
+        .unwrap_or_default();
+    let listed = || -> StdResult<bool> {
+        Ok(transfer_list_r(storage, token_id)
+            .may_load(to_binary(address)?.as_slice())?
+            .unwrap_or(false))
+    };
+    let is_escrow =
+        || -> StdResult<bool> { Ok(address == &contr_conf_r(storage).load()?.contract_address) };
+    match mode {
+        TransferListMode::Disabled => (),
+        TransferListMode::AllowList if !listed()? && !is_escrow()? => {
+            return Err(StdError::generic_err(format!(
+                "address {} is not on the allow list of token_id {}",
+                address, token_id
+            )))
+        }
+        TransferListMode::DenyList if listed()? => {
+            return Err(StdError::generic_err(format!(
+                "address {} is on the deny list of token_id {}",
+                address, token_id
+            )))
+        }
+        TransferListMode::AllowList | TransferListMode::DenyList => (),
+    }
+
+    Ok(())
+}
+
+/// appends a compliance change to the audit log
+pub fn append_compliance_event(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    admin: &Addr,
+    action: ComplianceAction,
+) -> StdResult<()> {
+    let event = ComplianceEvent {
+        action,
+        admin: admin.clone(),
+        block_height: block.height,
+        block_time: block.time.seconds(),
+    };
+    COMPLIANCE_LOG.push(storage, &event)
+}
+
+/// Returns StdResult<(Vec<ComplianceEvent>, u64)> of a page of the audit log, starting from the
+/// latest event, and the total number of events
+pub fn get_compliance_log(
+    storage: &dyn Storage,
+    page: u32,
+    page_size: u32,
+) -> StdResult<(Vec<ComplianceEvent>, u64)> {
+    let count = COMPLIANCE_LOG.get_len(storage)? as u64;
+    let events: StdResult<Vec<ComplianceEvent>> = COMPLIANCE_LOG
+        .iter(storage)?
+        .rev()
+        .skip((page as usize).saturating_mul(page_size as usize))
+        .take(page_size as usize)
+        .collect();
+
+    events.map(|events| (events, count))
+}
@@ -0,0 +1,133 @@
+use super::*;
+
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Addr, StdError, StdResult, Storage, Uint256};
+
+use secret_toolkit::storage::AppendStore;
+
+pub static HOLDER_STORE: AppendStore<HolderEntry> = AppendStore::new(PREFIX_HOLDERS);
+
+/// an address in the holder index of a token_id
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HolderEntry {
+    pub address: Addr,
+    /// false once the address no longer holds any tokens
+    pub holding: bool,
+}
+
+/////////////////////////////////////////////////////////////////////////////////
+// Holder index (for token_ids with `public_holders` only)
+/////////////////////////////////////////////////////////////////////////////////
+
+/// adds or removes `holder` from the holder index of a token_id, depending on its new balance.
+/// Addresses keep their position in the index after they stop holding tokens, and take it back
+/// if they hold tokens again, so the order of the index is stable
+pub fn update_holder(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    holder: &Addr,
+    new_balance: Uint256,
+) -> StdResult<()> {
+    let holder_key = to_binary(holder)?;
+    let position_op = holder_positions_r(storage, token_id).may_load(holder_key.as_slice())?;
+    let token_id_store = HOLDER_STORE.add_suffix(token_id.as_bytes());
+    let holding = !new_balance.is_zero();
+
+    let was_holding = match position_op {
+        // first balance change of the address. Addresses with a zero balance, such as decoys, are
+        // also indexed, so that they cannot be told apart from new holders
+        None => {
+            let position = token_id_store.get_len(storage)?;
+            token_id_store.push(
+                storage,
+                &HolderEntry {
+                    address: holder.clone(),
+                    holding,
+                },
+            )?;
+            holder_positions_w(storage, token_id).save(holder_key.as_slice(), &position)?;
+            false
+        }
+        // the entry and count are rewritten even if unchanged, so that decoys cannot be told
+        // apart from real recipients
+        Some(position) => {
+            let mut entry = token_id_store.get_at(storage, position)?;
+            let was_holding = entry.holding;
+            entry.holding = holding;
+            token_id_store.set_at(storage, position, &entry)?;
+            was_holding
+        }
+    };
+
+    let count = match (was_holding, holding) {
+        (false, true) => holder_count(storage, token_id)? + 1,
+        (true, false) => holder_count(storage, token_id)? - 1,
+        _ => holder_count(storage, token_id)?,
+    };
+    holder_counts_w(storage).save(token_id.as_bytes(), &count)?;
+
+    Ok(())
+}
+
+/// Returns StdResult<(Vec<Addr>, Option<Addr>)> of the current holders among the next `limit`
+/// entries of the holder index of a token_id after the position of `start_after`, and the address
+/// to start after for the next page, if the index has more entries. At most `limit` entries are
+/// read, so a page can hold fewer than `limit` holders, or none, if many addresses left.
+/// `start_after` can be an address that no longer holds any tokens, so pagination continues where
+/// it left off
+///
+/// # Arguments
+///
+/// * `storage` - a reference to the contract's storage
+/// * `token_id` - the token_id whose holders to display
+/// * `start_after` - optional holder to start after, for pagination
+/// * `limit` - maximum number of index entries to read
+pub fn get_holders(
+    storage: &dyn Storage,
+    token_id: &str,
+    start_after: Option<&Addr>,
+    limit: u32,
+) -> StdResult<(Vec<Addr>, Option<Addr>)> {
+    let skip = match start_after {
+        None => 0,
+        Some(holder) => {
+            let position = holder_positions_r(storage, token_id)
+                .may_load(to_binary(holder)?.as_slice())?
+                .ok_or_else(|| {
+                    StdError::generic_err(format!(
+                        "{} has never held token_id {}",
+                        holder, token_id
+                    ))
+                })?;
+            position + 1
+        }
+    };
+
+    let token_id_store = HOLDER_STORE.add_suffix(token_id.as_bytes());
+    let end = token_id_store
+        .get_len(storage)?
+        .min(skip.saturating_add(limit));
+    let mut holders = vec![];
+    let mut last = None;
+    for position in skip..end {
+        let entry = token_id_store.get_at(storage, position)?;
+        if entry.holding {
+            holders.push(entry.address.clone());
+        }
+        last = Some(entry.address);
+    }
+    let next_start_after = if end < token_id_store.get_len(storage)? {
+        last
+    } else {
+        None
+    };
+    Ok((holders, next_start_after))
+}
+
+/// number of addresses holding a non-zero balance of a token_id
+pub fn holder_count(storage: &dyn Storage, token_id: &str) -> StdResult<u32> {
+    Ok(holder_counts_r(storage)
+        .may_load(token_id.as_bytes())?
+        .unwrap_or_default())
+}
@@ -1,30 +1,70 @@
+pub mod accumulators;
+pub mod checkpoints;
+pub mod compliance;
 pub mod expiration;
+pub mod holders;
 pub mod metadata;
 pub mod permissions;
+pub mod rewards;
 mod save_load_functions;
 pub mod state_structs;
 pub mod txhistory;
+pub mod vesting;
 
 use cosmwasm_std::{to_binary, Addr, BlockInfo, StdError, StdResult, Storage, Uint256};
 
+use secret_toolkit::viewing_key::ViewingKeyStore;
+
 use cosmwasm_storage::{
     bucket, bucket_read, singleton, singleton_read, Bucket, PrefixedStorage, ReadonlyBucket,
     ReadonlyPrefixedStorage, ReadonlySingleton, Singleton,
 };
 
 use self::{
+    compliance::TransferListMode,
     expiration::Expiration,
     permissions::Permission,
-    state_structs::{ContractConfig, StoredTokenInfo},
+    rewards::{HolderReward, RewardPool},
+    state_structs::{
+        ContractConfig, NftUser, ScopedViewingKeyInfo, StoredSeriesInfo, StoredTokenInfo,
+        SwapOffer, TokenBacking,
+    },
+    vesting::VestingGrant,
 };
 
 pub const RESPONSE_BLOCK_SIZE: usize = 256;
+/// id of the submessage replies to `Snip1155Receive` callbacks sent with the `safe_with_reply`
+/// send mode
+pub const RECEIVE_REPLY_ID: u64 = 1;
+/// default maximum number of sub-queries in a `Batch` query
+pub const DEFAULT_MAX_BATCH_QUERIES: u32 = 16;
+/// number of entries returned by paginated queries when no `limit` is given
+pub const DEFAULT_QUERY_LIMIT: u32 = 30;
+/// maximum number of entries returned by a page of a paginated query
+pub const MAX_QUERY_LIMIT: u32 = 100;
 
 // namespaces
 pub const CONTR_CONF: &[u8] = b"contrconfig";
 pub const TKN_TOTAL_SUPPLY: &[u8] = b"totalsupply";
 pub const BALANCES: &[u8] = b"balances";
 pub const TKN_INFO: &[u8] = b"tokeninfo";
+pub const SERIES_INFO: &[u8] = b"seriesinfo";
+pub const TKN_BACKING: &[u8] = b"tokenbacking";
+pub const TKN_RESERVE: &[u8] = b"tokenreserve";
+pub const PERMIT_NONCES: &[u8] = b"permitnonces";
+pub const SCOPED_VKS: &[u8] = b"scopedvks";
+pub const HOLDER_POSITIONS: &[u8] = b"holderpositions";
+pub const HOLDER_COUNTS: &[u8] = b"holdercounts";
+pub const TKN_REWARDS: &[u8] = b"tokenrewards";
+pub const HOLDER_REWARDS: &[u8] = b"holderrewards";
+pub const TRANSFER_HOOKS: &[u8] = b"transferhooks";
+pub const FROZEN: &[u8] = b"frozen";
+pub const TKN_FROZEN: &[u8] = b"tokenfrozen";
+pub const TRANSFER_LIST_MODE: &[u8] = b"transferlistmode";
+pub const TRANSFER_LIST: &[u8] = b"transferlist";
+pub const VESTING: &[u8] = b"vesting";
+pub const SWAPS: &[u8] = b"swaps";
+pub const NFT_USERS: &[u8] = b"nftusers";
 /// storage key for the BlockInfo when the last handle was executed
 pub const BLOCK_KEY: &[u8] = b"blockinfo";
 
@@ -40,6 +80,18 @@ pub const PREFIX_PERMISSIONS: &[u8] = b"permissions";
 pub const PREFIX_PERMISSION_ID: &[u8] = b"permid";
 pub const PREFIX_REVOKED_PERMITS: &str = "revokedperms";
 pub const PREFIX_RECEIVERS: &[u8] = b"s1155receivers";
+/// prefix for the holder index of token_ids with `public_holders`
+pub const PREFIX_HOLDERS: &[u8] = b"holders";
+/// prefix for the balance history of each owner and token_id
+pub const PREFIX_BALANCE_CHECKPOINTS: &[u8] = b"balancecheckpoints";
+/// prefix for the total supply history of each token_id
+pub const PREFIX_SUPPLY_CHECKPOINTS: &[u8] = b"supplycheckpoints";
+/// prefix for the balance×time accumulators of each owner and token_id
+pub const PREFIX_BALANCE_ACCUMULATORS: &[u8] = b"balanceaccumulators";
+/// prefix for the total supply×time accumulators of each token_id
+pub const PREFIX_SUPPLY_ACCUMULATORS: &[u8] = b"supplyaccumulators";
+/// prefix for the audit log of compliance changes
+pub const PREFIX_COMPLIANCE_LOG: &[u8] = b"compliancelog";
 
 /////////////////////////////////////////////////////////////////////////////////
 // Singletons
@@ -76,6 +128,15 @@ pub fn tkn_info_r(storage: &dyn Storage) -> ReadonlyBucket<StoredTokenInfo> {
     bucket_read(storage, TKN_INFO)
 }
 
+/// NFT series configs. Key is `series_id.as_bytes()`
+pub fn series_info_w(storage: &mut dyn Storage) -> Bucket<'_, StoredSeriesInfo> {
+    bucket(storage, SERIES_INFO)
+}
+/// NFT series configs. Key is `series_id.as_bytes()`
+pub fn series_info_r(storage: &dyn Storage) -> ReadonlyBucket<'_, StoredSeriesInfo> {
+    bucket_read(storage, SERIES_INFO)
+}
+
 /// total supply of a token_id. Key is `token_id.as_bytes()`
 pub fn tkn_tot_supply_w(storage: &mut dyn Storage) -> Bucket<Uint256> {
     bucket(storage, TKN_TOTAL_SUPPLY)
@@ -85,6 +146,42 @@ pub fn tkn_tot_supply_r(storage: &dyn Storage) -> ReadonlyBucket<Uint256> {
     bucket_read(storage, TKN_TOTAL_SUPPLY)
 }
 
+/// collateral backing a token_id, if any. Key is `token_id.as_bytes()`
+pub fn tkn_backing_w(storage: &mut dyn Storage) -> Bucket<'_, TokenBacking> {
+    bucket(storage, TKN_BACKING)
+}
+/// collateral backing a token_id, if any. Key is `token_id.as_bytes()`
+pub fn tkn_backing_r(storage: &dyn Storage) -> ReadonlyBucket<'_, TokenBacking> {
+    bucket_read(storage, TKN_BACKING)
+}
+
+/// amount of collateral held for a backed token_id. Key is `token_id.as_bytes()`
+pub fn tkn_reserve_w(storage: &mut dyn Storage) -> Bucket<'_, Uint256> {
+    bucket(storage, TKN_RESERVE)
+}
+/// amount of collateral held for a backed token_id. Key is `token_id.as_bytes()`
+pub fn tkn_reserve_r(storage: &dyn Storage) -> ReadonlyBucket<'_, Uint256> {
+    bucket_read(storage, TKN_RESERVE)
+}
+
+/// next nonce expected in an address's signed execute permit. Key is `address.as_bytes()`
+pub fn permit_nonces_w(storage: &mut dyn Storage) -> Bucket<'_, u64> {
+    bucket(storage, PERMIT_NONCES)
+}
+/// next nonce expected in an address's signed execute permit. Key is `address.as_bytes()`
+pub fn permit_nonces_r(storage: &dyn Storage) -> ReadonlyBucket<'_, u64> {
+    bucket_read(storage, PERMIT_NONCES)
+}
+
+/// labels, scopes and expirations of an address's scoped viewing keys. Key is `address.as_bytes()`
+pub fn scoped_vks_w(storage: &mut dyn Storage) -> Bucket<'_, Vec<ScopedViewingKeyInfo>> {
+    bucket(storage, SCOPED_VKS)
+}
+/// labels, scopes and expirations of an address's scoped viewing keys. Key is `address.as_bytes()`
+pub fn scoped_vks_r(storage: &dyn Storage) -> ReadonlyBucket<'_, Vec<ScopedViewingKeyInfo>> {
+    bucket_read(storage, SCOPED_VKS)
+}
+
 /////////////////////////////////////////////////////////////////////////////////
 // Multi-level Buckets
 /////////////////////////////////////////////////////////////////////////////////
@@ -101,6 +198,141 @@ pub fn balances_r<'a>(storage: &'a dyn Storage, token_id: &str) -> ReadonlyBucke
     ReadonlyBucket::multilevel(storage, &[BALANCES, token_id.as_bytes()])
 }
 
+/// Multilevel bucket to store the position of each holder in the holder index of a token_id. Key
+/// is to be [`token_id`, `holder`: to_binary(&Addr)?.as_slice()]
+pub fn holder_positions_w<'a>(storage: &'a mut dyn Storage, token_id: &str) -> Bucket<'a, u32> {
+    Bucket::multilevel(storage, &[HOLDER_POSITIONS, token_id.as_bytes()])
+}
+/// Multilevel bucket to read the position of each holder in the holder index of a token_id
+pub fn holder_positions_r<'a>(storage: &'a dyn Storage, token_id: &str) -> ReadonlyBucket<'a, u32> {
+    ReadonlyBucket::multilevel(storage, &[HOLDER_POSITIONS, token_id.as_bytes()])
+}
+
+/// number of current holders of a token_id. Key is `token_id.as_bytes()`
+pub fn holder_counts_w(storage: &mut dyn Storage) -> Bucket<'_, u32> {
+    bucket(storage, HOLDER_COUNTS)
+}
+/// number of current holders of a token_id. Key is `token_id.as_bytes()`
+pub fn holder_counts_r(storage: &dyn Storage) -> ReadonlyBucket<'_, u32> {
+    bucket_read(storage, HOLDER_COUNTS)
+}
+
+/// Reward pools of each token_id. Key is to be `token_id`
+pub fn tkn_rewards_w(storage: &mut dyn Storage) -> Bucket<'_, Vec<RewardPool>> {
+    bucket(storage, TKN_REWARDS)
+}
+/// Reward pools of each token_id
+pub fn tkn_rewards_r(storage: &dyn Storage) -> ReadonlyBucket<'_, Vec<RewardPool>> {
+    bucket_read(storage, TKN_REWARDS)
+}
+
+/// Multilevel bucket to store the rewards of each holder of a token_id. Key is to be
+/// [`token_id`, `holder`: to_binary(&Addr)?.as_slice()]
+pub fn holder_rewards_w<'a>(
+    storage: &'a mut dyn Storage,
+    token_id: &str,
+) -> Bucket<'a, Vec<HolderReward>> {
+    Bucket::multilevel(storage, &[HOLDER_REWARDS, token_id.as_bytes()])
+}
+/// Multilevel bucket to read the rewards of each holder of a token_id
+pub fn holder_rewards_r<'a>(
+    storage: &'a dyn Storage,
+    token_id: &str,
+) -> ReadonlyBucket<'a, Vec<HolderReward>> {
+    ReadonlyBucket::multilevel(storage, &[HOLDER_REWARDS, token_id.as_bytes()])
+}
+
+/// addresses frozen for all token_ids. Key is to_binary(&Addr)?.as_slice()
+pub fn frozen_w(storage: &mut dyn Storage) -> Bucket<'_, bool> {
+    bucket(storage, FROZEN)
+}
+/// addresses frozen for all token_ids. Key is to_binary(&Addr)?.as_slice()
+pub fn frozen_r(storage: &dyn Storage) -> ReadonlyBucket<'_, bool> {
+    bucket_read(storage, FROZEN)
+}
+
+/// Multilevel bucket to store the addresses frozen for a token_id. Key is to be
+/// [`token_id`, `address`: to_binary(&Addr)?.as_slice()]
+pub fn tkn_frozen_w<'a>(storage: &'a mut dyn Storage, token_id: &str) -> Bucket<'a, bool> {
+    Bucket::multilevel(storage, &[TKN_FROZEN, token_id.as_bytes()])
+}
+/// Multilevel bucket to read the addresses frozen for a token_id
+pub fn tkn_frozen_r<'a>(storage: &'a dyn Storage, token_id: &str) -> ReadonlyBucket<'a, bool> {
+    ReadonlyBucket::multilevel(storage, &[TKN_FROZEN, token_id.as_bytes()])
+}
+
+/// how the transfer list of a token_id is applied. Key is `token_id.as_bytes()`
+pub fn transfer_list_mode_w(storage: &mut dyn Storage) -> Bucket<'_, TransferListMode> {
+    bucket(storage, TRANSFER_LIST_MODE)
+}
+/// how the transfer list of a token_id is applied. Key is `token_id.as_bytes()`
+pub fn transfer_list_mode_r(storage: &dyn Storage) -> ReadonlyBucket<'_, TransferListMode> {
+    bucket_read(storage, TRANSFER_LIST_MODE)
+}
+
+/// Multilevel bucket to store the addresses on the transfer list of a token_id. Key is to be
+/// [`token_id`, `address`: to_binary(&Addr)?.as_slice()]
+pub fn transfer_list_w<'a>(storage: &'a mut dyn Storage, token_id: &str) -> Bucket<'a, bool> {
+    Bucket::multilevel(storage, &[TRANSFER_LIST, token_id.as_bytes()])
+}
+/// Multilevel bucket to read the addresses on the transfer list of a token_id
+pub fn transfer_list_r<'a>(storage: &'a dyn Storage, token_id: &str) -> ReadonlyBucket<'a, bool> {
+    ReadonlyBucket::multilevel(storage, &[TRANSFER_LIST, token_id.as_bytes()])
+}
+
+/// Multilevel bucket to store the vesting grants of each holder of a token_id. Key is to be
+/// [`token_id`, `holder`: to_binary(&Addr)?.as_slice()]
+pub fn vesting_w<'a>(
+    storage: &'a mut dyn Storage,
+    token_id: &str,
+) -> Bucket<'a, Vec<VestingGrant>> {
+    Bucket::multilevel(storage, &[VESTING, token_id.as_bytes()])
+}
+/// Multilevel bucket to read the vesting grants of each holder of a token_id
+pub fn vesting_r<'a>(
+    storage: &'a dyn Storage,
+    token_id: &str,
+) -> ReadonlyBucket<'a, Vec<VestingGrant>> {
+    ReadonlyBucket::multilevel(storage, &[VESTING, token_id.as_bytes()])
+}
+
+/// open swap offers. Key is `swap_id.to_be_bytes()`
+pub fn swaps_w(storage: &mut dyn Storage) -> Bucket<'_, SwapOffer> {
+    bucket(storage, SWAPS)
+}
+/// open swap offers. Key is `swap_id.to_be_bytes()`
+pub fn swaps_r(storage: &dyn Storage) -> ReadonlyBucket<'_, SwapOffer> {
+    bucket_read(storage, SWAPS)
+}
+
+/// current user of an NFT. Key is `token_id.as_bytes()`
+pub fn nft_users_w(storage: &mut dyn Storage) -> Bucket<'_, NftUser> {
+    bucket(storage, NFT_USERS)
+}
+/// current user of an NFT. Key is `token_id.as_bytes()`
+pub fn nft_users_r(storage: &dyn Storage) -> ReadonlyBucket<'_, NftUser> {
+    bucket_read(storage, NFT_USERS)
+}
+/// loads the user of an NFT, ignoring a user whose role has expired at `block`
+pub fn may_load_nft_user(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    token_id: &str,
+) -> StdResult<Option<NftUser>> {
+    Ok(nft_users_r(storage)
+        .may_load(token_id.as_bytes())?
+        .filter(|user| !user.expires.is_expired(block)))
+}
+
+/// whether a token_id sends transfer hooks to the controller. Key is `token_id.as_bytes()`
+pub fn transfer_hooks_w(storage: &mut dyn Storage) -> Bucket<'_, bool> {
+    bucket(storage, TRANSFER_HOOKS)
+}
+/// whether a token_id sends transfer hooks to the controller. Key is `token_id.as_bytes()`
+pub fn transfer_hooks_r(storage: &dyn Storage) -> ReadonlyBucket<'_, bool> {
+    bucket_read(storage, TRANSFER_HOOKS)
+}
+
 /// private functions.
 /// To store permission. key is to be [`owner`, `token_id`, `allowed_addr`]
 /// `allowed_addr` is `to_binary(&Addr)?.as_slice()`
@@ -170,3 +402,20 @@ pub fn set_receiver_hash(store: &mut dyn Storage, account: &Addr, code_hash: Str
     let mut store = PrefixedStorage::new(store, PREFIX_RECEIVERS);
     store.set(account.as_str().as_bytes(), code_hash.as_bytes());
 }
+
+/////////////////////////////////////////////////////////////////////////////////
+// Viewing keys
+/////////////////////////////////////////////////////////////////////////////////
+
+/// store of scoped viewing key hashes. The account of each key is `"{address}/{label}"`
+pub struct ScopedViewingKey;
+
+impl ViewingKeyStore for ScopedViewingKey {
+    const STORAGE_KEY: &'static [u8] = b"scoped_viewing_keys";
+}
+
+impl ScopedViewingKey {
+    pub fn account(address: &Addr, label: &str) -> String {
+        format!("{}/{}", address, label)
+    }
+}
@@ -0,0 +1,189 @@
+use super::*;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Addr, StdError, StdResult, Storage, Uint256};
+
+use crate::state::state_structs::BackingAsset;
+
+/// scale of `reward_per_share`, so that rewards smaller than the total supply are not lost to
+/// rounding
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+/////////////////////////////////////////////////////////////////////////////////
+// Reward distribution to token_id holders
+/////////////////////////////////////////////////////////////////////////////////
+
+/// rewards distributed to the holders of a token_id in one asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardPool {
+    pub asset: BackingAsset,
+    /// cumulative rewards per share distributed, scaled by `REWARD_PRECISION`
+    pub reward_per_share: Uint256,
+}
+
+/// rewards of a holder in one asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HolderReward {
+    pub asset: BackingAsset,
+    /// the pool's `reward_per_share` when the holder's rewards were last settled
+    pub reward_per_share_paid: Uint256,
+    /// rewards settled but not claimed yet
+    pub unclaimed: Uint256,
+}
+
+/// amount of rewards in one asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardAmount {
+    pub asset: BackingAsset,
+    pub amount: Uint256,
+}
+
+/// distributes `amount` of an asset to the holders of a token_id, pro rata to their balances.
+/// Tokens escrowed by the contract in swap offers do not earn rewards
+pub fn add_rewards(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    asset: &BackingAsset,
+    amount: Uint256,
+    total_supply: Uint256,
+) -> StdResult<()> {
+    let contract_address = contr_conf_r(storage).load()?.contract_address;
+    let escrowed = balances_r(storage, token_id)
+        .may_load(to_binary(&contract_address)?.as_slice())?
+        .unwrap_or_default();
+    let eligible_supply = total_supply.saturating_sub(escrowed);
+    if eligible_supply.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "token_id {} has no holders to distribute rewards to",
+            token_id
+        )));
+    }
+    let increase = amount.checked_mul(Uint256::from(REWARD_PRECISION))? / eligible_supply;
+
+    let mut pools = tkn_rewards_r(storage)
+        .may_load(token_id.as_bytes())?
+        .unwrap_or_default();
+    match pools.iter_mut().find(|i| &i.asset == asset) {
+        Some(pool) => pool.reward_per_share = pool.reward_per_share.checked_add(increase)?,
+        None => pools.push(RewardPool {
+            asset: asset.clone(),
+            reward_per_share: increase,
+        }),
+    }
+    tkn_rewards_w(storage).save(token_id.as_bytes(), &pools)
+}
+
+/// adds the rewards a holder has earned since its last settlement to its unclaimed rewards.
+/// Must be called with the holder's balance before every change to it
+pub fn settle_rewards(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    holder: &Addr,
+    balance: Uint256,
+) -> StdResult<()> {
+    let pools = match tkn_rewards_r(storage).may_load(token_id.as_bytes())? {
+        Some(i) => i,
+        None => return Ok(()),
+    };
+    let balance = reward_balance(storage, holder, balance)?;
+    let holder_key = to_binary(holder)?;
+    let holder_rewards = holder_rewards_r(storage, token_id)
+        .may_load(holder_key.as_slice())?
+        .unwrap_or_default();
+    let holder_rewards = accrue(&pools, holder_rewards, balance)?;
+    holder_rewards_w(storage, token_id).save(holder_key.as_slice(), &holder_rewards)
+}
+
+/// settles and returns the unclaimed rewards of a holder, and resets them to zero
+pub fn take_rewards(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    holder: &Addr,
+    balance: Uint256,
+) -> StdResult<Vec<RewardAmount>> {
+    settle_rewards(storage, token_id, holder, balance)?;
+
+    let holder_key = to_binary(holder)?;
+    let mut holder_rewards = holder_rewards_r(storage, token_id)
+        .may_load(holder_key.as_slice())?
+        .unwrap_or_default();
+    let mut rewards = vec![];
+    for holder_reward in holder_rewards.iter_mut() {
+        if !holder_reward.unclaimed.is_zero() {
+            rewards.push(RewardAmount {
+                asset: holder_reward.asset.clone(),
+                amount: holder_reward.unclaimed,
+            });
+            holder_reward.unclaimed = Uint256::zero();
+        }
+    }
+    holder_rewards_w(storage, token_id).save(holder_key.as_slice(), &holder_rewards)?;
+
+    Ok(rewards)
+}
+
+/// returns the unclaimed rewards of a holder, including rewards not settled yet
+pub fn pending_rewards(
+    storage: &dyn Storage,
+    token_id: &str,
+    holder: &Addr,
+    balance: Uint256,
+) -> StdResult<Vec<RewardAmount>> {
+    let pools = tkn_rewards_r(storage)
+        .may_load(token_id.as_bytes())?
+        .unwrap_or_default();
+    let holder_rewards = holder_rewards_r(storage, token_id)
+        .may_load(to_binary(holder)?.as_slice())?
+        .unwrap_or_default();
+    let balance = reward_balance(storage, holder, balance)?;
+    let rewards = accrue(&pools, holder_rewards, balance)?
+        .into_iter()
+        .map(|i| RewardAmount {
+            asset: i.asset,
+            amount: i.unclaimed,
+        })
+        .collect();
+
+    Ok(rewards)
+}
+
+/// the balance on which a holder earns rewards: zero for the contract itself, whose balance is
+/// escrowed in swap offers and is excluded from reward distributions
+fn reward_balance(storage: &dyn Storage, holder: &Addr, balance: Uint256) -> StdResult<Uint256> {
+    match holder == &contr_conf_r(storage).load()?.contract_address {
+        true => Ok(Uint256::zero()),
+        false => Ok(balance),
+    }
+}
+
+/// brings a holder's rewards up to date with the reward pools of a token_id. A holder without an
+/// entry for a pool has held `balance` since the pool was created
+fn accrue(
+    pools: &[RewardPool],
+    mut holder_rewards: Vec<HolderReward>,
+    balance: Uint256,
+) -> StdResult<Vec<HolderReward>> {
+    for pool in pools {
+        let position = match holder_rewards.iter().position(|i| i.asset == pool.asset) {
+            Some(i) => i,
+            None => {
+                holder_rewards.push(HolderReward {
+                    asset: pool.asset.clone(),
+                    reward_per_share_paid: Uint256::zero(),
+                    unclaimed: Uint256::zero(),
+                });
+                holder_rewards.len() - 1
+            }
+        };
+        let holder_reward = &mut holder_rewards[position];
+        let earned = balance
+            .checked_mul(pool.reward_per_share - holder_reward.reward_per_share_paid)?
+            / Uint256::from(REWARD_PRECISION);
+        holder_reward.unclaimed = holder_reward.unclaimed.checked_add(earned)?;
+        holder_reward.reward_per_share_paid = pool.reward_per_share;
+    }
+
+    Ok(holder_rewards)
+}
@@ -3,7 +3,7 @@ use super::*;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint256};
+use cosmwasm_std::{Addr, Binary, Uint256};
 
 use self::metadata::Metadata;
 
@@ -27,6 +27,72 @@ pub struct ContractConfig {
     pub prng_seed: Vec<u8>,
     pub contract_address: Addr,
     pub lb_pair_info: LbPair,
+    /// ERC-1155-style uri template, eg: `https://meta.example/{id}.json`. In public info queries, `{id}`
+    /// is replaced with the token_id for token_ids that have no public metadata of their own
+    pub uri_template: Option<String>,
+    /// template used by `MintTokens` to curate token_ids that do not exist yet
+    pub auto_curate_template: AutoCurateTemplate,
+    /// maximum number of sub-queries in a `Batch` query
+    pub max_batch_queries: u32,
+    /// contract which receives `BeforeTransfer` and `AfterTransfer` hooks for token_ids that
+    /// have transfer hooks enabled
+    pub controller: Option<Controller>,
+    /// id of the next swap offer
+    pub swap_cnt: u64,
+    /// minimum `SendMode` of every `Send` and `BatchSend`
+    pub send_mode: SendMode,
+}
+
+impl ContractConfig {
+    /// returns the uri for a given `token_id` by substituting `{id}` in the uri template, or
+    /// `None` if no uri template is set
+    pub fn token_uri(&self, token_id: &str) -> Option<String> {
+        self.uri_template
+            .as_ref()
+            .map(|template| template.replace("{id}", token_id))
+    }
+}
+
+/// contract which receives transfer hooks, see [ControllerHandleMsg](crate::controller::ControllerHandleMsg)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Controller {
+    pub address: Addr,
+    pub code_hash: String,
+}
+
+/// how `Send` and `BatchSend` deliver the `Snip1155Receive` callback to the recipient. Modes are
+/// ordered from least to most strict, and a send uses the stricter of the contract-wide mode and
+/// its own
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SendMode {
+    /// the callback is skipped if no `recipient_code_hash` is given and the recipient has not
+    /// registered a code hash
+    #[default]
+    Lenient,
+    /// the send fails if no `recipient_code_hash` is given and the recipient has not registered a
+    /// code hash
+    Safe,
+    /// as `Safe`, and the callback is sent as a submessage. The recipient must accept the tokens
+    /// by setting [ReceiverHandleAnswer](crate::receiver::ReceiverHandleAnswer) as the `data` of
+    /// its response, or the whole transaction is rolled back
+    SafeWithReply,
+}
+
+/// balance change of a token_id with transfer hooks enabled, queued during a handle and sent to
+/// the controller with its response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingTransferHook {
+    pub token_id: String,
+    pub from: Option<Addr>,
+    pub to: Option<Addr>,
+    pub amount: Uint256,
+    pub from_balance_before: Option<Uint256>,
+    pub to_balance_before: Option<Uint256>,
+    pub from_balance_after: Option<Uint256>,
+    pub to_balance_after: Option<Uint256>,
 }
 
 /// message sent my instantiator and curators for a specific `token_id`'s token info
@@ -38,6 +104,67 @@ pub struct LbPair {
     pub decimals: u8,
 }
 
+/// template which `MintTokens` uses to curate a token_id that does not exist yet.
+/// In `name` and `symbol`, `{name}` and `{symbol}` are replaced with the lb pair's name and
+/// symbol, and `{id}` is replaced with the token_id
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AutoCurateTemplate {
+    /// if `false`, `MintTokens` returns an error for token_ids that do not exist
+    pub enabled: bool,
+    pub name: String,
+    pub symbol: String,
+    pub token_config: TknConfig,
+    pub public_metadata: Option<Metadata>,
+    pub private_metadata: Option<Metadata>,
+    /// enables transfer hooks for the token_ids curated from this template
+    #[serde(default)]
+    pub transfer_hooks: bool,
+}
+
+impl AutoCurateTemplate {
+    /// default template: fungible token named `{name}-{id}`, with the lb pair's symbol and decimals
+    pub fn from_lb_pair(lb_pair: &LbPair) -> Self {
+        AutoCurateTemplate {
+            enabled: true,
+            name: "{name}-{id}".to_string(),
+            symbol: "{symbol}".to_string(),
+            token_config: TknConfig::Fungible {
+                minters: vec![],
+                decimals: lb_pair.decimals,
+                public_total_supply: true,
+                enable_mint: true,
+                enable_burn: true,
+                minter_may_update_metadata: false,
+                max_supply: None,
+                public_holders: false,
+                transferable: true,
+                admin_may_transfer: false,
+            },
+            public_metadata: None,
+            private_metadata: None,
+            transfer_hooks: false,
+        }
+    }
+
+    /// creates the token info for a new `token_id` from this template
+    pub fn to_token_info_msg(&self, lb_pair: &LbPair, token_id: &str) -> TokenInfoMsg {
+        let fill = |pattern: &str| -> String {
+            pattern
+                .replace("{name}", &lb_pair.name)
+                .replace("{symbol}", &lb_pair.symbol)
+                .replace("{id}", token_id)
+        };
+        TokenInfoMsg {
+            token_id: token_id.to_string(),
+            name: fill(&self.name),
+            symbol: fill(&self.symbol),
+            token_config: self.token_config.clone(),
+            public_metadata: self.public_metadata.clone(),
+            private_metadata: self.private_metadata.clone(),
+        }
+    }
+}
+
 /// message sent my instantiator and curators for a specific `token_id`'s token info
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TokenInfoMsg {
@@ -75,6 +202,70 @@ pub struct StoredTokenInfo {
     pub curator: Addr,
 }
 
+/// message sent by curators to create a series of numbered NFT editions
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SeriesInfoMsg {
+    /// editions are minted as token_ids `{series_id}#1` to `{series_id}#{max_editions}`
+    pub series_id: String,
+    pub name: String,
+    pub symbol: String,
+    /// must be `TknConfig::Nft`. Minters of the series can mint editions
+    pub token_config: TknConfig,
+    pub public_metadata: Option<Metadata>,
+    pub private_metadata: Option<Metadata>,
+    pub max_editions: u32,
+}
+
+impl SeriesInfoMsg {
+    pub fn to_store(&self, curator: &Addr) -> StoredSeriesInfo {
+        StoredSeriesInfo {
+            series_id: self.series_id.clone(),
+            name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            token_config: self.token_config.clone(),
+            public_metadata: self.public_metadata.clone(),
+            private_metadata: self.private_metadata.clone(),
+            max_editions: self.max_editions,
+            minted_editions: 0,
+            curator: curator.clone(),
+        }
+    }
+}
+
+/// information for a series of numbered NFT editions
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StoredSeriesInfo {
+    pub series_id: String,
+    pub name: String,
+    pub symbol: String,
+    pub token_config: TknConfig,
+    pub public_metadata: Option<Metadata>,
+    pub private_metadata: Option<Metadata>,
+    pub max_editions: u32,
+    /// number of editions minted so far. The next edition minted is number `minted_editions + 1`
+    pub minted_editions: u32,
+    pub curator: Addr,
+}
+
+impl StoredSeriesInfo {
+    /// token_id of a given edition number
+    pub fn edition_token_id(&self, edition: u32) -> String {
+        format!("{}#{}", self.series_id, edition)
+    }
+
+    /// token info of a given edition number, which inherits the series' configuration and metadata
+    pub fn edition_token_info(&self, edition: u32) -> TokenInfoMsg {
+        TokenInfoMsg {
+            token_id: self.edition_token_id(edition),
+            name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            token_config: self.token_config.clone(),
+            public_metadata: self.public_metadata.clone(),
+            private_metadata: self.private_metadata.clone(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TknConfig {
@@ -89,6 +280,22 @@ pub enum TknConfig {
         enable_mint: bool,
         enable_burn: bool,
         minter_may_update_metadata: bool,
+        /// optional maximum total supply, enforced on every mint. Can only be lowered after
+        /// curation, using `LowerMaxSupply`
+        max_supply: Option<Uint256>,
+        /// if true, the contract keeps an index of holders that anyone can list with the
+        /// `Holders` query
+        #[serde(default)]
+        public_holders: bool,
+        /// if false, the tokens are bound to their holders (soulbound): they cannot be transferred
+        /// or sent, and transfer allowances cannot be given. They can still be burned if `enable_burn`
+        /// == true. Defaults to true
+        #[serde(default = "default_transferable")]
+        transferable: bool,
+        /// if `transferable` == false, the admin may still transfer tokens out of any balance without
+        /// an allowance, eg: to recover tokens from a compromised address
+        #[serde(default)]
+        admin_may_transfer: bool,
     },
     /// no `enable_mint` option because NFT can be minted only once using `CurateTokenIds`
     Nft {
@@ -100,6 +307,19 @@ pub enum TknConfig {
         enable_burn: bool,
         owner_may_update_metadata: bool,
         minter_may_update_metadata: bool,
+        /// if true, the contract keeps an index of holders that anyone can list with the
+        /// `Holders` query
+        #[serde(default)]
+        public_holders: bool,
+        /// if false, the tokens are bound to their holders (soulbound): they cannot be transferred
+        /// or sent, and transfer allowances cannot be given. They can still be burned if `enable_burn`
+        /// == true. Defaults to true
+        #[serde(default = "default_transferable")]
+        transferable: bool,
+        /// if `transferable` == false, the admin may still transfer tokens out of any balance without
+        /// an allowance, eg: to recover tokens from a compromised address
+        #[serde(default)]
+        admin_may_transfer: bool,
     },
 }
 
@@ -114,6 +334,10 @@ impl TknConfig {
                 enable_mint,
                 enable_burn,
                 minter_may_update_metadata,
+                max_supply,
+                public_holders,
+                transferable,
+                admin_may_transfer,
             } => {
                 TknConfigFlat {
                     is_nft: false,
@@ -126,6 +350,10 @@ impl TknConfig {
                     minter_may_update_metadata: *minter_may_update_metadata,
                     /// there can be multiple owners, so owners cannot update metadata
                     owner_may_update_metadata: false,
+                    max_supply: *max_supply,
+                    public_holders: *public_holders,
+                    transferable: *transferable,
+                    admin_may_transfer: *admin_may_transfer,
                 }
             }
             TknConfig::Nft {
@@ -135,6 +363,9 @@ impl TknConfig {
                 enable_burn,
                 owner_may_update_metadata,
                 minter_may_update_metadata,
+                public_holders,
+                transferable,
+                admin_may_transfer,
             } => {
                 TknConfigFlat {
                     is_nft: true,
@@ -148,6 +379,11 @@ impl TknConfig {
                     enable_burn: *enable_burn,
                     minter_may_update_metadata: *minter_may_update_metadata,
                     owner_may_update_metadata: *owner_may_update_metadata,
+                    // there can only be one NFT
+                    max_supply: Some(Uint256::from(1_u64)),
+                    public_holders: *public_holders,
+                    transferable: *transferable,
+                    admin_may_transfer: *admin_may_transfer,
                 }
             }
         }
@@ -163,6 +399,10 @@ impl TknConfig {
             enable_mint: true,
             enable_burn: true,
             minter_may_update_metadata: true,
+            max_supply: None,
+            public_holders: false,
+            transferable: true,
+            admin_may_transfer: false,
         }
     }
 
@@ -175,10 +415,17 @@ impl TknConfig {
             enable_burn: true,
             owner_may_update_metadata: true,
             minter_may_update_metadata: true,
+            public_holders: false,
+            transferable: true,
+            admin_may_transfer: false,
         }
     }
 }
 
+fn default_transferable() -> bool {
+    true
+}
+
 /// Constructed from input enum `TknConfig`. Flattened for easier handling in contract logic  
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TknConfigFlat {
@@ -191,6 +438,10 @@ pub struct TknConfigFlat {
     pub enable_burn: bool,
     pub minter_may_update_metadata: bool,
     pub owner_may_update_metadata: bool,
+    pub max_supply: Option<Uint256>,
+    pub public_holders: bool,
+    pub transferable: bool,
+    pub admin_may_transfer: bool,
 }
 
 impl TknConfigFlat {
@@ -203,6 +454,9 @@ impl TknConfigFlat {
                 enable_burn: self.enable_burn,
                 owner_may_update_metadata: self.owner_may_update_metadata,
                 minter_may_update_metadata: self.minter_may_update_metadata,
+                public_holders: self.public_holders,
+                transferable: self.transferable,
+                admin_may_transfer: self.admin_may_transfer,
             },
             false => TknConfig::Fungible {
                 minters: self.minters.clone(),
@@ -211,6 +465,10 @@ impl TknConfigFlat {
                 enable_mint: self.enable_mint,
                 enable_burn: self.enable_burn,
                 minter_may_update_metadata: self.minter_may_update_metadata,
+                max_supply: self.max_supply,
+                public_holders: self.public_holders,
+                transferable: self.transferable,
+                admin_may_transfer: self.admin_may_transfer,
             },
         }
     }
@@ -223,7 +481,15 @@ impl TknConfigFlat {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CurateTokenId {
     pub token_info: TokenInfoMsg,
-    pub balances: Vec<TokenIdBalance>,
+    pub balances: Vec<MintTokenIdBalance>,
+    /// optionally binds the token_id to a collateral asset. Shares of a backed token_id can only be
+    /// minted by depositing the collateral and burnt by redeeming it, so `balances` must be empty,
+    /// and `enable_mint` and `enable_burn` must be `false`
+    pub backing: Option<TokenBacking>,
+    /// enables transfer hooks for the token_id from its creation, so that the controller is also
+    /// notified of the initial balances
+    #[serde(default)]
+    pub transfer_hooks: bool,
 }
 
 #[cfg(test)]
@@ -247,12 +513,61 @@ impl Default for CurateTokenId {
             balances: vec![TokenIdBalance {
                 address: Addr::unchecked("addr0".to_string()),
                 amount: Uint256::from(1000_u64),
-            }],
+            }
+            .into()],
+            backing: None,
+            transfer_hooks: false,
         }
     }
 }
 
-/// used for MintToken and BurnToken in the base specifications
+/// collateral asset backing a token_id. Shares are minted when the collateral is deposited and
+/// burnt when the collateral is redeemed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenBacking {
+    pub asset: BackingAsset,
+    /// amount of the token_id minted for each unit of collateral deposited, eg: `1` for 1:1 wrapping
+    pub shares_per_unit: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BackingAsset {
+    /// native coin, deposited by attaching funds to a `Deposit` message
+    Native { denom: String },
+    /// SNIP20 token, deposited by a SNIP20 `Send` to this contract with a
+    /// [Snip20ReceiveAction](crate::snip20::Snip20ReceiveAction) `msg`. This contract registers
+    /// itself with the SNIP20 contract when the token_id is curated
+    Snip20 { contract: Addr, code_hash: String },
+}
+
+/// offer to swap balances, created with `CreateSwapOffer`. The offered balances are held by the
+/// contract until the offer is accepted or cancelled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapOffer {
+    pub swap_id: u64,
+    pub creator: Addr,
+    /// balances held in escrow, given to the address that accepts the offer
+    pub give: Vec<TokenAmount>,
+    /// balances the accepting address must send. Each balance's `address` receives its tokens
+    pub want: Vec<TokenAmount>,
+    /// if set, only this address can accept the offer
+    pub counterparty: Option<Addr>,
+    pub expiry: Expiration,
+    /// whether any address can query the offer with the public `SwapOffer` query
+    #[serde(default)]
+    pub public: bool,
+}
+
+/// ERC-4907-style user of an NFT, who may view its private metadata until `expires`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftUser {
+    pub user: Addr,
+    pub expires: Expiration,
+}
+
+/// used for BurnToken in the base specifications, and for swap offers. MintTokens uses
+/// [MintTokenAmount], which also accepts receiver callback fields
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TokenAmount {
     pub token_id: String,
@@ -271,8 +586,91 @@ pub struct TokenIdBalance {
     pub amount: Uint256,
 }
 
+/// token_id and balances minted by `MintTokens`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintTokenAmount {
+    pub token_id: String,
+    pub balances: Vec<MintTokenIdBalance>,
+}
+
+/// balance minted by `MintTokens`, or an initial balance of `CurateTokenIds`. If
+/// `recipient_code_hash` or `msg` is set, a `Snip1155Receive` callback is sent to `address`, using
+/// its registered code hash unless `recipient_code_hash` is given
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintTokenIdBalance {
+    pub address: Addr,
+    pub amount: Uint256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_code_hash: Option<String>,
+    /// optional message of the `Snip1155Receive` callback
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub msg: Option<Binary>,
+}
+
+impl From<TokenAmount> for MintTokenAmount {
+    fn from(token_amount: TokenAmount) -> Self {
+        Self {
+            token_id: token_amount.token_id,
+            balances: token_amount.balances.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<TokenIdBalance> for MintTokenIdBalance {
+    fn from(balance: TokenIdBalance) -> Self {
+        Self {
+            address: balance.address,
+            amount: balance.amount,
+            recipient_code_hash: None,
+            msg: None,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct OwnerBalance {
     pub token_id: String,
     pub amount: Uint256,
 }
+
+/// balance of an (owner, token_id) pair in a `BalanceOfBatch` query
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchBalance {
+    pub owner: Addr,
+    pub token_id: String,
+    /// `None` if the viewer does not have permission to view this balance
+    pub amount: Option<Uint256>,
+}
+
+/// what a scoped viewing key can be used to query
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewingKeyScope {
+    /// `Balance` and `AllBalances` queries
+    Balances,
+    /// `TransactionHistory` queries
+    History,
+    /// `Permission` and `AllPermissions` queries
+    Permissions,
+    /// `TokenIdPrivateInfo` queries
+    PrivateInfo,
+}
+
+/// a labeled viewing key of an address, which can only be used for queries within its scopes,
+/// until it expires. The key itself is stored as a hash in the
+/// [ScopedViewingKey](crate::state::ScopedViewingKey) store
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScopedViewingKeyInfo {
+    pub label: String,
+    pub scopes: Vec<ViewingKeyScope>,
+    pub expiration: Expiration,
+}
+
+/// an edition of an NFT series, as returned by the `SeriesInfo` query
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EditionInfo {
+    pub edition: u32,
+    pub token_id: String,
+    /// if owner_is_public == false, owner = None
+    pub owner: Option<Addr>,
+}
@@ -189,6 +189,19 @@ pub fn store_burn(
     Ok(())
 }
 
+/// reads and rewrites the latest tx id of an address, without changing it. Used to access the
+/// storage of decoy addresses
+pub fn touch_tx_ids(storage: &mut dyn Storage, address: &CanonicalAddr) -> StdResult<()> {
+    let addr_store = TX_ID_STORE.add_suffix(address.as_slice());
+    let len = addr_store.get_len(storage)?;
+    if len > 0 {
+        let tx_id = addr_store.get_at(storage, len - 1)?;
+        addr_store.set_at(storage, len - 1, &tx_id)?;
+    }
+
+    Ok(())
+}
+
 /// Returns StdResult<()> after saving tx id
 ///
 /// # Arguments
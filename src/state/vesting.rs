@@ -0,0 +1,219 @@
+use super::*;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Addr, BlockInfo, StdError, StdResult, Storage, Uint256};
+
+use crate::state::expiration::Expiration;
+
+/////////////////////////////////////////////////////////////////////////////////
+// Vesting schedules
+/////////////////////////////////////////////////////////////////////////////////
+
+/// how the tokens of a vesting grant unlock over time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VestingSchedule {
+    /// the whole amount unlocks at `unlock`
+    Cliff { unlock: Expiration },
+    /// the amount unlocks linearly from `start` to `end`, which must either both be heights or
+    /// both be times
+    Linear { start: Expiration, end: Expiration },
+    /// each step unlocks its amount. The amounts of the steps must add up to the amount granted
+    Steps { steps: Vec<VestingStep> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingStep {
+    pub unlock: Expiration,
+    pub amount: Uint256,
+}
+
+impl VestingSchedule {
+    /// verifies that the schedule can unlock exactly `amount`
+    pub fn validate(&self, amount: Uint256) -> StdResult<()> {
+        match self {
+            VestingSchedule::Cliff { .. } => (),
+            VestingSchedule::Linear { start, end } => match (start, end) {
+                (Expiration::AtHeight(start), Expiration::AtHeight(end))
+                | (Expiration::AtTime(start), Expiration::AtTime(end))
+                    if start < end => {}
+                _ => {
+                    return Err(StdError::generic_err(
+                        "linear vesting must start before it ends, and use either heights or times for both",
+                    ))
+                }
+            },
+            VestingSchedule::Steps { steps } => {
+                let total = steps
+                    .iter()
+                    .try_fold(Uint256::zero(), |total, step| total.checked_add(step.amount))?;
+                if total != amount {
+                    return Err(StdError::generic_err(format!(
+                        "vesting steps add up to {}, but {} tokens are granted",
+                        total, amount
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// tokens minted into a vesting schedule. They count toward the holder's balance, but cannot be
+/// transferred or burned until they unlock
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingGrant {
+    pub amount: Uint256,
+    pub schedule: VestingSchedule,
+    /// the address that minted the grant
+    pub minter: Addr,
+    /// if true, the minter can burn the tokens that are still locked with `RevokeVesting`
+    pub revocable: bool,
+}
+
+impl VestingGrant {
+    /// amount of the grant unlocked at `block`
+    pub fn unlocked(&self, block: &BlockInfo) -> Uint256 {
+        match &self.schedule {
+            VestingSchedule::Cliff { unlock } => match unlock.is_expired(block) {
+                true => self.amount,
+                false => Uint256::zero(),
+            },
+            VestingSchedule::Linear { start, end } => {
+                let (now, start, end) = match (start, end) {
+                    (Expiration::AtHeight(start), Expiration::AtHeight(end)) => {
+                        (block.height, *start, *end)
+                    }
+                    (Expiration::AtTime(start), Expiration::AtTime(end)) => {
+                        (block.time.seconds(), *start, *end)
+                    }
+                    // rejected when the grant is created
+                    _ => return Uint256::zero(),
+                };
+                if now <= start {
+                    Uint256::zero()
+                } else if now >= end {
+                    self.amount
+                } else {
+                    self.amount.multiply_ratio(now - start, end - start)
+                }
+            }
+            VestingSchedule::Steps { steps } => steps
+                .iter()
+                .filter(|step| step.unlock.is_expired(block))
+                .fold(Uint256::zero(), |unlocked, step| unlocked + step.amount),
+        }
+    }
+
+    /// amount of the grant still locked at `block`
+    pub fn locked(&self, block: &BlockInfo) -> Uint256 {
+        self.amount - self.unlocked(block)
+    }
+}
+
+/// adds a vesting grant to a holder's balance of a token_id
+pub fn add_vesting_grant(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    holder: &Addr,
+    grant: VestingGrant,
+) -> StdResult<()> {
+    let holder_key = to_binary(holder)?;
+    let mut grants = vesting_r(storage, token_id)
+        .may_load(holder_key.as_slice())?
+        .unwrap_or_default();
+    grants.push(grant);
+    vesting_w(storage, token_id).save(holder_key.as_slice(), &grants)
+}
+
+/// the vesting grants of a holder of a token_id
+pub fn vesting_grants(
+    storage: &dyn Storage,
+    token_id: &str,
+    holder: &Addr,
+) -> StdResult<Vec<VestingGrant>> {
+    Ok(vesting_r(storage, token_id)
+        .may_load(to_binary(holder)?.as_slice())?
+        .unwrap_or_default())
+}
+
+/// amount of a holder's balance of a token_id that is still locked at `block`
+pub fn locked_amount(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    token_id: &str,
+    holder: &Addr,
+) -> StdResult<Uint256> {
+    Ok(vesting_grants(storage, token_id, holder)?
+        .iter()
+        .fold(Uint256::zero(), |locked, grant| {
+            locked + grant.locked(block)
+        }))
+}
+
+/// removes the fully unlocked grants of a holder, and returns the amount still locked
+pub fn prune_vesting_grants(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    token_id: &str,
+    holder: &Addr,
+) -> StdResult<Uint256> {
+    let grants = vesting_grants(storage, token_id, holder)?;
+    if grants.is_empty() {
+        return Ok(Uint256::zero());
+    }
+
+    let (locked_grants, unlocked_grants): (Vec<VestingGrant>, Vec<VestingGrant>) = grants
+        .into_iter()
+        .partition(|grant| !grant.locked(block).is_zero());
+    if !unlocked_grants.is_empty() {
+        save_grants(storage, token_id, holder, &locked_grants)?;
+    }
+
+    Ok(locked_grants.iter().fold(Uint256::zero(), |locked, grant| {
+        locked + grant.locked(block)
+    }))
+}
+
+/// removes the revocable grants that `minter` minted to a holder, and returns the amount that was
+/// still locked. The unlocked part of the removed grants stays with the holder
+pub fn revoke_vesting_grants(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    token_id: &str,
+    holder: &Addr,
+    minter: &Addr,
+) -> StdResult<Uint256> {
+    let (revoked, kept): (Vec<VestingGrant>, Vec<VestingGrant>) =
+        vesting_grants(storage, token_id, holder)?
+            .into_iter()
+            .partition(|grant| grant.revocable && &grant.minter == minter);
+    if revoked.is_empty() {
+        return Err(StdError::generic_err(format!(
+            "{} has no revocable vesting grants of token_id {} from {}",
+            holder, token_id, minter
+        )));
+    }
+    save_grants(storage, token_id, holder, &kept)?;
+
+    Ok(revoked.iter().fold(Uint256::zero(), |locked, grant| {
+        locked + grant.locked(block)
+    }))
+}
+
+fn save_grants(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    holder: &Addr,
+    grants: &Vec<VestingGrant>,
+) -> StdResult<()> {
+    let holder_key = to_binary(holder)?;
+    match grants.is_empty() {
+        true => vesting_w(storage, token_id).remove(holder_key.as_slice()),
+        false => vesting_w(storage, token_id).save(holder_key.as_slice(), grants)?,
+    }
+    Ok(())
+}
@@ -1,16 +1,39 @@
 use super::testhelpers::*;
 
 use super::super::{
+    client::{SendBuilder, Snip1155Contract, TransferBuilder},
+    controller::{ControllerHandleMsg, TransferHookMsg},
     handles::*,
     msg::*,
     queries::*,
-    receiver::{ReceiverHandleMsg, Snip1155ReceiveMsg},
+    receiver::{
+        parse_execute_response_data, ReceiverHandleAnswer, ReceiverHandleMsg, Snip1155ReceiveMsg,
+        MINT_FROM_MARKER,
+    },
+    snip20::*,
     state::*,
-    state::{expiration::*, metadata::*, permissions::*, state_structs::*},
+    state::{
+        checkpoints::{balance_at, BALANCE_CHECKPOINTS},
+        compliance::{ComplianceAction, TransferListMode},
+        expiration::*,
+        metadata::*,
+        permissions::*,
+        rewards::{pending_rewards, RewardAmount},
+        state_structs::*,
+        txhistory::{TxAction, TX_ID_STORE},
+        vesting::{VestingSchedule, VestingStep},
+    },
 };
 
-use cosmwasm_std::{from_binary, testing::*, to_binary, Addr, Response, StdResult, Uint256};
-use secret_toolkit::{crypto::sha_256, permit::RevokedPermits};
+use cosmwasm_std::{
+    coins, from_binary, testing::*, to_binary, Addr, Api, BankMsg, Binary, ContractResult,
+    CosmosMsg, Deps, OwnedDeps, QuerierWrapper, Reply, ReplyOn, Response, StdResult,
+    SubMsgResponse, SubMsgResult, SystemError, SystemResult, Uint128, Uint256, WasmMsg, WasmQuery,
+};
+use secret_toolkit::{
+    crypto::sha_256,
+    permit::{PermitParams, RevokedPermits},
+};
 
 /////////////////////////////////////////////////////////////////////////////////
 // Tests
@@ -135,11 +158,13 @@ fn test_curate_token_id() -> StdResult<()> {
         TokenIdBalance {
             address: addr0.clone(),
             amount: Uint256::from(1u128),
-        },
+        }
+        .into(),
         TokenIdBalance {
             address: addr1.clone(),
             amount: Uint256::from(1u128),
-        },
+        }
+        .into(),
     ];
     let mut msg = ExecuteMsg::CurateTokenIds {
         initial_tokens: vec![curate],
@@ -224,7 +249,7 @@ fn test_mint_tokens() -> StdResult<()> {
         }],
     };
     let msg = ExecuteMsg::MintTokens {
-        mint_tokens: vec![mint_non_exist],
+        mint_tokens: vec![mint_non_exist.into()],
         memo: None,
         padding: None,
     };
@@ -249,7 +274,7 @@ fn test_mint_tokens() -> StdResult<()> {
         ],
     };
     let msg = ExecuteMsg::MintTokens {
-        mint_tokens: vec![mint],
+        mint_tokens: vec![mint.into()],
         memo: None,
         padding: None,
     };
@@ -274,24 +299,1913 @@ fn test_mint_tokens() -> StdResult<()> {
     info.sender = addr.a();
     let mint = TokenAmount {
         token_id: "2".to_string(),
-        balances: vec![TokenIdBalance {
-            address: addr.a(),
-            amount: Uint256::from(1u128),
-        }],
+        balances: vec![TokenIdBalance {
+            address: addr.a(),
+            amount: Uint256::from(1u128),
+        }],
+    };
+    let msg = ExecuteMsg::MintTokens {
+        mint_tokens: vec![mint.into()],
+        memo: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info, msg);
+    assert!(extract_error_msg(&result).contains("minting is not enabled for this token_id"));
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.a()).unwrap(),
+        Uint256::from(1010u128)
+    );
+    // 1 initial balance, 4 curate_token_id, 3 mint_token
+    assert_eq!(contr_conf_r(&deps.storage).load()?.tx_cnt, 8u64);
+
+    Ok(())
+}
+
+#[test]
+fn test_mint_receiver_callbacks() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    let balance = |address: Addr, recipient_code_hash: Option<String>, msg: Option<Binary>| {
+        MintTokenIdBalance {
+            address,
+            amount: Uint256::from(10u128),
+            recipient_code_hash,
+            msg,
+        }
+    };
+    let exp_receive_msg = |token_id: &str, msg: Option<Binary>| Snip1155ReceiveMsg {
+        sender: addr.a(),
+        token_id: token_id.to_string(),
+        from: Addr::unchecked(MINT_FROM_MARKER),
+        amount: Uint256::from(10u128),
+        memo: None,
+        msg,
+    };
+
+    // only balances with a code hash or msg get a callback
+    let msg = ExecuteMsg::MintTokens {
+        mint_tokens: vec![MintTokenAmount {
+            token_id: "0".to_string(),
+            balances: vec![
+                balance(addr.b(), Some(addr.b_hash()), None),
+                balance(addr.c(), None, None),
+            ],
+        }],
+        memo: None,
+        padding: None,
+    };
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    assert_eq!(response.messages.len(), 1);
+    let (receiver_msg, receiver_addr, receiver_hash) =
+        extract_cosmos_msg::<ReceiverHandleMsg>(&response.messages[0].msg)?;
+    assert_eq!(receiver_addr, Some(addr.b()));
+    assert_eq!(receiver_hash, &addr.b_hash());
+    match receiver_msg {
+        ReceiverHandleMsg::Snip1155Receive(i) => assert_eq!(i, exp_receive_msg("0", None)),
+    }
+
+    // a callback to a recipient without a known code hash fails before minting
+    let msg = ExecuteMsg::MintTokens {
+        mint_tokens: vec![MintTokenAmount {
+            token_id: "0".to_string(),
+            balances: vec![balance(addr.c(), None, Some(to_binary(&"msg_str")?))],
+        }],
+        memo: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("addr2 has not registered a receiver"));
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.c()),
+        Some(Uint256::from(10u128))
+    );
+
+    // curating uses the registered code hash of the recipient
+    let msg = ExecuteMsg::RegisterReceive {
+        code_hash: addr.c_hash(),
+        padding: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.c().as_str(), &[]),
+        msg,
+    )?;
+    let mut curate = CurateTokenId::default();
+    curate.token_info.token_id = "test0".to_string();
+    curate.balances = vec![balance(addr.c(), None, Some(to_binary(&"msg_str")?))];
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate],
+        memo: None,
+        padding: None,
+    };
+    let response = execute(deps.as_mut(), mock_env(), info, msg)?;
+    let (receiver_msg, receiver_addr, receiver_hash) =
+        extract_cosmos_msg::<ReceiverHandleMsg>(&response.messages[0].msg)?;
+    assert_eq!(receiver_addr, Some(addr.c()));
+    assert_eq!(receiver_hash, &addr.c_hash());
+    match receiver_msg {
+        ReceiverHandleMsg::Snip1155Receive(i) => {
+            assert_eq!(i, exp_receive_msg("test0", Some(to_binary(&"msg_str")?)))
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_auto_curate_template() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+
+    // default template: named after lb pair, with lb pair symbol and decimals
+    let mint = TokenAmount {
+        token_id: "100".to_string(),
+        balances: vec![TokenIdBalance {
+            address: addr.b(),
+            amount: Uint256::from(50u128),
+        }],
+    };
+    let msg = ExecuteMsg::MintTokens {
+        mint_tokens: vec![mint.into()],
+        memo: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let tkn_info = tkn_info_r(&deps.storage).load("100".as_bytes())?;
+    assert_eq!(tkn_info.name, "lbpair-100".to_string());
+    assert_eq!(tkn_info.symbol, "LBP".to_string());
+    assert_eq!(tkn_info.token_config.flatten().decimals, 18u8);
+    assert_eq!(tkn_info.public_metadata, None);
+
+    // non-admin cannot change template
+    let mut template =
+        AutoCurateTemplate::from_lb_pair(&contr_conf_r(&deps.storage).load()?.lb_pair_info);
+    template.name = "bin {id}".to_string();
+    template.symbol = "BIN".to_string();
+    template.public_metadata = Some(Metadata {
+        token_uri: Some("bin uri".to_string()),
+        extension: None,
+    });
+    let msg = ExecuteMsg::SetAutoCurateTemplate {
+        template: Box::new(template.clone()),
+        padding: None,
+    };
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &[]),
+        msg.clone(),
+    );
+    assert!(extract_error_msg(&result).contains("This is an admin function"));
+
+    // templates that render an invalid symbol, or create NFTs, are rejected when set
+    let mut invalid = template.clone();
+    invalid.symbol = "B{id}".to_string();
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::SetAutoCurateTemplate {
+            template: Box::new(invalid),
+            padding: None,
+        },
+    );
+    assert!(extract_error_msg(&result)
+        .contains("auto curate template symbol B0 is not in the expected format"));
+    let mut invalid = template.clone();
+    invalid.token_config = TknConfig::default_nft();
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::SetAutoCurateTemplate {
+            template: Box::new(invalid),
+            padding: None,
+        },
+    );
+    assert!(extract_error_msg(&result).contains("the auto curate template cannot create NFTs"));
+
+    // admin changes template
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let mint = TokenAmount {
+        token_id: "101".to_string(),
+        balances: vec![TokenIdBalance {
+            address: addr.b(),
+            amount: Uint256::from(50u128),
+        }],
+    };
+    let msg = ExecuteMsg::MintTokens {
+        mint_tokens: vec![mint.into()],
+        memo: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let tkn_info = tkn_info_r(&deps.storage).load("101".as_bytes())?;
+    assert_eq!(tkn_info.name, "bin 101".to_string());
+    assert_eq!(tkn_info.symbol, "BIN".to_string());
+    assert_eq!(tkn_info.public_metadata, template.public_metadata);
+
+    // template is queryable
+    let q_answer = from_binary::<QueryAnswer>(&query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AutoCurateTemplate {},
+    )?)?;
+    match q_answer {
+        QueryAnswer::AutoCurateTemplate {
+            template: q_template,
+        } => {
+            assert_eq!(q_template, template)
+        }
+        _ => panic!("query error"),
+    }
+
+    // disable implicit curation
+    template.enabled = false;
+    let msg = ExecuteMsg::SetAutoCurateTemplate {
+        template: Box::new(template),
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let mint = TokenAmount {
+        token_id: "typo".to_string(),
+        balances: vec![TokenIdBalance {
+            address: addr.b(),
+            amount: Uint256::from(50u128),
+        }],
+    };
+    let msg = ExecuteMsg::MintTokens {
+        mint_tokens: vec![mint.into()],
+        memo: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info, msg);
+    assert!(extract_error_msg(&result).contains("automatic curation is disabled"));
+    assert!(tkn_info_r(&deps.storage)
+        .may_load("typo".as_bytes())?
+        .is_none());
+    assert_eq!(chk_bal(&deps.storage, "typo", &addr.b()), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_max_supply() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+
+    // cannot curate initial balances above max supply
+    let mut curate = CurateTokenId::default();
+    curate.token_info.token_id = "capped".to_string();
+    let mut flat_config = curate.token_info.token_config.flatten();
+    flat_config.max_supply = Some(Uint256::from(1500u128));
+    curate.token_info.token_config = flat_config.to_enum();
+    curate.balances = vec![
+        TokenIdBalance {
+            address: addr.a(),
+            amount: Uint256::from(1000u128),
+        }
+        .into(),
+        TokenIdBalance {
+            address: addr.b(),
+            amount: Uint256::from(1000u128),
+        }
+        .into(),
+    ];
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate.clone()],
+        memo: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("exceed the max supply of 1500"));
+
+    // success: initial balances within max supply; total supply sums all initial balances
+    curate.balances[1].amount = Uint256::from(400u128);
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate],
+        memo: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    assert_eq!(
+        tkn_tot_supply_r(&deps.storage).load("capped".as_bytes())?,
+        Uint256::from(1400u128)
+    );
+
+    // cannot mint above max supply
+    let mint = |amount: u128| ExecuteMsg::MintTokens {
+        mint_tokens: vec![TokenAmount {
+            token_id: "capped".to_string(),
+            balances: vec![TokenIdBalance {
+                address: addr.c(),
+                amount: Uint256::from(amount),
+            }],
+        }
+        .into()],
+        memo: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), mint(101));
+    assert!(extract_error_msg(&result).contains("minting would exceed the max supply of 1500"));
+    execute(deps.as_mut(), mock_env(), info.clone(), mint(50))?;
+    assert_eq!(
+        chk_bal(&deps.storage, "capped", &addr.c()),
+        Some(Uint256::from(50u128))
+    );
+
+    // cannot raise max supply, or lower it below total supply
+    let lower = |max_supply: Option<u128>| ExecuteMsg::LowerMaxSupply {
+        token_id: "capped".to_string(),
+        max_supply: max_supply.map(Uint256::from),
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), lower(Some(2000)));
+    assert!(extract_error_msg(&result).contains("max supply can only be lowered"));
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), lower(Some(1449)));
+    assert!(extract_error_msg(&result).contains("cannot be lower than the current total supply"));
+
+    // non-admin, non-curator, non-minter cannot lower max supply
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.c().as_str(), &[]),
+        lower(Some(1460)),
+    );
+    assert!(extract_error_msg(&result).contains("Only the admin, curators or minters"));
+
+    // success: lower max supply
+    execute(deps.as_mut(), mock_env(), info.clone(), lower(Some(1460)))?;
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), mint(11));
+    assert!(extract_error_msg(&result).contains("minting would exceed the max supply of 1460"));
+
+    // lock supply permanently
+    execute(deps.as_mut(), mock_env(), info.clone(), lower(None))?;
+    let tkn_config = tkn_info_r(&deps.storage)
+        .load("capped".as_bytes())?
+        .token_config
+        .flatten();
+    assert_eq!(tkn_config.max_supply, Some(Uint256::from(1450u128)));
+    assert!(!tkn_config.enable_mint);
+    let result = execute(deps.as_mut(), mock_env(), info, mint(1));
+    assert!(extract_error_msg(&result).contains("minting is not enabled for this token_id"));
+
+    Ok(())
+}
+
+#[test]
+fn test_nft_series() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+
+    let mut flat_config = TknConfig::default_nft().flatten();
+    flat_config.minters = vec![addr.b()];
+    let series = SeriesInfoMsg {
+        series_id: "drop".to_string(),
+        name: "drop series".to_string(),
+        symbol: "DROP".to_string(),
+        token_config: flat_config.to_enum(),
+        public_metadata: Some(Metadata {
+            token_uri: Some("public uri".to_string()),
+            extension: None,
+        }),
+        private_metadata: Some(Metadata {
+            token_uri: Some("private uri".to_string()),
+            extension: None,
+        }),
+        max_editions: 3,
+    };
+
+    // series must be an NFT config
+    let mut fungible_series = series.clone();
+    fungible_series.token_config = TknConfig::default_fungible();
+    let msg = ExecuteMsg::CurateSeries {
+        series: vec![fungible_series],
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("must have an NFT token_config"));
+
+    // non-curator cannot curate series
+    let msg = ExecuteMsg::CurateSeries {
+        series: vec![series.clone()],
+        padding: None,
+    };
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &[]),
+        msg.clone(),
+    );
+    assert!(extract_error_msg(&result).contains("Only curators are allowed to curate"));
+
+    // success: curate series; series_id cannot be reused
+    execute(deps.as_mut(), mock_env(), info.clone(), msg.clone())?;
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("series_id already exists"));
+
+    // edition token_ids are reserved for the series
+    let mut curate = CurateTokenId::default();
+    curate.token_info.token_id = "drop#3".to_string();
+    let curate_msg = |curate: CurateTokenId| ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate],
+        memo: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), curate_msg(curate));
+    assert!(extract_error_msg(&result).contains("token_id drop#3 is reserved for edition 3"));
+
+    // a series cannot be curated over existing token_ids of the form `<series_id>#<edition>`
+    let mut curate = CurateTokenId::default();
+    curate.token_info.token_id = "other#1".to_string();
+    execute(deps.as_mut(), mock_env(), info.clone(), curate_msg(curate))?;
+    let mut other_series = series.clone();
+    other_series.series_id = "other".to_string();
+    let msg = ExecuteMsg::CurateSeries {
+        series: vec![other_series],
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(
+        extract_error_msg(&result).contains("token_ids of the form other#<edition> already exist")
+    );
+
+    // non-minter cannot mint editions
+    let mint = |recipients: Vec<Addr>| ExecuteMsg::MintEditions {
+        series_id: "drop".to_string(),
+        recipients,
+        memo: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info, mint(vec![addr.c()]));
+    assert!(extract_error_msg(&result).contains("Only minters are allowed to mint editions"));
+
+    // success: minter mints editions on demand
+    let minter_info = mock_info(addr.b().as_str(), &[]);
+    let response = execute(
+        deps.as_mut(),
+        mock_env(),
+        minter_info.clone(),
+        mint(vec![addr.c(), addr.d()]),
+    )?;
+    match from_binary::<ExecuteAnswer>(&response.data.unwrap())? {
+        ExecuteAnswer::MintEditions { token_ids } => {
+            assert_eq!(token_ids, vec!["drop#1".to_string(), "drop#2".to_string()])
+        }
+        _ => panic!("unexpected answer"),
+    }
+    assert_eq!(
+        chk_bal(&deps.storage, "drop#1", &addr.c()),
+        Some(Uint256::from(1u128))
+    );
+    assert_eq!(
+        chk_bal(&deps.storage, "drop#2", &addr.d()),
+        Some(Uint256::from(1u128))
+    );
+
+    // editions inherit the series' configuration and metadata
+    let edition_info = tkn_info_r(&deps.storage).load("drop#2".as_bytes())?;
+    assert_eq!(edition_info.name, series.name);
+    assert_eq!(edition_info.token_config, series.token_config);
+    assert_eq!(edition_info.public_metadata, series.public_metadata);
+    assert_eq!(edition_info.private_metadata, series.private_metadata);
+
+    // cannot mint more than max editions
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        minter_info.clone(),
+        mint(vec![addr.c(), addr.d()]),
+    );
+    assert!(extract_error_msg(&result).contains("Only 1 of 3 editions remain"));
+    execute(deps.as_mut(), mock_env(), minter_info, mint(vec![addr.a()]))?;
+
+    // query editions together
+    let msg = QueryMsg::SeriesInfo {
+        series_id: "drop".to_string(),
+        start_after: Some(1),
+        limit: None,
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::SeriesInfo {
+            series_info,
+            editions,
+        } => {
+            assert_eq!(series_info.minted_editions, 3);
+            assert_eq!(series_info.private_metadata, None);
+            assert_eq!(
+                editions,
+                vec![
+                    EditionInfo {
+                        edition: 2,
+                        token_id: "drop#2".to_string(),
+                        owner: Some(addr.d()),
+                    },
+                    EditionInfo {
+                        edition: 3,
+                        token_id: "drop#3".to_string(),
+                        owner: Some(addr.a()),
+                    },
+                ]
+            );
+        }
+        _ => panic!("query error"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_deposit_and_redeem_native() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+
+    let mut curate = CurateTokenId::default();
+    curate.token_info.token_id = "wscrt".to_string();
+    curate.balances = vec![];
+    curate.backing = Some(TokenBacking {
+        asset: BackingAsset::Native {
+            denom: "uscrt".to_string(),
+        },
+        shares_per_unit: Uint256::from(10u128),
+    });
+
+    // backed token_id cannot be mintable or burnable
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate.clone()],
+        memo: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("with enable_mint and enable_burn == false"));
+    let mut flat_config = curate.token_info.token_config.flatten();
+    flat_config.enable_mint = false;
+    flat_config.enable_burn = true;
+    curate.token_info.token_config = flat_config.to_enum();
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate.clone()],
+        memo: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("with enable_mint and enable_burn == false"));
+
+    // success: curate backed token_id
+    let mut flat_config = curate.token_info.token_config.flatten();
+    flat_config.enable_burn = false;
+    curate.token_info.token_config = flat_config.to_enum();
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate],
+        memo: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg)?;
+
+    // cannot deposit wrong denom
+    let deposit = ExecuteMsg::Deposit {
+        token_id: "wscrt".to_string(),
+        memo: None,
+        padding: None,
+    };
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &coins(100, "uatom")),
+        deposit.clone(),
+    );
+    assert!(extract_error_msg(&result).contains("Deposit requires a non-zero amount of uscrt"));
+
+    // success: deposit mints shares at configured ratio
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &coins(100, "uscrt")),
+        deposit,
+    )?;
+    assert_eq!(
+        chk_bal(&deps.storage, "wscrt", &addr.b()),
+        Some(Uint256::from(1000u128))
+    );
+
+    // cannot redeem fractions of a unit
+    let redeem = |amount: u128| ExecuteMsg::Redeem {
+        token_id: "wscrt".to_string(),
+        amount: Uint256::from(amount),
+        memo: None,
+        padding: None,
+    };
+    let info = mock_info(addr.b().as_str(), &[]);
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), redeem(15));
+    assert!(extract_error_msg(&result).contains("must be a non-zero multiple of 10"));
+
+    // success: redeem burns shares and returns coins
+    let response = execute(deps.as_mut(), mock_env(), info, redeem(300))?;
+    assert_eq!(
+        response.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: addr.b().to_string(),
+            amount: coins(30, "uscrt"),
+        })
+    );
+    assert_eq!(
+        chk_bal(&deps.storage, "wscrt", &addr.b()),
+        Some(Uint256::from(700u128))
+    );
+
+    // reserve matches total supply
+    let msg = QueryMsg::Reserve {
+        token_id: "wscrt".to_string(),
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::Reserve {
+            reserve,
+            total_supply,
+            ..
+        } => {
+            assert_eq!(reserve, Uint256::from(70u128));
+            assert_eq!(total_supply, Uint256::from(700u128));
+        }
+        _ => panic!("query error"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_deposit_and_redeem_snip20() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+    let snip20 = Addr::unchecked("snip20".to_string());
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+
+    let mut curate = CurateTokenId::default();
+    curate.token_info.token_id = "wsscrt".to_string();
+    curate.balances = vec![];
+    let mut flat_config = curate.token_info.token_config.flatten();
+    flat_config.enable_mint = false;
+    flat_config.enable_burn = false;
+    curate.token_info.token_config = flat_config.to_enum();
+    curate.backing = Some(TokenBacking {
+        asset: BackingAsset::Snip20 {
+            contract: snip20.clone(),
+            code_hash: "snip20hash".to_string(),
+        },
+        shares_per_unit: Uint256::from(1u128),
+    });
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate],
+        memo: None,
+        padding: None,
+    };
+
+    // cannot curate if the backing contract is not a SNIP20
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    assert!(extract_error_msg(&result).contains("unable to query SNIP20 token info"));
+
+    // success: curation registers this contract with the SNIP20 contract
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { contract_addr, .. } if contract_addr == "snip20" => {
+            SystemResult::Ok(ContractResult::Ok(
+                to_binary(&Snip20TokenInfoResponse {
+                    token_info: Snip20TokenInfo {
+                        name: "secret SCRT".to_string(),
+                        symbol: "SSCRT".to_string(),
+                        decimals: 6,
+                        total_supply: None,
+                    },
+                })
+                .unwrap(),
+            ))
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "not a SNIP20 contract".to_string(),
+        }),
+    });
+    let response = execute(deps.as_mut(), mock_env(), info, msg)?;
+    assert_eq!(
+        response.messages[0].msg,
+        Snip20HandleMsg::RegisterReceive {
+            code_hash: mock_env().contract.code_hash,
+            padding: None,
+        }
+        .into_cosmos_msg("snip20hash".to_string(), snip20.clone())?
+    );
+
+    // native deposit not possible
+    let deposit = ExecuteMsg::Deposit {
+        token_id: "wsscrt".to_string(),
+        memo: None,
+        padding: None,
+    };
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &coins(100, "uscrt")),
+        deposit,
+    );
+    assert!(extract_error_msg(&result).contains("Use SNIP20 `Send` to deposit"));
+
+    // only the backing SNIP20 contract can deposit
+    let receive = ExecuteMsg::Receive {
+        sender: addr.b(),
+        from: addr.b(),
+        amount: Uint128::from(100u128),
+        memo: None,
+        msg: Some(to_binary(&Snip20ReceiveAction::Deposit {
+            token_id: "wsscrt".to_string(),
+            memo: None,
+        })?),
+    };
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("fake_snip20", &[]),
+        receive.clone(),
+    );
+    assert!(extract_error_msg(&result).contains("is not backed by SNIP20 contract fake_snip20"));
+
+    // success: deposit through SNIP20 `Send`
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(snip20.as_str(), &[]),
+        receive,
+    )?;
+    assert_eq!(
+        chk_bal(&deps.storage, "wsscrt", &addr.b()),
+        Some(Uint256::from(100u128))
+    );
+
+    // success: redeem returns SNIP20 tokens
+    let redeem = ExecuteMsg::Redeem {
+        token_id: "wsscrt".to_string(),
+        amount: Uint256::from(40u128),
+        memo: None,
+        padding: None,
+    };
+    let response = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &[]),
+        redeem,
+    )?;
+    assert_eq!(
+        response.messages[0].msg,
+        Snip20HandleMsg::Transfer {
+            recipient: addr.b().to_string(),
+            amount: Uint128::from(40u128),
+            memo: None,
+            padding: None,
+        }
+        .into_cosmos_msg("snip20hash".to_string(), snip20)?
+    );
+    assert_eq!(
+        chk_bal(&deps.storage, "wsscrt", &addr.b()),
+        Some(Uint256::from(60u128))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_distribute_and_claim_rewards() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate, with 1000 token_id 0 to addr0
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    let vks = generate_viewing_keys(&mut deps, mock_env(), info.clone(), vec![addr.a()])?;
+    let transfer = |amount: u128| ExecuteMsg::Transfer {
+        token_id: "0".to_string(),
+        from: addr.a(),
+        recipient: addr.b(),
+        amount: Uint256::from(amount),
+        memo: None,
+        decoys: None,
+        entropy: None,
+        padding: None,
+    };
+    let distribute = |amount: u128| ExecuteMsg::DistributeRewards {
+        token_id: "0".to_string(),
+        asset: BackingAsset::Native {
+            denom: "uscrt".to_string(),
+        },
+        amount: Uint128::from(amount),
+        padding: None,
+    };
+    let pending = |deps: Deps| -> StdResult<Vec<RewardAmount>> {
+        let msg = QueryMsg::PendingRewards {
+            owner: addr.a(),
+            key: vks.a(),
+            token_id: "0".to_string(),
+        };
+        match from_binary::<QueryAnswer>(&query(deps, mock_env(), msg)?)? {
+            QueryAnswer::PendingRewards { rewards } => Ok(rewards),
+            _ => panic!("query error"),
+        }
+    };
+
+    // attached coins must match the reward amount
+    execute(deps.as_mut(), mock_env(), info.clone(), transfer(250))?;
+    let distributor = mock_info(addr.c().as_str(), &coins(999, "uscrt"));
+    let result = execute(deps.as_mut(), mock_env(), distributor, distribute(1000));
+    assert!(extract_error_msg(&result).contains("requires 1000uscrt attached"));
+
+    // rewards are split pro rata: 750 to addr0, 250 to addr1
+    let distributor = mock_info(addr.c().as_str(), &coins(1000, "uscrt"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        distributor.clone(),
+        distribute(1000),
+    )?;
+    assert_eq!(pending(deps.as_ref())?[0].amount, Uint256::from(750u128));
+
+    // rewards earned before a transfer are kept: addr0 holds 500 for the next distribution
+    execute(deps.as_mut(), mock_env(), info.clone(), transfer(250))?;
+    execute(deps.as_mut(), mock_env(), distributor, distribute(1000))?;
+    assert_eq!(pending(deps.as_ref())?[0].amount, Uint256::from(1250u128));
+
+    // claim
+    let msg = ExecuteMsg::ClaimRewards {
+        token_id: "0".to_string(),
+        padding: None,
+    };
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone())?;
+    assert_eq!(
+        response.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: addr.a().to_string(),
+            amount: coins(1250, "uscrt"),
+        })
+    );
+    assert_eq!(pending(deps.as_ref())?[0].amount, Uint256::zero());
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("no rewards to claim"));
+
+    // addr1 can claim its rewards too
+    let msg = ExecuteMsg::ClaimRewards {
+        token_id: "0".to_string(),
+        padding: None,
+    };
+    let response = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &[]),
+        msg,
+    )?;
+    assert_eq!(
+        response.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: addr.b().to_string(),
+            amount: coins(750, "uscrt"),
+        })
+    );
+
+    // tokens escrowed in a swap offer do not earn rewards: addr0's 250 remaining tokens and
+    // addr1's 500 share the whole distribution
+    let msg = ExecuteMsg::CreateSwapOffer {
+        give: vec![TokenAmount {
+            token_id: "0".to_string(),
+            balances: vec![TokenIdBalance {
+                address: addr.a(),
+                amount: Uint256::from(250u128),
+            }],
+        }],
+        want: vec![TokenAmount {
+            token_id: "0".to_string(),
+            balances: vec![TokenIdBalance {
+                address: addr.a(),
+                amount: Uint256::from(1u128),
+            }],
+        }],
+        counterparty: None,
+        expiry: None,
+        public: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg)?;
+    let distributor = mock_info(addr.c().as_str(), &coins(750, "uscrt"));
+    execute(deps.as_mut(), mock_env(), distributor, distribute(750))?;
+    assert_eq!(pending(deps.as_ref())?[0].amount, Uint256::from(250u128));
+    let contract = contr_conf_r(&deps.storage).load()?.contract_address;
+    let contract_pending = pending_rewards(&deps.storage, "0", &contract, Uint256::from(250u128))?;
+    assert_eq!(contract_pending[0].amount, Uint256::zero());
+
+    Ok(())
+}
+
+#[test]
+fn test_transfer_hooks() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate, with 1000 token_id 0 to addr0
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    let controller = Controller {
+        address: Addr::unchecked("controller"),
+        code_hash: "controllerhash".to_string(),
+    };
+    let transfer = ExecuteMsg::Transfer {
+        token_id: "0".to_string(),
+        from: addr.a(),
+        recipient: addr.b(),
+        amount: Uint256::from(100u128),
+        memo: None,
+        decoys: None,
+        entropy: None,
+        padding: None,
+    };
+
+    // only the admin can set the controller or enable hooks
+    let msg = ExecuteMsg::SetController {
+        controller: Some(controller.clone()),
+        padding: None,
+    };
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr1", &[]),
+        msg.clone(),
+    );
+    assert!(extract_error_msg(&result).contains("This is an admin function"));
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+
+    // hooks are disabled by default
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone())?;
+    assert!(response.messages.is_empty());
+
+    // cannot enable hooks for a token_id that does not exist
+    let msg = ExecuteMsg::SetTransferHooks {
+        token_ids: vec!["0".to_string(), "1".to_string()],
+        enabled: true,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("token_id 1 does not exist"));
+    let msg = ExecuteMsg::SetTransferHooks {
+        token_ids: vec!["0".to_string()],
+        enabled: true,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+
+    // transfer sends BeforeTransfer, with the balances before the change, and AfterTransfer, with
+    // the balances after it, to the controller
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone())?;
+    assert_eq!(response.messages.len(), 2);
+    let (msg, contract, code_hash) =
+        extract_cosmos_msg::<ControllerHandleMsg>(&response.messages[0].msg)?;
+    assert_eq!(
+        msg,
+        ControllerHandleMsg::BeforeTransfer(TransferHookMsg::new(
+            addr.a(),
+            "0".to_string(),
+            Some(addr.a()),
+            Some(addr.b()),
+            Uint256::from(100u128),
+            Some(Uint256::from(900u128)),
+            Some(Uint256::from(100u128)),
+        ))
+    );
+    assert_eq!(contract, Some(controller.address.clone()));
+    assert_eq!(code_hash, &controller.code_hash);
+    let (msg, _, _) = extract_cosmos_msg::<ControllerHandleMsg>(&response.messages[1].msg)?;
+    assert_eq!(
+        msg,
+        ControllerHandleMsg::AfterTransfer(TransferHookMsg::new(
+            addr.a(),
+            "0".to_string(),
+            Some(addr.a()),
+            Some(addr.b()),
+            Uint256::from(100u128),
+            Some(Uint256::from(800u128)),
+            Some(Uint256::from(200u128)),
+        ))
+    );
+
+    // mints and burns are hooked too, with `from` and `to` left empty respectively
+    let msg = ExecuteMsg::MintTokens {
+        mint_tokens: vec![TokenAmount {
+            token_id: "0".to_string(),
+            balances: vec![TokenIdBalance {
+                address: addr.b(),
+                amount: Uint256::from(10u128),
+            }],
+        }
+        .into()],
+        memo: None,
+        padding: None,
+    };
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let (msg, _, _) = extract_cosmos_msg::<ControllerHandleMsg>(&response.messages[0].msg)?;
+    assert_eq!(
+        msg,
+        ControllerHandleMsg::BeforeTransfer(TransferHookMsg::new(
+            addr.a(),
+            "0".to_string(),
+            None,
+            Some(addr.b()),
+            Uint256::from(10u128),
+            None,
+            Some(Uint256::from(200u128)),
+        ))
+    );
+    let msg = ExecuteMsg::BurnTokens {
+        burn_tokens: vec![TokenAmount {
+            token_id: "0".to_string(),
+            balances: vec![TokenIdBalance {
+                address: addr.a(),
+                amount: Uint256::from(10u128),
+            }],
+        }],
+        memo: None,
+        padding: None,
+    };
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let (msg, _, _) = extract_cosmos_msg::<ControllerHandleMsg>(&response.messages[1].msg)?;
+    assert_eq!(
+        msg,
+        ControllerHandleMsg::AfterTransfer(TransferHookMsg::new(
+            addr.a(),
+            "0".to_string(),
+            Some(addr.a()),
+            None,
+            Uint256::from(10u128),
+            Some(Uint256::from(790u128)),
+            None,
+        ))
+    );
+
+    // a failed transfer sends no hooks, and does not leave any queued for the next transfer
+    let msg = ExecuteMsg::Transfer {
+        token_id: "0".to_string(),
+        from: addr.a(),
+        recipient: addr.b(),
+        amount: Uint256::from(10_000u128),
+        memo: None,
+        decoys: None,
+        entropy: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("insufficient funds"));
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone())?;
+    assert_eq!(response.messages.len(), 2);
+
+    // the sender of a transfer with permit is the permit's signer, rather than the relayer
+    let contract = mock_env().contract.address.to_string();
+    let (permit, signer) = sign_permit(
+        PermitParams {
+            allowed_tokens: vec![contract],
+            permit_name: "hooked".to_string(),
+            chain_id: mock_env().block.chain_id,
+            permissions: vec![TransferPermitPayload {
+                token_id: "0".to_string(),
+                recipient: addr.c(),
+                amount: Uint256::from(10u128),
+                memo: None,
+                nonce: 0,
+                expiration: Expiration::Never,
+            }],
+        },
+        &[2u8; 32],
+    )?;
+    let mut fund_signer = transfer.clone();
+    if let ExecuteMsg::Transfer { recipient, .. } = &mut fund_signer {
+        *recipient = signer.clone();
+    }
+    execute(deps.as_mut(), mock_env(), info.clone(), fund_signer)?;
+    let msg = ExecuteMsg::TransferWithPermit {
+        permit,
+        padding: None,
+    };
+    let response = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.d().as_str(), &[]),
+        msg,
+    )?;
+    let (msg, _, _) = extract_cosmos_msg::<ControllerHandleMsg>(&response.messages[0].msg)?;
+    assert_eq!(
+        msg,
+        ControllerHandleMsg::BeforeTransfer(TransferHookMsg::new(
+            signer.clone(),
+            "0".to_string(),
+            Some(signer),
+            Some(addr.c()),
+            Uint256::from(10u128),
+            Some(Uint256::from(100u128)),
+            Some(Uint256::zero()),
+        ))
+    );
+
+    // token_ids curated with transfer hooks send hooks for their initial balances
+    let mut curate = CurateTokenId::default();
+    curate.token_info.token_id = "hooked".to_string();
+    curate.transfer_hooks = true;
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate],
+        memo: None,
+        padding: None,
+    };
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let (msg, _, _) = extract_cosmos_msg::<ControllerHandleMsg>(&response.messages[0].msg)?;
+    assert_eq!(
+        msg,
+        ControllerHandleMsg::BeforeTransfer(TransferHookMsg::new(
+            addr.a(),
+            "hooked".to_string(),
+            None,
+            Some(addr.a()),
+            Uint256::from(1000u128),
+            None,
+            Some(Uint256::zero()),
+        ))
+    );
+
+    // editions take the transfer hooks setting of their series
+    let mut flat_config = TknConfig::default_nft().flatten();
+    flat_config.minters = vec![addr.a()];
+    let msg = ExecuteMsg::CurateSeries {
+        series: vec![SeriesInfoMsg {
+            series_id: "drop".to_string(),
+            name: "drop series".to_string(),
+            symbol: "DROP".to_string(),
+            token_config: flat_config.to_enum(),
+            public_metadata: None,
+            private_metadata: None,
+            max_editions: 3,
+        }],
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let msg = ExecuteMsg::SetTransferHooks {
+        token_ids: vec!["drop".to_string()],
+        enabled: true,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let msg = ExecuteMsg::MintEditions {
+        series_id: "drop".to_string(),
+        recipients: vec![addr.b()],
+        memo: None,
+        padding: None,
+    };
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let (msg, _, _) = extract_cosmos_msg::<ControllerHandleMsg>(&response.messages[1].msg)?;
+    assert_eq!(
+        msg,
+        ControllerHandleMsg::AfterTransfer(TransferHookMsg::new(
+            addr.a(),
+            "drop#1".to_string(),
+            None,
+            Some(addr.b()),
+            Uint256::from(1u128),
+            None,
+            Some(Uint256::from(1u128)),
+        ))
+    );
+
+    // disabling hooks, or removing the controller, stops the hooks
+    let msg = ExecuteMsg::SetController {
+        controller: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone())?;
+    assert!(response.messages.is_empty());
+    let msg = ExecuteMsg::SetController {
+        controller: Some(controller),
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let msg = ExecuteMsg::SetTransferHooks {
+        token_ids: vec!["0".to_string()],
+        enabled: false,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let response = execute(deps.as_mut(), mock_env(), info, transfer)?;
+    assert!(response.messages.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_soulbound_tokens() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+
+    // curate a non-transferable token_id, which the admin may still move
+    let mut curate = CurateTokenId::default();
+    curate.token_info.token_id = "badge".to_string();
+    let mut flat_config = curate.token_info.token_config.flatten();
+    flat_config.transferable = false;
+    flat_config.admin_may_transfer = true;
+    curate.token_info.token_config = flat_config.to_enum();
+    curate.balances = vec![TokenIdBalance {
+        address: addr.b(),
+        amount: Uint256::from(100u128),
+    }
+    .into()];
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate],
+        memo: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let transfer = |recipient: Addr| ExecuteMsg::Transfer {
+        token_id: "badge".to_string(),
+        from: addr.b(),
+        recipient,
+        amount: Uint256::from(10u128),
+        memo: None,
+        decoys: None,
+        entropy: None,
+        padding: None,
+    };
+
+    // holder cannot transfer, or give a transfer allowance
+    let holder = mock_info(addr.b().as_str(), &[]);
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        holder.clone(),
+        transfer(addr.c()),
+    );
+    assert!(extract_error_msg(&result).contains("token_id badge is not transferable"));
+    let msg = ExecuteMsg::GivePermission {
+        allowed_address: addr.c(),
+        token_id: "badge".to_string(),
+        view_balance: Some(true),
+        view_balance_expiry: None,
+        view_private_metadata: None,
+        view_private_metadata_expiry: None,
+        transfer: Some(Uint256::from(10u128)),
+        transfer_expiry: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), holder.clone(), msg);
+    assert!(extract_error_msg(&result).contains("transfer allowances cannot be given"));
+
+    // holder can still burn
+    let msg = ExecuteMsg::BurnTokens {
+        burn_tokens: vec![TokenAmount {
+            token_id: "badge".to_string(),
+            balances: vec![TokenIdBalance {
+                address: addr.b(),
+                amount: Uint256::from(10u128),
+            }],
+        }],
+        memo: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), holder, msg)?;
+    assert_eq!(
+        chk_bal(&deps.storage, "badge", &addr.b()),
+        Some(Uint256::from(90u128))
+    );
+
+    // admin can move the tokens without an allowance, other addresses cannot
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.c().as_str(), &[]),
+        transfer(addr.c()),
+    );
+    assert!(extract_error_msg(&result).contains("no permission to transfer"));
+    execute(deps.as_mut(), mock_env(), info, transfer(addr.c()))?;
+    assert_eq!(
+        chk_bal(&deps.storage, "badge", &addr.c()),
+        Some(Uint256::from(10u128))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_freeze_and_transfer_lists() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate, with 1000 token_id 0 to addr0
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    let transfer = |recipient: Addr| ExecuteMsg::Transfer {
+        token_id: "0".to_string(),
+        from: addr.a(),
+        recipient,
+        amount: Uint256::from(10u128),
+        memo: None,
+        decoys: None,
+        entropy: None,
+        padding: None,
+    };
+    let frozen = |deps: Deps, token_id: Option<String>| -> StdResult<bool> {
+        let msg = QueryMsg::Frozen {
+            address: addr.b(),
+            token_id,
+        };
+        match from_binary::<QueryAnswer>(&query(deps, mock_env(), msg)?)? {
+            QueryAnswer::Frozen { frozen } => Ok(frozen),
+            _ => panic!("query error"),
+        }
+    };
+
+    // only the admin can freeze
+    let msg = ExecuteMsg::SetFrozen {
+        address: addr.b(),
+        token_id: Some("0".to_string()),
+        frozen: true,
+        padding: None,
+    };
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr1", &[]),
+        msg.clone(),
+    );
+    assert!(extract_error_msg(&result).contains("This is an admin function"));
+
+    // an address frozen for a token_id cannot receive it
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    assert!(frozen(deps.as_ref(), Some("0".to_string()))?);
+    assert!(!frozen(deps.as_ref(), None)?);
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), transfer(addr.b()));
+    assert!(extract_error_msg(&result).contains("is frozen"));
+    let msg = ExecuteMsg::SetFrozen {
+        address: addr.b(),
+        token_id: Some("0".to_string()),
+        frozen: false,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    execute(deps.as_mut(), mock_env(), info.clone(), transfer(addr.b()))?;
+
+    // an address frozen globally cannot send either
+    let msg = ExecuteMsg::SetFrozen {
+        address: addr.a(),
+        token_id: None,
+        frozen: true,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), transfer(addr.b()));
+    assert!(extract_error_msg(&result).contains("is frozen"));
+
+    // or receive the initial balances of a new token_id
+    let mut curate = CurateTokenId::default();
+    curate.token_info.token_id = "new".to_string();
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate],
+        memo: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("is frozen"));
+    assert!(tkn_info_r(&deps.storage).may_load(b"new")?.is_none());
+    let msg = ExecuteMsg::SetFrozen {
+        address: addr.a(),
+        token_id: None,
+        frozen: false,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+
+    // allow list: only listed addresses can send and receive
+    let msg = ExecuteMsg::SetTransferListMode {
+        token_id: "0".to_string(),
+        mode: TransferListMode::AllowList,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let msg = ExecuteMsg::UpdateTransferList {
+        token_id: "0".to_string(),
+        add: vec![addr.a(), addr.b()],
+        remove: vec![],
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    execute(deps.as_mut(), mock_env(), info.clone(), transfer(addr.b()))?;
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), transfer(addr.c()));
+    assert!(extract_error_msg(&result).contains("is not on the allow list of token_id 0"));
+
+    // the contract can hold balances in escrow for swap offers without being on the allow list
+    let token_amount = |address: Addr| TokenAmount {
+        token_id: "0".to_string(),
+        balances: vec![TokenIdBalance {
+            address,
+            amount: Uint256::from(10u128),
+        }],
+    };
+    let msg = ExecuteMsg::CreateSwapOffer {
+        give: vec![token_amount(addr.a())],
+        want: vec![token_amount(addr.a())],
+        counterparty: Some(addr.b()),
+        expiry: None,
+        public: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+
+    // deny list: listed addresses cannot send or receive
+    let msg = ExecuteMsg::SetTransferListMode {
+        token_id: "0".to_string(),
+        mode: TransferListMode::DenyList,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let msg = ExecuteMsg::UpdateTransferList {
+        token_id: "0".to_string(),
+        add: vec![],
+        remove: vec![addr.a()],
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    execute(deps.as_mut(), mock_env(), info.clone(), transfer(addr.c()))?;
+    let result = execute(deps.as_mut(), mock_env(), info, transfer(addr.b()));
+    assert!(extract_error_msg(&result).contains("is on the deny list of token_id 0"));
+
+    // every change is in the audit log, latest first
+    let msg = QueryMsg::ComplianceLog {
+        page: None,
+        page_size: 2,
+    };
+    match from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)? {
+        QueryAnswer::ComplianceLog { events, total } => {
+            assert_eq!(total, 8);
+            assert_eq!(events.len(), 2);
+            assert_eq!(
+                events[0].action,
+                ComplianceAction::UpdateTransferList {
+                    token_id: "0".to_string(),
+                    add: vec![],
+                    remove: vec![addr.a()],
+                }
+            );
+            assert_eq!(events[0].admin, addr.a());
+        }
+        _ => panic!("query error"),
+    }
+
+    // page_size is capped, and pages past the end of the log are empty rather than overflowing
+    let msg = QueryMsg::ComplianceLog {
+        page: None,
+        page_size: u32::MAX,
+    };
+    match from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)? {
+        QueryAnswer::ComplianceLog { events, total } => {
+            assert_eq!(total, 8);
+            assert_eq!(events.len(), 8);
+        }
+        _ => panic!("query error"),
+    }
+    let msg = QueryMsg::ComplianceLog {
+        page: Some(u32::MAX),
+        page_size: u32::MAX,
+    };
+    match from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)? {
+        QueryAnswer::ComplianceLog { events, .. } => assert!(events.is_empty()),
+        _ => panic!("query error"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_vesting() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    let vks = generate_viewing_keys(&mut deps, mock_env(), info.clone(), vec![addr.b()])?;
+    let mut env = mock_env();
+    let start = env.block.height;
+    let transfer = |amount: u128| ExecuteMsg::Transfer {
+        token_id: "0".to_string(),
+        from: addr.b(),
+        recipient: addr.c(),
+        amount: Uint256::from(amount),
+        memo: None,
+        decoys: None,
+        entropy: None,
+        padding: None,
+    };
+    let vesting_balance = |deps: Deps| -> StdResult<(Uint256, Uint256)> {
+        let msg = QueryMsg::VestingBalance {
+            owner: addr.b(),
+            viewer: addr.b(),
+            key: vks.a(),
+            token_id: "0".to_string(),
+        };
+        match from_binary::<QueryAnswer>(&query(deps, mock_env(), msg)?)? {
+            QueryAnswer::VestingBalance {
+                locked, unlocked, ..
+            } => Ok((locked, unlocked)),
+            _ => panic!("query error"),
+        }
+    };
+
+    // steps must add up to the amount granted
+    let mint_vested = |schedule: VestingSchedule| ExecuteMsg::MintVested {
+        token_id: "0".to_string(),
+        recipient: addr.b(),
+        amount: Uint256::from(100u128),
+        schedule,
+        revocable: true,
+        memo: None,
+        padding: None,
+    };
+    let msg = mint_vested(VestingSchedule::Steps {
+        steps: vec![VestingStep {
+            unlock: Expiration::AtHeight(start + 10),
+            amount: Uint256::from(50u128),
+        }],
+    });
+    let result = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("vesting steps add up to 50"));
+
+    // 100 tokens vesting linearly over 100 blocks
+    let msg = mint_vested(VestingSchedule::Linear {
+        start: Expiration::AtHeight(start),
+        end: Expiration::AtHeight(start + 100),
+    });
+    execute(deps.as_mut(), env.clone(), info.clone(), msg)?;
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.b()),
+        Some(Uint256::from(100u128))
+    );
+
+    // after 40 blocks, 40 tokens can be transferred, but not more
+    env.block.height = start + 40;
+    let holder = mock_info(addr.b().as_str(), &[]);
+    let result = execute(deps.as_mut(), env.clone(), holder.clone(), transfer(41));
+    assert!(extract_error_msg(&result).contains("60 tokens are still vesting"));
+    execute(deps.as_mut(), env.clone(), holder.clone(), transfer(40))?;
+    assert_eq!(
+        vesting_balance(deps.as_ref())?,
+        (Uint256::from(60u128), Uint256::zero())
+    );
+
+    // only the minter can revoke. The locked 60 tokens are burned, the rest is liquid
+    let msg = ExecuteMsg::RevokeVesting {
+        token_id: "0".to_string(),
+        holder: addr.b(),
+        memo: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), env.clone(), holder.clone(), msg.clone());
+    assert!(extract_error_msg(&result).contains("has no revocable vesting grants"));
+    execute(deps.as_mut(), env.clone(), info, msg)?;
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.b()),
+        Some(Uint256::zero())
+    );
+    assert_eq!(
+        vesting_balance(deps.as_ref())?,
+        (Uint256::zero(), Uint256::zero())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_swap_offers() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate, with 1000 token_id 0 to addr0 and NFT 2 to addr2
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    curate_addtl_default(&mut deps, mock_env(), info.clone())?;
+    let vks = generate_viewing_keys(
+        &mut deps,
+        mock_env(),
+        info.clone(),
+        vec![addr.c(), addr.a()],
+    )?;
+    let contract = contr_conf_r(&deps.storage).load()?.contract_address;
+    let token_amount = |token_id: &str, address: Addr, amount: u128| TokenAmount {
+        token_id: token_id.to_string(),
+        balances: vec![TokenIdBalance {
+            address,
+            amount: Uint256::from(amount),
+        }],
+    };
+
+    // addr0 offers 100 token_id 0 for addr2's NFT
+    let msg = ExecuteMsg::CreateSwapOffer {
+        give: vec![token_amount("0", addr.b(), 100)],
+        want: vec![token_amount("2", addr.a(), 1)],
+        counterparty: Some(addr.c()),
+        expiry: None,
+        public: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("can only give the sender's own balances"));
+    let msg = ExecuteMsg::CreateSwapOffer {
+        give: vec![token_amount("0", addr.a(), 100)],
+        want: vec![token_amount("2", contract.clone(), 1)],
+        counterparty: Some(addr.c()),
+        expiry: None,
+        public: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result)
+        .contains("the contract cannot receive the wanted balances of a swap offer"));
+    let msg = ExecuteMsg::CreateSwapOffer {
+        give: vec![token_amount("0", addr.a(), 100)],
+        want: vec![token_amount("2", addr.a(), 1)],
+        counterparty: Some(addr.c()),
+        expiry: None,
+        public: None,
+        padding: None,
+    };
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let swap_id = match from_binary(&response.data.unwrap())? {
+        ExecuteAnswer::CreateSwapOffer { swap_id } => swap_id,
+        _ => panic!("wrong response"),
+    };
+
+    // the offered tokens are held by the contract
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.a()),
+        Some(Uint256::from(900u128))
+    );
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &contract),
+        Some(Uint256::from(100u128))
+    );
+
+    // the offer is not public, but its counterparty can view it
+    let msg = QueryMsg::SwapOffer { swap_id };
+    let result = query(deps.as_ref(), mock_env(), msg);
+    assert!(extract_error_msg(&result).contains("you do not have permission to view swap offer 0"));
+    let msg = QueryMsg::PrivateSwapOffer {
+        address: addr.c(),
+        key: vks.a(),
+        swap_id,
+    };
+    match from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)? {
+        QueryAnswer::SwapOffer(offer) => assert_eq!(offer.creator, addr.a()),
+        _ => panic!("query error"),
+    }
+
+    // only the counterparty can accept
+    let accept = ExecuteMsg::AcceptSwap {
+        swap_id,
+        padding: None,
+    };
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &[]),
+        accept.clone(),
+    );
+    assert!(extract_error_msg(&result).contains("only addr2 can accept swap offer 0"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.c().as_str(), &[]),
+        accept.clone(),
+    )?;
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.c()),
+        Some(Uint256::from(100u128))
+    );
+    assert_eq!(
+        chk_bal(&deps.storage, "2", &addr.a()),
+        Some(Uint256::from(1u128))
+    );
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &contract),
+        Some(Uint256::zero())
+    );
+
+    // both legs are in the counterparty's tx history
+    let msg = QueryMsg::TransactionHistory {
+        address: addr.c(),
+        key: vks.a(),
+        page: None,
+        page_size: 2,
+    };
+    match from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)? {
+        QueryAnswer::TransactionHistory { txs, .. } => {
+            let transfers = txs
+                .iter()
+                .map(|tx| match &tx.action {
+                    TxAction::Transfer {
+                        from,
+                        recipient,
+                        amount,
+                        ..
+                    } => (from.clone(), recipient.clone(), *amount),
+                    _ => panic!("wrong tx history variant"),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                transfers,
+                vec![
+                    (contract.clone(), addr.c(), Uint256::from(100u128)),
+                    (addr.c(), addr.a(), Uint256::from(1u128)),
+                ]
+            );
+        }
+        _ => panic!("query error"),
+    }
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.c().as_str(), &[]),
+        accept,
+    );
+    assert!(extract_error_msg(&result).contains("swap offer 0 does not exist"));
+
+    // cancelling returns the tokens held in escrow
+    let msg = ExecuteMsg::CreateSwapOffer {
+        give: vec![token_amount("0", addr.a(), 50)],
+        want: vec![token_amount("1", addr.a(), 50)],
+        counterparty: None,
+        expiry: Some(Expiration::AtHeight(mock_env().block.height + 10)),
+        public: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.a()),
+        Some(Uint256::from(850u128))
+    );
+    let msg = ExecuteMsg::AcceptSwap {
+        swap_id: 1,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("the creator cannot accept swap offer 1"));
+    let msg = ExecuteMsg::CancelSwap {
+        swap_id: 1,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg)?;
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.a()),
+        Some(Uint256::from(900u128))
+    );
+
+    // the lock into escrow and the refund are in the creator's tx history
+    let msg = QueryMsg::TransactionHistory {
+        address: addr.a(),
+        key: vks.b(),
+        page: None,
+        page_size: 2,
+    };
+    match from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)? {
+        QueryAnswer::TransactionHistory { txs, .. } => {
+            let transfers = txs
+                .iter()
+                .map(|tx| match &tx.action {
+                    TxAction::Transfer {
+                        from, recipient, ..
+                    } => (from.clone(), recipient.clone()),
+                    _ => panic!("wrong tx history variant"),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                transfers,
+                vec![(contract.clone(), addr.a()), (addr.a(), contract)]
+            );
+        }
+        _ => panic!("query error"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_nft_user() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate, with NFT 2 to addr2
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    curate_addtl_default(&mut deps, mock_env(), info.clone())?;
+    let vks = generate_viewing_keys(&mut deps, mock_env(), info.clone(), vec![addr.d()])?;
+    let owner_info = mock_info(addr.c().as_str(), &[]);
+    let set_user = |token_id: &str, user: Option<Addr>| ExecuteMsg::SetUser {
+        token_id: token_id.to_string(),
+        user,
+        expires: Expiration::AtHeight(mock_env().block.height + 10),
+        padding: None,
+    };
+    let private_info = QueryMsg::TokenIdPrivateInfo {
+        address: addr.d(),
+        key: vks.a(),
+        token_id: "2".to_string(),
+    };
+
+    // only the owner of an NFT can set its user
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        set_user("2", Some(addr.d())),
+    );
+    assert!(extract_error_msg(&result).contains("only the owner of token_id 2 can set its user"));
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &[]),
+        set_user("1", Some(addr.d())),
+    );
+    assert!(extract_error_msg(&result).contains("only NFTs can have a user"));
+    let result = query(deps.as_ref(), mock_env(), private_info.clone());
+    assert!(extract_error_msg(&result).contains("you do have have permission"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        owner_info.clone(),
+        set_user("2", Some(addr.d())),
+    )?;
+
+    // the user is public, and can view the private metadata until the role expires
+    let user_of = QueryMsg::UserOf {
+        token_id: "2".to_string(),
     };
-    let msg = ExecuteMsg::MintTokens {
-        mint_tokens: vec![mint],
+    match from_binary(&query(deps.as_ref(), mock_env(), user_of.clone())?)? {
+        QueryAnswer::UserOf { user, expires } => {
+            assert_eq!(user, Some(addr.d()));
+            assert_eq!(
+                expires,
+                Some(Expiration::AtHeight(mock_env().block.height + 10))
+            );
+        }
+        _ => panic!("query error"),
+    }
+    match from_binary(&query(deps.as_ref(), mock_env(), private_info.clone())?)? {
+        QueryAnswer::TokenIdPrivateInfo { token_id_info, .. } => {
+            assert!(token_id_info.private_metadata.is_some());
+        }
+        _ => panic!("query error"),
+    }
+
+    // the user is cleared when the NFT is transferred
+    let msg = ExecuteMsg::Transfer {
+        token_id: "2".to_string(),
+        from: addr.c(),
+        recipient: addr.b(),
+        amount: Uint256::from(1u128),
         memo: None,
+        decoys: None,
+        entropy: None,
         padding: None,
     };
-    let result = execute(deps.as_mut(), mock_env(), info, msg);
-    assert!(extract_error_msg(&result).contains("minting is not enabled for this token_id"));
-    assert_eq!(
-        chk_bal(&deps.storage, "0", &addr.a()).unwrap(),
-        Uint256::from(1010u128)
-    );
-    // 1 initial balance, 4 curate_token_id, 3 mint_token
-    assert_eq!(contr_conf_r(&deps.storage).load()?.tx_cnt, 8u64);
+    execute(deps.as_mut(), mock_env(), owner_info, msg)?;
+    match from_binary(&query(deps.as_ref(), mock_env(), user_of)?)? {
+        QueryAnswer::UserOf { user, expires } => {
+            assert_eq!(user, None);
+            assert_eq!(expires, None);
+        }
+        _ => panic!("query error"),
+    }
+    let result = query(deps.as_ref(), mock_env(), private_info);
+    assert!(extract_error_msg(&result).contains("you do have have permission"));
 
     Ok(())
 }
@@ -415,6 +2329,9 @@ fn test_change_metadata_nft() -> StdResult<()> {
             lb_pair_address: Addr::unchecked("address"),
             decimals: 18,
         },
+        uri_template: None,
+        auto_curate_template: None,
+        max_batch_queries: None,
     };
     instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg)?;
 
@@ -425,7 +2342,8 @@ fn test_change_metadata_nft() -> StdResult<()> {
     curate0.balances = vec![TokenIdBalance {
         address: addr.c(),
         amount: Uint256::from(1u128),
-    }];
+    }
+    .into()];
 
     // ... one which owner cannot change metadata...
     let mut curate1 = CurateTokenId::default();
@@ -437,7 +2355,8 @@ fn test_change_metadata_nft() -> StdResult<()> {
     curate1.balances = vec![TokenIdBalance {
         address: addr.c(),
         amount: Uint256::from(1u128),
-    }];
+    }
+    .into()];
 
     // ... and one where minter can change metadata (and owner cannot)
     let mut curate2 = CurateTokenId::default();
@@ -450,7 +2369,8 @@ fn test_change_metadata_nft() -> StdResult<()> {
     curate2.balances = vec![TokenIdBalance {
         address: addr.c(),
         amount: Uint256::from(1u128),
-    }];
+    }
+    .into()];
 
     let msg_curate = ExecuteMsg::CurateTokenIds {
         initial_tokens: vec![curate0, curate1, curate2],
@@ -553,6 +2473,8 @@ fn test_change_metadata_nft() -> StdResult<()> {
         recipient: addr.d(),
         amount: Uint256::from(1u128),
         memo: None,
+        decoys: None,
+        entropy: None,
         padding: None,
     };
     info.sender = addr.c();
@@ -669,6 +2591,9 @@ fn test_change_metadata_fungible() -> StdResult<()> {
             lb_pair_address: Addr::unchecked("address"),
             decimals: 18,
         },
+        uri_template: None,
+        auto_curate_template: None,
+        max_batch_queries: None,
     };
     instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg)?;
 
@@ -682,7 +2607,8 @@ fn test_change_metadata_fungible() -> StdResult<()> {
     curate0.balances = vec![TokenIdBalance {
         address: addr.d(),
         amount: Uint256::from(1000u128),
-    }];
+    }
+    .into()];
 
     let mut curate1 = CurateTokenId::default();
     curate1.token_info.token_id = "test1".to_string();
@@ -694,7 +2620,8 @@ fn test_change_metadata_fungible() -> StdResult<()> {
     curate1.balances = vec![TokenIdBalance {
         address: addr.d(),
         amount: Uint256::from(1000u128),
-    }];
+    }
+    .into()];
 
     let msg_curate = ExecuteMsg::CurateTokenIds {
         initial_tokens: vec![curate0, curate1],
@@ -851,6 +2778,8 @@ fn test_transfer() -> StdResult<()> {
         recipient: addr1.clone(),
         amount: Uint256::from(800u128),
         memo: None,
+        decoys: None,
+        entropy: None,
         padding: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg.clone())?;
@@ -877,6 +2806,8 @@ fn test_transfer() -> StdResult<()> {
         recipient: addr1.clone(),
         amount: Uint256::from(0u128),
         memo: None,
+        decoys: None,
+        entropy: None,
         padding: None,
     };
     let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -889,6 +2820,8 @@ fn test_transfer() -> StdResult<()> {
         recipient: addr1.clone(),
         amount: Uint256::from(1u128),
         memo: None,
+        decoys: None,
+        entropy: None,
         padding: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg)?;
@@ -916,6 +2849,110 @@ fn test_transfer() -> StdResult<()> {
     Ok(())
 }
 
+#[test]
+fn test_transfer_with_decoys() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    curate_addtl_default(&mut deps, mock_env(), info.clone())?;
+
+    let tx_id_count = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, address: &Addr| {
+        let address = deps.api.addr_canonicalize(address.as_str()).unwrap();
+        TX_ID_STORE
+            .add_suffix(address.as_slice())
+            .get_len(&deps.storage)
+            .unwrap()
+    };
+    let tx_ids_b = tx_id_count(&deps, &addr.b());
+
+    // decoys must be valid addresses
+    let msg = ExecuteMsg::Transfer {
+        token_id: "0".to_string(),
+        from: addr.a(),
+        recipient: addr.c(),
+        amount: Uint256::from(10u128),
+        memo: None,
+        decoys: Some(vec![Addr::unchecked("ADDR3")]),
+        entropy: None,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(extract_error_msg(&result).contains("not normalized"));
+
+    // success: transfer with decoys, one of which has a balance and tx history
+    let msg = ExecuteMsg::Transfer {
+        token_id: "0".to_string(),
+        from: addr.a(),
+        recipient: addr.c(),
+        amount: Uint256::from(10u128),
+        memo: None,
+        decoys: Some(vec![addr.b(), addr.d()]),
+        entropy: Some("decoy entropy".to_string()),
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.c()),
+        Some(Uint256::from(10u128))
+    );
+
+    // decoy balances and tx histories are unchanged
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.b()),
+        Some(Uint256::from(0u128))
+    );
+    assert_eq!(
+        chk_bal(&deps.storage, "1", &addr.b()),
+        Some(Uint256::from(500u128))
+    );
+    assert_eq!(tx_id_count(&deps, &addr.b()), tx_ids_b);
+    assert_eq!(tx_id_count(&deps, &addr.d()), 0);
+
+    // decoys' balance histories are rewritten like the recipient's, with unchanged balances
+    let checkpoint_count = |address: &Addr| {
+        BALANCE_CHECKPOINTS
+            .add_suffix("0".as_bytes())
+            .add_suffix(to_binary(address).unwrap().as_slice())
+            .get_len(&deps.storage)
+            .unwrap()
+    };
+    assert_eq!(checkpoint_count(&addr.c()), 1);
+    assert_eq!(checkpoint_count(&addr.d()), 1);
+    let height = mock_env().block.height;
+    assert_eq!(
+        balance_at(&deps.storage, "0", &addr.d(), height)?,
+        Uint256::zero()
+    );
+
+    // success: batch send with decoys
+    let msg = ExecuteMsg::BatchSend {
+        actions: vec![SendAction {
+            token_id: "0a".to_string(),
+            from: addr.a(),
+            recipient: addr.c(),
+            recipient_code_hash: None,
+            amount: Uint256::from(20u128),
+            msg: None,
+            memo: None,
+            decoys: Some(vec![addr.d()]),
+            send_mode: None,
+        }],
+        entropy: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg)?;
+    assert_eq!(
+        chk_bal(&deps.storage, "0a", &addr.c()),
+        Some(Uint256::from(20u128))
+    );
+    assert_eq!(tx_id_count(&deps, &addr.d()), 0);
+
+    Ok(())
+}
+
 #[test]
 fn test_send() -> StdResult<()> {
     // init addresses
@@ -940,6 +2977,9 @@ fn test_send() -> StdResult<()> {
         amount: Uint256::from(800u128),
         msg: Some(to_binary(&"msg_str")?),
         memo: None,
+        decoys: None,
+        send_mode: None,
+        entropy: None,
         padding: None,
     };
     let response = execute(deps.as_mut(), mock_env(), info, msg)?;
@@ -970,6 +3010,338 @@ fn test_send() -> StdResult<()> {
     Ok(())
 }
 
+#[test]
+fn test_send_modes() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    let send =
+        |recipient_code_hash: Option<String>, send_mode: Option<SendMode>| ExecuteMsg::Send {
+            token_id: "0".to_string(),
+            from: addr.a(),
+            recipient: addr.c(),
+            recipient_code_hash,
+            amount: Uint256::from(100u128),
+            msg: None,
+            memo: None,
+            decoys: None,
+            send_mode,
+            entropy: None,
+            padding: None,
+        };
+
+    // by default, sending to an address without a receiver skips the callback
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), send(None, None))?;
+    assert!(response.messages.is_empty());
+
+    // a safe send to an address without a receiver fails before any balance changes
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        send(None, Some(SendMode::Safe)),
+    );
+    assert!(extract_error_msg(&result).contains("addr2 has not registered a receiver"));
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.a()),
+        Some(Uint256::from(900u128))
+    );
+
+    // the contract-wide send mode applies when it is stricter
+    let msg = ExecuteMsg::SetSendMode {
+        send_mode: SendMode::Safe,
+        padding: None,
+    };
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &[]),
+        msg.clone(),
+    );
+    assert!(extract_error_msg(&result).contains("This is an admin function"));
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        send(None, Some(SendMode::Lenient)),
+    );
+    assert!(extract_error_msg(&result).contains("addr2 has not registered a receiver"));
+    let response = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        send(Some(addr.c_hash()), None),
+    )?;
+    assert_eq!(response.messages[0].reply_on, ReplyOn::Never);
+
+    // with safe_with_reply, the callback is a submessage whose reply must accept the tokens
+    let response = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        send(Some(addr.c_hash()), Some(SendMode::SafeWithReply)),
+    )?;
+    assert_eq!(response.messages[0].reply_on, ReplyOn::Success);
+    assert_eq!(response.messages[0].id, RECEIVE_REPLY_ID);
+    let receiver_reply = |data: Option<Binary>| Reply {
+        id: RECEIVE_REPLY_ID,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data,
+        }),
+    };
+    let accepted =
+        |accepted: bool| to_binary(&ReceiverHandleAnswer::Snip1155Receive { accepted }).unwrap();
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        receiver_reply(Some(accepted(true))),
+    )?;
+    let result = reply(
+        deps.as_mut(),
+        mock_env(),
+        receiver_reply(Some(accepted(false))),
+    );
+    assert!(extract_error_msg(&result).contains("the recipient did not accept the tokens"));
+    let result = reply(deps.as_mut(), mock_env(), receiver_reply(None));
+    assert!(extract_error_msg(&result).contains("the recipient did not accept the tokens"));
+
+    // the answer may be wrapped in a protobuf `MsgExecuteContractResponse`
+    let wrap = |data: &[u8], len_prefix: &[u8]| {
+        let mut wrapped = vec![0x0a];
+        wrapped.extend_from_slice(len_prefix);
+        wrapped.extend_from_slice(data);
+        Binary::from(wrapped)
+    };
+    let answer = accepted(true);
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        receiver_reply(Some(wrap(answer.as_slice(), &[answer.len() as u8]))),
+    )?;
+    // a padded answer longer than 127 bytes has a two byte length prefix
+    let mut padded = answer.to_vec();
+    padded.resize(300, b' ');
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        receiver_reply(Some(wrap(&padded, &[0xac, 0x02]))),
+    )?;
+
+    // lengths over 127 bytes are varints of several bytes
+    for len in [128usize, 300, 16_384, 70_000] {
+        let data = vec![7u8; len];
+        let mut len_prefix = vec![];
+        let mut rest = len;
+        while rest >= 0x80 {
+            len_prefix.push((rest as u8 & 0x7f) | 0x80);
+            rest >>= 7;
+        }
+        len_prefix.push(rest as u8);
+        let parsed = parse_execute_response_data(wrap(&data, &len_prefix).as_slice())?;
+        assert_eq!(parsed, Some(Binary::from(data)));
+    }
+    assert_eq!(parse_execute_response_data(&[])?, None);
+    let result = parse_execute_response_data(wrap(&[7u8; 200], &[0xc8]).as_slice());
+    assert!(extract_error_msg(&result).contains("message too short"));
+    let result = parse_execute_response_data(wrap(&[], &[0xc8, 0x81]).as_slice());
+    assert!(extract_error_msg(&result).contains("varint data too short"));
+    let result = parse_execute_response_data(wrap(&[], &[0xff; 10]).as_slice());
+    assert!(extract_error_msg(&result).contains("varint data too long"));
+    let result = parse_execute_response_data(&[0x12, 0x01, 0x00]);
+    assert!(extract_error_msg(&result).contains("invalid field #2 for field #1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_client_messages() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    let contract = Snip1155Contract::new(mock_env().contract.address, "contract_hash".to_string());
+
+    // messages built by the client are padded, and are understood by the contract
+    let msg = SendBuilder::new("0".to_string(), addr.a(), addr.b(), Uint256::from(100u128))
+        .recipient_code_hash(addr.b_hash())
+        .memo("memo".to_string())
+        .build();
+    let cosmos_msg = contract.execute(msg)?;
+    match &cosmos_msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => assert_eq!(msg.len() % 256, 0),
+        _ => panic!("wrong cosmos msg"),
+    }
+    let (msg, contract_addr, code_hash) = extract_cosmos_msg::<ExecuteMsg>(&cosmos_msg)?;
+    assert_eq!(contract_addr, Some(contract.address.clone()));
+    assert_eq!(code_hash, &contract.code_hash);
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    assert_eq!(response.messages.len(), 1);
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.b()),
+        Some(Uint256::from(100u128))
+    );
+
+    // builders also create the actions of batch messages
+    let msg = ExecuteMsg::BatchTransfer {
+        actions: vec![TransferBuilder::new(
+            "0".to_string(),
+            addr.a(),
+            addr.c(),
+            Uint256::from(10u128),
+        )
+        .into_action()],
+        entropy: None,
+        padding: None,
+    };
+    let (msg, _, _) = extract_cosmos_msg::<ExecuteMsg>(&contract.execute(msg)?)?;
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.c()),
+        Some(Uint256::from(10u128))
+    );
+
+    // queries
+    let wasm_query = QueryMsg::ContractInfo {}
+        .into_wasm_query(contract.code_hash.clone(), contract.address.clone())?;
+    let msg = match wasm_query {
+        WasmQuery::Smart {
+            contract_addr,
+            code_hash,
+            msg,
+        } => {
+            assert_eq!(contract_addr, contract.address.to_string());
+            assert_eq!(code_hash, contract.code_hash);
+            from_binary::<QueryMsg>(&msg)?
+        }
+        _ => panic!("wrong wasm query"),
+    };
+    match from_binary(&query(deps.as_ref(), mock_env(), msg)?)? {
+        QueryAnswer::ContractInfo { all_token_ids, .. } => {
+            assert_eq!(all_token_ids, vec!["0".to_string()])
+        }
+        _ => panic!("query error"),
+    }
+
+    // typed constructors create the message variant, with funds where the message takes them
+    match contract.deposit("0".to_string(), None, coins(100, "uscrt"))? {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, funds, .. }) => {
+            assert_eq!(funds, coins(100, "uscrt"));
+            assert!(matches!(
+                from_binary(&msg)?,
+                ExecuteMsg::Deposit { token_id, .. } if token_id == "0"
+            ));
+        }
+        _ => panic!("wrong cosmos msg"),
+    }
+    let (msg, _, _) = extract_cosmos_msg::<ExecuteMsg>(&contract.cancel_swap(3)?)?;
+    assert!(matches!(msg, ExecuteMsg::CancelSwap { swap_id: 3, .. }));
+    let token_amount = |address: Addr, amount: u128| TokenAmount {
+        token_id: "0".to_string(),
+        balances: vec![TokenIdBalance {
+            address,
+            amount: Uint256::from(amount),
+        }],
+    };
+    let create = contract.create_swap_offer(
+        vec![token_amount(addr.a(), 10)],
+        vec![token_amount(addr.a(), 5)],
+        Some(addr.b()),
+        None,
+        false,
+    )?;
+    let (msg, _, _) = extract_cosmos_msg::<ExecuteMsg>(&create)?;
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let (msg, _, _) = extract_cosmos_msg::<ExecuteMsg>(&contract.accept_swap(0)?)?;
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &[]),
+        msg,
+    )?;
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.b()),
+        Some(Uint256::from(105u128))
+    );
+
+    // typed queries return the concrete answer. The mock querier answers with the contract's
+    // answer to the query it expects
+    let vks = generate_viewing_keys(&mut deps, mock_env(), info, vec![addr.b()])?;
+    let querier_for = |msg: QueryMsg| -> StdResult<MockQuerier> {
+        let expected = msg.clone().into_binary()?;
+        let answer: ContractResult<Binary> = query(deps.as_ref(), mock_env(), msg).into();
+        let mut querier = MockQuerier::new(&[]);
+        querier.update_wasm(move |wasm_query| match wasm_query {
+            WasmQuery::Smart { msg, .. } if msg == &expected => SystemResult::Ok(answer.clone()),
+            _ => panic!("unexpected wasm query"),
+        });
+        Ok(querier)
+    };
+    let querier = querier_for(QueryMsg::Balance {
+        owner: addr.b(),
+        viewer: addr.b(),
+        key: vks.a(),
+        token_id: "0".to_string(),
+    })?;
+    let amount = contract.balance(
+        QuerierWrapper::new(&querier),
+        addr.b(),
+        addr.b(),
+        vks.a(),
+        "0".to_string(),
+    )?;
+    assert_eq!(amount, Uint256::from(105u128));
+    let querier = querier_for(QueryMsg::BalanceOfBatch {
+        viewer: addr.b(),
+        key: vks.a(),
+        owners: vec![addr.a(), addr.b()],
+        token_ids: vec!["0".to_string()],
+    })?;
+    let balances = contract.balance_of_batch(
+        QuerierWrapper::new(&querier),
+        addr.b(),
+        vks.a(),
+        vec![addr.a(), addr.b()],
+        vec!["0".to_string()],
+    )?;
+    assert_eq!(balances.len(), 2);
+    assert_eq!(balances[0].amount, None);
+    assert_eq!(balances[1].amount, Some(Uint256::from(105u128)));
+
+    // viewing key errors and query errors are returned as errors
+    let querier = querier_for(QueryMsg::BalanceAt {
+        owner: addr.b(),
+        viewer: addr.b(),
+        key: "wrong key".to_string(),
+        token_id: "0".to_string(),
+        height: mock_env().block.height,
+    })?;
+    let result = contract.balance_at(
+        QuerierWrapper::new(&querier),
+        addr.b(),
+        addr.b(),
+        "wrong key".to_string(),
+        "0".to_string(),
+        mock_env().block.height,
+    );
+    assert!(extract_error_msg(&result).contains("Wrong viewing key"));
+    let querier = querier_for(QueryMsg::Holders {
+        token_id: "0".to_string(),
+        start_after: None,
+        limit: None,
+    })?;
+    let result = contract.holders(QuerierWrapper::new(&querier), "0".to_string(), None, None);
+    assert!(extract_error_msg(&result).contains("holders of token_id 0 are not public"));
+
+    Ok(())
+}
+
 /// note: tested more extensively in integration tests
 #[test]
 fn test_batch_transfer_and_send_sanity() -> StdResult<()> {
@@ -996,6 +3368,7 @@ fn test_batch_transfer_and_send_sanity() -> StdResult<()> {
                 recipient: addr.b(),
                 amount: Uint256::from(10u128),
                 memo: None,
+                decoys: None,
             },
             TransferAction {
                 token_id: "0a".to_string(),
@@ -1003,8 +3376,10 @@ fn test_batch_transfer_and_send_sanity() -> StdResult<()> {
                 recipient: addr.c(),
                 amount: Uint256::from(20u128),
                 memo: None,
+                decoys: None,
             },
         ],
+        entropy: None,
         padding: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg_batch_trans)?;
@@ -1029,6 +3404,8 @@ fn test_batch_transfer_and_send_sanity() -> StdResult<()> {
                 amount: Uint256::from(20u128),
                 msg: Some(to_binary(&"test message to b")?),
                 memo: None,
+                decoys: None,
+                send_mode: None,
             },
             SendAction {
                 token_id: "0a".to_string(),
@@ -1038,8 +3415,11 @@ fn test_batch_transfer_and_send_sanity() -> StdResult<()> {
                 amount: Uint256::from(30u128),
                 msg: Some(to_binary(&"test message to c")?),
                 memo: None,
+                decoys: None,
+                send_mode: None,
             },
         ],
+        entropy: None,
         padding: None,
     };
     let response = execute(deps.as_mut(), mock_env(), info, msg_batch_send)?;
@@ -1108,6 +3488,7 @@ fn test_batch_transfer_and_send_errors() -> StdResult<()> {
                 recipient: addr.b(),
                 amount: Uint256::from(10u128),
                 memo: None,
+                decoys: None,
             },
             TransferAction {
                 token_id: "0a".to_string(),
@@ -1115,8 +3496,10 @@ fn test_batch_transfer_and_send_errors() -> StdResult<()> {
                 recipient: addr.c(),
                 amount: Uint256::from(20u128),
                 memo: None,
+                decoys: None,
             },
         ],
+        entropy: None,
         padding: None,
     };
     let info = mock_info("addr0", &[]);
@@ -1157,6 +3540,8 @@ fn test_transfer_permissions_fungible() -> StdResult<()> {
         recipient: addr1.clone(),
         amount: Uint256::from(10u128),
         memo: None,
+        decoys: None,
+        entropy: None,
         padding: None,
     };
     let mut result = execute(deps.as_mut(), mock_env(), info.clone(), msg_trnsf_0.clone());
@@ -1313,6 +3698,8 @@ fn test_transfer_permissions_nft() -> StdResult<()> {
         recipient: addr0.clone(),
         amount: Uint256::from(1u128),
         memo: None,
+        decoys: None,
+        entropy: None,
         padding: None,
     };
     let mut result = execute(
@@ -1409,6 +3796,8 @@ fn test_transfer_permissions_nft() -> StdResult<()> {
         recipient: addr0.clone(),
         amount: Uint256::from(1u128),
         memo: None,
+        decoys: None,
+        entropy: None,
         padding: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
@@ -1430,6 +3819,8 @@ fn test_transfer_permissions_nft() -> StdResult<()> {
         recipient: addr1.clone(),
         amount: Uint256::from(1u128),
         memo: None,
+        decoys: None,
+        entropy: None,
         padding: None,
     };
     result = execute(deps.as_mut(), mock_env(), info, msg);
@@ -1444,6 +3835,140 @@ fn test_transfer_permissions_nft() -> StdResult<()> {
     Ok(())
 }
 
+#[test]
+fn test_transfer_and_give_permission_with_permit() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let contract = mock_env().contract.address.to_string();
+    let permit_params = |permit_name: &str, payload| PermitParams {
+        allowed_tokens: vec![contract.clone()],
+        permit_name: permit_name.to_string(),
+        chain_id: mock_env().block.chain_id,
+        permissions: vec![payload],
+    };
+    let transfer_payload = |nonce: u64, expiration: Expiration| TransferPermitPayload {
+        token_id: "0".to_string(),
+        recipient: addr.c(),
+        amount: Uint256::from(10u128),
+        memo: None,
+        nonce,
+        expiration,
+    };
+
+    // give tokens to permit signer
+    let (permit, signer) = sign_permit(
+        permit_params("transfer0", transfer_payload(0, Expiration::Never)),
+        &[2u8; 32],
+    )?;
+    let msg = ExecuteMsg::Transfer {
+        token_id: "0".to_string(),
+        from: addr.a(),
+        recipient: signer.clone(),
+        amount: Uint256::from(100u128),
+        memo: None,
+        decoys: None,
+        entropy: None,
+        padding: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.a().as_str(), &[]),
+        msg,
+    )?;
+
+    // success: relayer submits signed transfer
+    let relayer = mock_info(addr.b().as_str(), &[]);
+    let msg = ExecuteMsg::TransferWithPermit {
+        permit,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), relayer.clone(), msg.clone())?;
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &signer),
+        Some(Uint256::from(90u128))
+    );
+    assert_eq!(
+        chk_bal(&deps.storage, "0", &addr.c()),
+        Some(Uint256::from(10u128))
+    );
+
+    // cannot replay signed transfer
+    let result = execute(deps.as_mut(), mock_env(), relayer.clone(), msg);
+    assert!(extract_error_msg(&result).contains("invalid nonce 0, expected nonce 1"));
+
+    // cannot use expired signed transfer
+    let (permit, _) = sign_permit(
+        permit_params(
+            "transfer1",
+            transfer_payload(1, Expiration::AtHeight(mock_env().block.height)),
+        ),
+        &[2u8; 32],
+    )?;
+    let msg = ExecuteMsg::TransferWithPermit {
+        permit,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), relayer.clone(), msg);
+    assert!(extract_error_msg(&result).contains("signed payload has expired"));
+
+    // cannot tamper with a signed payload
+    let (mut permit, _) = sign_permit(
+        permit_params("transfer1", transfer_payload(1, Expiration::Never)),
+        &[2u8; 32],
+    )?;
+    permit.params.permissions[0].recipient = addr.b();
+    let msg = ExecuteMsg::TransferWithPermit {
+        permit,
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), relayer.clone(), msg);
+    assert!(extract_error_msg(&result).contains("Failed to verify signatures"));
+
+    // success: relayer submits signed permission
+    let (permit, _) = sign_permit(
+        PermitParams {
+            allowed_tokens: vec![contract.clone()],
+            permit_name: "approve1".to_string(),
+            chain_id: mock_env().block.chain_id,
+            permissions: vec![GivePermissionPermitPayload {
+                allowed_address: addr.d(),
+                token_id: "0".to_string(),
+                view_balance: Some(true),
+                view_balance_expiry: None,
+                view_private_metadata: None,
+                view_private_metadata_expiry: None,
+                transfer: Some(Uint256::from(50u128)),
+                transfer_expiry: None,
+                nonce: 1,
+                expiration: Expiration::Never,
+            }],
+        },
+        &[2u8; 32],
+    )?;
+    let msg = ExecuteMsg::GivePermissionWithPermit {
+        permit,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), relayer, msg)?;
+    let permission = may_load_any_permission(&deps.storage, &signer, "0", &addr.d())?.unwrap();
+    assert!(permission.view_balance_perm);
+    assert_eq!(permission.trfer_allowance_perm, Uint256::from(50u128));
+
+    // nonce is public
+    let msg = QueryMsg::PermitNonce { address: signer };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::PermitNonce { nonce } => assert_eq!(nonce, 2),
+        _ => panic!("query error"),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_revoke_permission_sanity() -> StdResult<()> {
     //init addresses
@@ -2071,6 +4596,7 @@ fn test_remove_admin() -> StdResult<()> {
             admin,
             curators,
             all_token_ids,
+            ..
         } => {
             assert_eq!(admin, Some(addr.a()));
             assert_eq!(curators, vec![addr.a()]);
@@ -2158,6 +4684,7 @@ fn test_remove_admin() -> StdResult<()> {
             admin,
             curators,
             all_token_ids,
+            ..
         } => {
             assert_eq!(admin, None);
             assert_eq!(curators, vec![addr.a(), addr.b()]);
@@ -2190,6 +4717,9 @@ fn test_instantiate_admin_inputs() -> StdResult<()> {
             lb_pair_address: Addr::unchecked("address"),
             decimals: 18,
         },
+        uri_template: None,
+        auto_curate_template: None,
+        max_batch_queries: None,
     };
 
     instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg)?;
@@ -2210,6 +4740,9 @@ fn test_instantiate_admin_inputs() -> StdResult<()> {
             lb_pair_address: Addr::unchecked("address"),
             decimals: 18,
         },
+        uri_template: None,
+        auto_curate_template: None,
+        max_batch_queries: None,
     };
 
     instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg)?;
@@ -2230,6 +4763,9 @@ fn test_instantiate_admin_inputs() -> StdResult<()> {
             lb_pair_address: Addr::unchecked("address"),
             decimals: 18,
         },
+        uri_template: None,
+        auto_curate_template: None,
+        max_batch_queries: None,
     };
 
     info.sender = addr.a();
@@ -2251,6 +4787,9 @@ fn test_instantiate_admin_inputs() -> StdResult<()> {
             lb_pair_address: Addr::unchecked("address"),
             decimals: 18,
         },
+        uri_template: None,
+        auto_curate_template: None,
+        max_batch_queries: None,
     };
 
     info.sender = addr.a();
@@ -2278,6 +4817,9 @@ fn test_receiver_sanity() -> StdResult<()> {
         amount: Uint256::from(800u128),
         msg: Some(to_binary(&"msg_str")?),
         memo: Some("some memo".to_string()),
+        decoys: None,
+        send_mode: None,
+        entropy: None,
         padding: None,
     };
     let response = execute(deps.as_mut(), mock_env(), info, msg)?;
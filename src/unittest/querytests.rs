@@ -10,7 +10,8 @@ use super::super::{
     state::{expiration::*, permissions::*, state_structs::*, txhistory::*},
 };
 
-use cosmwasm_std::{from_binary, testing::*, Addr, Response, StdResult, Uint256};
+use cosmwasm_std::{from_binary, testing::*, Addr, Deps, Response, StdResult, Uint256};
+use secret_toolkit::permit::PermitParams;
 
 /////////////////////////////////////////////////////////////////////////////////
 // Tests
@@ -34,10 +35,14 @@ fn test_q_init() -> StdResult<()> {
             admin,
             curators,
             all_token_ids,
+            uri_template,
+            send_mode,
         } => {
             assert_eq!(&admin.unwrap(), &addr0);
             assert_eq!(&curators, &vec![addr0.clone()]);
             assert_eq!(&all_token_ids, &vec!["0".to_string()]);
+            assert_eq!(uri_template, None);
+            assert_eq!(send_mode, SendMode::Lenient);
         }
         _ => panic!("query error"),
     }
@@ -86,6 +91,7 @@ fn test_query_tokenid_public_info_sanity() -> StdResult<()> {
             token_id_info,
             total_supply,
             owner,
+            holder_count,
         } => {
             assert!(serde_json::to_string(&token_id_info)
                 .unwrap()
@@ -94,6 +100,7 @@ fn test_query_tokenid_public_info_sanity() -> StdResult<()> {
             assert_eq!(token_id_info.curator, addr.a());
             assert_eq!(total_supply, Some(Uint256::from(1000u128)));
             assert!(owner.is_none());
+            assert!(holder_count.is_none());
         }
         _ => panic!("query error"),
     }
@@ -101,6 +108,97 @@ fn test_query_tokenid_public_info_sanity() -> StdResult<()> {
     Ok(())
 }
 
+#[test]
+fn test_query_tokenid_public_info_uri_template() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+
+    // curate token_id without public metadata
+    let info = mock_info(addr.a().as_str(), &[]);
+    let mut curate = CurateTokenId::default();
+    curate.token_info.token_id = "nometa".to_string();
+    curate.token_info.public_metadata = None;
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate],
+        memo: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+
+    // non-admin, non-curator cannot set uri template
+    let msg = ExecuteMsg::SetUriTemplate {
+        uri_template: Some("https://meta.example/{id}.json".to_string()),
+        padding: None,
+    };
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &[]),
+        msg.clone(),
+    );
+    assert!(extract_error_msg(&result).contains("Only the admin or curators"));
+
+    // template must contain `{id}`
+    let msg_no_id = ExecuteMsg::SetUriTemplate {
+        uri_template: Some("https://meta.example/token.json".to_string()),
+        padding: None,
+    };
+    let result = execute(deps.as_mut(), mock_env(), info.clone(), msg_no_id);
+    assert!(extract_error_msg(&result).contains("must contain the `{id}` placeholder"));
+
+    // curator can set uri template
+    execute(deps.as_mut(), mock_env(), info, msg)?;
+
+    // token_id without metadata uses uri template
+    let msg = QueryMsg::TokenIdPublicInfo {
+        token_id: "nometa".to_string(),
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::TokenIdPublicInfo { token_id_info, .. } => {
+            assert_eq!(
+                token_id_info.public_metadata.unwrap().token_uri,
+                Some("https://meta.example/nometa.json".to_string())
+            );
+        }
+        _ => panic!("query error"),
+    }
+
+    // token_id with its own metadata is unchanged
+    let msg = QueryMsg::TokenIdPublicInfo {
+        token_id: "0".to_string(),
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::TokenIdPublicInfo { token_id_info, .. } => {
+            assert_eq!(
+                token_id_info.public_metadata.unwrap().token_uri,
+                Some("public uri".to_string())
+            );
+        }
+        _ => panic!("query error"),
+    }
+
+    // contract info shows uri template
+    let q_answer = from_binary::<QueryAnswer>(&query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ContractInfo {},
+    )?)?;
+    match q_answer {
+        QueryAnswer::ContractInfo { uri_template, .. } => assert_eq!(
+            uri_template,
+            Some("https://meta.example/{id}.json".to_string())
+        ),
+        _ => panic!("query error"),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_query_registered_code_hash() -> StdResult<()> {
     // init addresses
@@ -343,7 +441,8 @@ fn test_query_all_balance() -> StdResult<()> {
                 address: addr.a(),
                 amount: Uint256::from(100u128),
             }],
-        }],
+        }
+        .into()],
         memo: None,
         padding: None,
     };
@@ -423,6 +522,837 @@ fn test_query_all_balance() -> StdResult<()> {
     Ok(())
 }
 
+#[test]
+fn test_query_with_permit() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    curate_addtl_default(&mut deps, mock_env(), info.clone())?;
+
+    let permit_params =
+        |allowed_tokens: Vec<&str>, permissions: Vec<Snip1155Permission>| PermitParams {
+            allowed_tokens: allowed_tokens.into_iter().map(|i| i.to_string()).collect(),
+            permit_name: "portfolio".to_string(),
+            chain_id: "secret-4".to_string(),
+            permissions,
+        };
+    let contract = mock_env().contract.address.to_string();
+
+    // transfer token_ids 0 and 0a to permit signer
+    let (_, signer) = sign_permit(
+        permit_params(vec![&contract], vec![Snip1155Permission::Owner]),
+        &[1u8; 32],
+    )?;
+    for token_id in ["0", "0a"] {
+        let msg = ExecuteMsg::Transfer {
+            token_id: token_id.to_string(),
+            from: addr.a(),
+            recipient: signer.clone(),
+            amount: Uint256::from(100u128),
+            memo: None,
+            decoys: None,
+            entropy: None,
+            padding: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    }
+    let balance_of = |token_id: &str| QueryWithPermit::Balance {
+        owner: signer.clone(),
+        token_id: token_id.to_string(),
+    };
+    let all_balances = QueryWithPermit::AllBalances {
+        tx_history_page: None,
+        tx_history_page_size: None,
+    };
+
+    // `History` permit cannot query balances, but can query transaction history
+    let (permit, _) = sign_permit(
+        permit_params(vec![&contract], vec![Snip1155Permission::History]),
+        &[1u8; 32],
+    )?;
+    let msg = QueryMsg::WithPermit {
+        permit: permit.clone(),
+        query: balance_of("0"),
+    };
+    let result = query(deps.as_ref(), mock_env(), msg);
+    assert!(extract_error_msg(&result).contains("`Balance` or `Owner` permit required"));
+    let msg = QueryMsg::WithPermit {
+        permit,
+        query: QueryWithPermit::TransactionHistory {
+            page: None,
+            page_size: 10,
+        },
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::TransactionHistory { total, .. } => assert_eq!(total, 2),
+        _ => panic!("query error"),
+    }
+
+    // `Balance` permit restricted to token_id 0 with a `token_ids` permission
+    let (permit, _) = sign_permit(
+        permit_params(
+            vec![&contract],
+            vec![
+                Snip1155Permission::Balance,
+                Snip1155Permission::TokenIds(vec!["0".to_string()]),
+            ],
+        ),
+        &[1u8; 32],
+    )?;
+    let msg = QueryMsg::WithPermit {
+        permit: permit.clone(),
+        query: balance_of("0"),
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::Balance { amount } => assert_eq!(amount, Uint256::from(100u128)),
+        _ => panic!("query error"),
+    }
+    let msg = QueryMsg::WithPermit {
+        permit: permit.clone(),
+        query: balance_of("0a"),
+    };
+    let result = query(deps.as_ref(), mock_env(), msg);
+    assert!(extract_error_msg(&result).contains("does not allow viewing balance of token_id 0a"));
+    let msg = QueryMsg::WithPermit {
+        permit,
+        query: all_balances.clone(),
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::AllBalances(i) => assert_eq!(
+            i,
+            vec![OwnerBalance {
+                token_id: "0".to_string(),
+                amount: Uint256::from(100u128),
+            }]
+        ),
+        _ => panic!("query error"),
+    }
+
+    // `Balance` permit restricted to token_id 0a through `allowed_tokens`
+    let restricted_token = format!("{}/0a", contract);
+    let (permit, _) = sign_permit(
+        permit_params(
+            vec![&contract, &restricted_token],
+            vec![Snip1155Permission::Balance],
+        ),
+        &[1u8; 32],
+    )?;
+    let msg = QueryMsg::WithPermit {
+        permit: permit.clone(),
+        query: balance_of("0"),
+    };
+    let result = query(deps.as_ref(), mock_env(), msg);
+    assert!(extract_error_msg(&result).contains("does not allow viewing balance of token_id 0"));
+    let msg = QueryMsg::WithPermit {
+        permit: permit.clone(),
+        query: QueryWithPermit::PendingRewards {
+            token_id: "0".to_string(),
+        },
+    };
+    let result = query(deps.as_ref(), mock_env(), msg);
+    assert!(extract_error_msg(&result).contains("does not allow viewing balance of token_id 0"));
+    let msg = QueryMsg::WithPermit {
+        permit,
+        query: all_balances,
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::AllBalances(i) => assert_eq!(
+            i,
+            vec![OwnerBalance {
+                token_id: "0a".to_string(),
+                amount: Uint256::from(100u128),
+            }]
+        ),
+        _ => panic!("query error"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_query_scoped_viewing_keys() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    let vks = generate_viewing_keys(&mut deps, mock_env(), info.clone(), vec![addr.a()])?;
+
+    let mut create_key = |label: &str, scopes: Vec<ViewingKeyScope>, expiration| {
+        let msg = ExecuteMsg::CreateScopedViewingKey {
+            label: label.to_string(),
+            scopes,
+            expiration,
+            entropy: "scoped".to_string(),
+            padding: None,
+        };
+        let response = execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+        match from_binary::<ExecuteAnswer>(&response.data.unwrap())? {
+            ExecuteAnswer::CreateScopedViewingKey { key } => Ok(key),
+            _ => panic!("execute error"),
+        }
+    };
+    let expiry_height = mock_env().block.height + 10;
+    let history_key = create_key(
+        "accountant",
+        vec![ViewingKeyScope::History],
+        Some(Expiration::AtHeight(expiry_height)),
+    )?;
+    let balance_key = create_key("tracker", vec![ViewingKeyScope::Balances], None)?;
+
+    // cannot reuse label
+    let result = create_key("tracker", vec![ViewingKeyScope::History], None);
+    assert!(extract_error_msg(&result).contains("empty or already in use"));
+
+    let is_authorized = |deps: Deps, msg: QueryMsg| -> StdResult<bool> {
+        let q_answer = from_binary::<QueryAnswer>(&query(deps, mock_env(), msg)?)?;
+        Ok(!matches!(q_answer, QueryAnswer::ViewingKeyError { .. }))
+    };
+    let history = |key: &str| QueryMsg::TransactionHistory {
+        address: addr.a(),
+        key: key.to_string(),
+        page: None,
+        page_size: 10,
+    };
+    let balance = |key: &str| QueryMsg::Balance {
+        owner: addr.a(),
+        viewer: addr.a(),
+        key: key.to_string(),
+        token_id: "0".to_string(),
+    };
+    let list = |key: &str| QueryMsg::ScopedViewingKeys {
+        address: addr.a(),
+        key: key.to_string(),
+    };
+
+    // scoped keys can only query within their scopes
+    assert!(is_authorized(deps.as_ref(), history(&history_key))?);
+    assert!(!is_authorized(deps.as_ref(), balance(&history_key))?);
+    assert!(is_authorized(deps.as_ref(), balance(&balance_key))?);
+    assert!(!is_authorized(deps.as_ref(), history(&balance_key))?);
+
+    // only the unscoped key can list scoped keys
+    assert!(!is_authorized(deps.as_ref(), list(&history_key))?);
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), list(&vks.a()))?)?;
+    match q_answer {
+        QueryAnswer::ScopedViewingKeys { keys } => {
+            assert_eq!(keys.len(), 2);
+            assert_eq!(keys[0].label, "accountant");
+            assert_eq!(keys[0].expiration, Expiration::AtHeight(expiry_height));
+            assert_eq!(keys[1].scopes, vec![ViewingKeyScope::Balances]);
+        }
+        _ => panic!("query error"),
+    }
+
+    // revoked key cannot be used
+    let msg = ExecuteMsg::RevokeScopedViewingKey {
+        label: "tracker".to_string(),
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    assert!(!is_authorized(deps.as_ref(), balance(&balance_key))?);
+
+    // expired key cannot be used
+    let mut env = mock_env();
+    env.block.height = expiry_height;
+    let msg = ExecuteMsg::SetViewingKey {
+        key: vks.a(),
+        padding: None,
+    };
+    execute(deps.as_mut(), env, info, msg)?;
+    assert!(!is_authorized(deps.as_ref(), history(&history_key))?);
+    assert!(is_authorized(deps.as_ref(), history(&vks.a()))?);
+
+    Ok(())
+}
+
+#[test]
+fn test_query_balance_and_total_supply_at() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate at mock_env height, with 1000 token_id 0 to addr0
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    let vks = generate_viewing_keys(
+        &mut deps,
+        mock_env(),
+        info.clone(),
+        vec![addr.a(), addr.b()],
+    )?;
+    let init_height = mock_env().block.height;
+
+    // mint 100 at init_height + 10, transfer 300 at init_height + 20
+    let mut env = mock_env();
+    env.block.height = init_height + 10;
+    let msg = ExecuteMsg::MintTokens {
+        mint_tokens: vec![TokenAmount {
+            token_id: "0".to_string(),
+            balances: vec![TokenIdBalance {
+                address: addr.a(),
+                amount: Uint256::from(100u128),
+            }],
+        }
+        .into()],
+        memo: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg)?;
+    env.block.height = init_height + 20;
+    let msg = ExecuteMsg::Transfer {
+        token_id: "0".to_string(),
+        from: addr.a(),
+        recipient: addr.b(),
+        amount: Uint256::from(300u128),
+        memo: None,
+        decoys: None,
+        entropy: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), env, info, msg)?;
+
+    let balance_at = |deps: Deps, owner: Addr, height: u64| -> StdResult<Uint256> {
+        let msg = QueryMsg::BalanceAt {
+            owner,
+            viewer: addr.a(),
+            key: vks.a(),
+            token_id: "0".to_string(),
+            height,
+        };
+        match from_binary::<QueryAnswer>(&query(deps, mock_env(), msg)?)? {
+            QueryAnswer::BalanceAt { amount, .. } => Ok(amount),
+            _ => panic!("query error"),
+        }
+    };
+    let total_supply_at = |deps: Deps, height: u64| -> StdResult<Uint256> {
+        let msg = QueryMsg::TotalSupplyAt {
+            token_id: "0".to_string(),
+            height,
+        };
+        match from_binary::<QueryAnswer>(&query(deps, mock_env(), msg)?)? {
+            QueryAnswer::TotalSupplyAt { amount, .. } => Ok(amount),
+            _ => panic!("query error"),
+        }
+    };
+    let expected = [
+        (init_height - 1, 0u128, 0u128),
+        (init_height, 1000, 1000),
+        (init_height + 9, 1000, 1000),
+        (init_height + 10, 1100, 1100),
+        (init_height + 20, 800, 1100),
+        (init_height + 100, 800, 1100),
+    ];
+    for (height, balance, supply) in expected {
+        assert_eq!(
+            balance_at(deps.as_ref(), addr.a(), height)?,
+            Uint256::from(balance)
+        );
+        assert_eq!(
+            total_supply_at(deps.as_ref(), height)?,
+            Uint256::from(supply)
+        );
+    }
+
+    // viewer without permission cannot view another owner's past balance
+    let result = balance_at(deps.as_ref(), addr.b(), init_height + 20);
+    assert!(extract_error_msg(&result).contains("you do have have permission to view balance"));
+    let msg = QueryMsg::BalanceAt {
+        owner: addr.b(),
+        viewer: addr.b(),
+        key: vks.b(),
+        token_id: "0".to_string(),
+        height: init_height + 20,
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::BalanceAt { amount, .. } => assert_eq!(amount, Uint256::from(300u128)),
+        _ => panic!("query error"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_query_time_weighted_balance() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate at mock_env time, with 1000 token_id 0 to addr0
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    let vks = generate_viewing_keys(&mut deps, mock_env(), info.clone(), vec![addr.a()])?;
+    let init_time = mock_env().block.time.seconds();
+
+    // transfer 400 to addr1 100 seconds later
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let msg = ExecuteMsg::Transfer {
+        token_id: "0".to_string(),
+        from: addr.a(),
+        recipient: addr.b(),
+        amount: Uint256::from(400u128),
+        memo: None,
+        decoys: None,
+        entropy: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), env, info, msg)?;
+
+    let time_weighted_balance = |deps: Deps, start: u64, end: u64| {
+        let msg = QueryMsg::TimeWeightedBalance {
+            owner: addr.a(),
+            viewer: addr.a(),
+            key: vks.a(),
+            token_id: "0".to_string(),
+            start,
+            end,
+        };
+        match from_binary::<QueryAnswer>(&query(deps, mock_env(), msg)?)? {
+            QueryAnswer::TimeWeightedBalance { accumulated, .. } => Ok(accumulated),
+            _ => panic!("query error"),
+        }
+    };
+    assert_eq!(
+        time_weighted_balance(deps.as_ref(), init_time, init_time + 200)?,
+        Uint256::from(1000u128 * 100 + 600 * 100)
+    );
+    assert_eq!(
+        time_weighted_balance(deps.as_ref(), init_time + 50, init_time + 150)?,
+        Uint256::from(1000u128 * 50 + 600 * 50)
+    );
+    assert_eq!(
+        time_weighted_balance(deps.as_ref(), init_time - 100, init_time)?,
+        Uint256::zero()
+    );
+    let result = time_weighted_balance(deps.as_ref(), init_time + 1, init_time);
+    assert!(extract_error_msg(&result).contains("start time must not be after end time"));
+
+    // transfers do not change the total supply
+    let msg = QueryMsg::TimeWeightedTotalSupply {
+        token_id: "0".to_string(),
+        start: init_time,
+        end: init_time + 200,
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::TimeWeightedTotalSupply { accumulated, .. } => {
+            assert_eq!(accumulated, Uint256::from(1000u128 * 200))
+        }
+        _ => panic!("query error"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_query_holders() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+
+    // curate token_id with public holders
+    let mut curate = CurateTokenId::default();
+    curate.token_info.token_id = "holders".to_string();
+    let mut flat_config = curate.token_info.token_config.flatten();
+    flat_config.public_holders = true;
+    curate.token_info.token_config = flat_config.to_enum();
+    curate.balances = vec![
+        TokenIdBalance {
+            address: addr.a(),
+            amount: Uint256::from(100u128),
+        }
+        .into(),
+        TokenIdBalance {
+            address: addr.b(),
+            amount: Uint256::from(50u128),
+        }
+        .into(),
+    ];
+    let msg = ExecuteMsg::CurateTokenIds {
+        initial_tokens: vec![curate],
+        memo: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg)?;
+
+    // addr1 transfers its whole balance to addr2, so is no longer a holder
+    let msg = ExecuteMsg::Transfer {
+        token_id: "holders".to_string(),
+        from: addr.b(),
+        recipient: addr.c(),
+        amount: Uint256::from(50u128),
+        memo: None,
+        decoys: None,
+        entropy: None,
+        padding: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.b().as_str(), &[]),
+        msg,
+    )?;
+
+    let holders = |start_after: Option<Addr>, limit: Option<u32>| QueryMsg::Holders {
+        token_id: "holders".to_string(),
+        start_after,
+        limit,
+    };
+    let q_answer =
+        from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), holders(None, None))?)?;
+    match q_answer {
+        QueryAnswer::Holders {
+            holders,
+            holder_count,
+            next_start_after,
+        } => {
+            assert_eq!(holder_count, 2);
+            assert_eq!(next_start_after, None);
+            assert_eq!(
+                holders,
+                vec![
+                    TokenIdBalance {
+                        address: addr.a(),
+                        amount: Uint256::from(100u128),
+                    },
+                    TokenIdBalance {
+                        address: addr.c(),
+                        amount: Uint256::from(50u128),
+                    },
+                ]
+            );
+        }
+        _ => panic!("query error"),
+    }
+
+    // paginated: `limit` counts the entries read, so the entry of former holder addr1 fills a
+    // page without holders, and the cursor moves past it
+    let msg = holders(Some(addr.a()), Some(1));
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::Holders {
+            holders,
+            next_start_after,
+            ..
+        } => {
+            assert!(holders.is_empty());
+            assert_eq!(next_start_after, Some(addr.b()));
+        }
+        _ => panic!("query error"),
+    }
+
+    // pagination can continue after an address that is no longer a holder
+    let msg = holders(Some(addr.b()), None);
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::Holders { holders, .. } => {
+            assert_eq!(holders.len(), 1);
+            assert_eq!(holders[0].address, addr.c());
+        }
+        _ => panic!("query error"),
+    }
+    let result = query(deps.as_ref(), mock_env(), holders(Some(addr.d()), None));
+    assert!(extract_error_msg(&result).contains("has never held token_id holders"));
+
+    // a decoy is indexed like a new holder, but is not listed or counted
+    let msg = ExecuteMsg::Transfer {
+        token_id: "holders".to_string(),
+        from: addr.a(),
+        recipient: addr.c(),
+        amount: Uint256::from(10u128),
+        memo: None,
+        decoys: Some(vec![addr.d()]),
+        entropy: None,
+        padding: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.a().as_str(), &[]),
+        msg,
+    )?;
+    let msg = holders(Some(addr.d()), None);
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::Holders { holders, .. } => assert!(holders.is_empty()),
+        _ => panic!("query error"),
+    }
+
+    // the contract, which holds the balances of swap offers in escrow, is not a holder
+    let msg = ExecuteMsg::CreateSwapOffer {
+        give: vec![TokenAmount {
+            token_id: "holders".to_string(),
+            balances: vec![TokenIdBalance {
+                address: addr.a(),
+                amount: Uint256::from(10u128),
+            }],
+        }],
+        want: vec![TokenAmount {
+            token_id: "0".to_string(),
+            balances: vec![TokenIdBalance {
+                address: addr.a(),
+                amount: Uint256::from(1u128),
+            }],
+        }],
+        counterparty: None,
+        expiry: None,
+        public: None,
+        padding: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr.a().as_str(), &[]),
+        msg,
+    )?;
+    let q_answer =
+        from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), holders(None, None))?)?;
+    match q_answer {
+        QueryAnswer::Holders {
+            holders,
+            holder_count,
+            ..
+        } => {
+            assert_eq!(holder_count, 2);
+            let addresses: Vec<Addr> = holders.into_iter().map(|h| h.address).collect();
+            assert_eq!(addresses, vec![addr.a(), addr.c()]);
+        }
+        _ => panic!("query error"),
+    }
+
+    // holder count is in public info
+    let msg = QueryMsg::TokenIdPublicInfo {
+        token_id: "holders".to_string(),
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::TokenIdPublicInfo { holder_count, .. } => assert_eq!(holder_count, Some(2)),
+        _ => panic!("query error"),
+    }
+
+    // holders of token_ids without `public_holders` cannot be listed
+    let msg = QueryMsg::Holders {
+        token_id: "0".to_string(),
+        start_after: None,
+        limit: None,
+    };
+    let result = query(deps.as_ref(), mock_env(), msg);
+    assert!(extract_error_msg(&result).contains("holders of token_id 0 are not public"));
+
+    Ok(())
+}
+
+#[test]
+fn test_query_balance_of_batch() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    curate_addtl_default(&mut deps, mock_env(), info.clone())?;
+    let vks = generate_viewing_keys(
+        &mut deps,
+        mock_env(),
+        info.clone(),
+        vec![addr.a(), addr.b(), addr.c()],
+    )?;
+
+    // addr0 gives addr2 permission to view its balance of token_id 0 only
+    let msg = ExecuteMsg::GivePermission {
+        allowed_address: addr.c(),
+        token_id: "0".to_string(),
+        view_balance: Some(true),
+        view_balance_expiry: None,
+        view_private_metadata: None,
+        view_private_metadata_expiry: None,
+        transfer: None,
+        transfer_expiry: None,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg)?;
+
+    let msg = QueryMsg::BalanceOfBatch {
+        viewer: addr.c(),
+        key: vks.c(),
+        owners: vec![addr.a(), addr.b(), addr.c()],
+        token_ids: vec!["0".to_string(), "0a".to_string()],
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    match q_answer {
+        QueryAnswer::BalanceOfBatch { balances } => {
+            let amounts: Vec<Option<Uint256>> = balances.iter().map(|i| i.amount).collect();
+            assert_eq!(
+                amounts,
+                vec![
+                    Some(Uint256::from(1000u128)),
+                    None,
+                    None,
+                    None,
+                    Some(Uint256::zero()),
+                    Some(Uint256::zero()),
+                ]
+            );
+            assert_eq!(balances[1].owner, addr.a());
+            assert_eq!(balances[1].token_id, "0a");
+        }
+        _ => panic!("query error"),
+    }
+
+    // the number of (owner, token_id) pairs is limited
+    let msg = QueryMsg::BalanceOfBatch {
+        viewer: addr.c(),
+        key: vks.c(),
+        owners: vec![addr.a(); 11],
+        token_ids: vec!["0".to_string(); 10],
+    };
+    let result = query(deps.as_ref(), mock_env(), msg);
+    assert!(extract_error_msg(&result).contains("at most 100 (owner, token_id) pairs"));
+
+    Ok(())
+}
+
+#[test]
+fn test_query_batch() -> StdResult<()> {
+    // init addresses
+    let addr = init_addrs();
+
+    // instantiate
+    let (_init_result, mut deps) = init_helper_default();
+    let info = mock_info(addr.a().as_str(), &[]);
+    let vks = generate_viewing_keys(&mut deps, mock_env(), info.clone(), vec![addr.a()])?;
+
+    let queries = vec![
+        BatchQuery::TokenIdPublicInfo {
+            token_id: "0".to_string(),
+        },
+        BatchQuery::Balance {
+            owner: addr.a(),
+            token_id: "0".to_string(),
+        },
+        BatchQuery::TransactionHistory {
+            page: None,
+            page_size: 10,
+        },
+        BatchQuery::TokenIdPublicInfo {
+            token_id: "nonexistent".to_string(),
+        },
+    ];
+    let batch = |deps: Deps, auth: Option<BatchAuth>, queries: Vec<BatchQuery>| {
+        let msg = QueryMsg::Batch { auth, queries };
+        match from_binary::<QueryAnswer>(&query(deps, mock_env(), msg)?)? {
+            QueryAnswer::Batch { results } => Ok(results),
+            _ => panic!("query error"),
+        }
+    };
+    let is_ok = |result: &BatchQueryResult| matches!(result, BatchQueryResult::Ok { .. });
+
+    // authenticated once with a viewing key; a failing sub-query does not fail the batch
+    let auth = BatchAuth::ViewingKey {
+        address: addr.a(),
+        key: vks.a(),
+    };
+    let results = batch(deps.as_ref(), Some(auth), queries.clone())?;
+    assert_eq!(results.len(), 4);
+    match &results[1] {
+        BatchQueryResult::Ok { answer } => match answer.as_ref() {
+            QueryAnswer::Balance { amount } => assert_eq!(amount, &Uint256::from(1000u128)),
+            _ => panic!("query error"),
+        },
+        _ => panic!("query error"),
+    }
+    assert!(is_ok(&results[0]) && is_ok(&results[2]) && !is_ok(&results[3]));
+
+    // without auth, only public sub-queries succeed
+    let results = batch(deps.as_ref(), None, queries.clone())?;
+    assert!(is_ok(&results[0]) && !is_ok(&results[1]) && !is_ok(&results[2]));
+
+    // wrong viewing key fails the batch
+    let auth = BatchAuth::ViewingKey {
+        address: addr.a(),
+        key: "wrong key".to_string(),
+    };
+    let msg = QueryMsg::Batch {
+        auth: Some(auth),
+        queries: queries.clone(),
+    };
+    let q_answer = from_binary::<QueryAnswer>(&query(deps.as_ref(), mock_env(), msg)?)?;
+    assert!(matches!(q_answer, QueryAnswer::ViewingKeyError { .. }));
+
+    // scoped viewing key can only perform sub-queries within its scopes
+    let msg = ExecuteMsg::CreateScopedViewingKey {
+        label: "tracker".to_string(),
+        scopes: vec![ViewingKeyScope::Balances],
+        expiration: None,
+        entropy: "scoped".to_string(),
+        padding: None,
+    };
+    let response = execute(deps.as_mut(), mock_env(), info.clone(), msg)?;
+    let key = match from_binary::<ExecuteAnswer>(&response.data.unwrap())? {
+        ExecuteAnswer::CreateScopedViewingKey { key } => key,
+        _ => panic!("execute error"),
+    };
+    let auth = BatchAuth::ViewingKey {
+        address: addr.a(),
+        key,
+    };
+    let results = batch(deps.as_ref(), Some(auth), queries.clone())?;
+    assert!(is_ok(&results[1]));
+    match &results[2] {
+        BatchQueryResult::Err { msg } => assert!(msg.contains("does not have the `History` scope")),
+        _ => panic!("query error"),
+    }
+
+    // authenticated once with a permit
+    let (permit, signer) = sign_permit(
+        PermitParams {
+            allowed_tokens: vec![mock_env().contract.address.to_string()],
+            permit_name: "batch".to_string(),
+            chain_id: "secret-4".to_string(),
+            permissions: vec![Snip1155Permission::Balance],
+        },
+        &[1u8; 32],
+    )?;
+    let queries = vec![
+        BatchQuery::Balance {
+            owner: signer,
+            token_id: "0".to_string(),
+        },
+        BatchQuery::TransactionHistory {
+            page: None,
+            page_size: 10,
+        },
+    ];
+    let results = batch(
+        deps.as_ref(),
+        Some(BatchAuth::Permit(Box::new(permit))),
+        queries.clone(),
+    )?;
+    assert!(is_ok(&results[0]) && !is_ok(&results[1]));
+
+    // cannot exceed the maximum number of sub-queries
+    let msg = ExecuteMsg::SetMaxBatchQueries {
+        max_batch_queries: 1,
+        padding: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg)?;
+    let result = batch(deps.as_ref(), None, queries);
+    assert!(extract_error_msg(&result).contains("at most 1 queries"));
+
+    Ok(())
+}
+
 #[test]
 fn test_query_transaction_history() -> StdResult<()> {
     // init addresses
@@ -523,6 +1453,8 @@ fn test_query_transaction_history() -> StdResult<()> {
         recipient: addr.b(),
         amount: Uint256::from(10u128),
         memo: None,
+        decoys: None,
+        entropy: None,
         padding: None,
     };
     info.sender = addr.a();
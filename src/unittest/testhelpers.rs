@@ -1,13 +1,17 @@
 use cosmwasm_storage::ReadonlyPrefixedStorage;
-use secret_toolkit::viewing_key::{ViewingKey, ViewingKeyStore};
+use secret_toolkit::{
+    crypto::secp256k1::PrivateKey,
+    permit::{validate, Permissions, Permit, PermitParams, PermitSignature, PubKey, SignedPermit},
+    viewing_key::{ViewingKey, ViewingKeyStore},
+};
 use serde::de::DeserializeOwned;
 use std::any::Any;
 
 use super::super::{handles::*, msg::*, state::state_structs::*, state::*};
 
 use cosmwasm_std::{
-    from_binary, testing::*, to_binary, Addr, CosmosMsg, Env, MessageInfo, OwnedDeps, Response,
-    StdError, StdResult, Storage, Uint256, WasmMsg,
+    from_binary, testing::*, to_binary, Addr, Api, Binary, CosmosMsg, Env, MessageInfo, OwnedDeps,
+    Response, StdError, StdResult, Storage, Uint256, WasmMsg,
 };
 
 /////////////////////////////////////////////////////////////////////////////////
@@ -82,11 +86,14 @@ pub fn init_helper_default() -> (
         initial_tokens: vec![CurateTokenId::default()],
         entropy: "seedentropy".to_string(),
         lb_pair_info: LbPair {
-            name: String::new(),
-            symbol: String::new(),
+            name: "lbpair".to_string(),
+            symbol: "LBP".to_string(),
             lb_pair_address: Addr::unchecked("address"),
             decimals: 18,
         },
+        uri_template: None,
+        auto_curate_template: None,
+        max_batch_queries: None,
     };
 
     (instantiate(deps.as_mut(), env, info, init_msg), deps)
@@ -132,7 +139,8 @@ pub fn curate_addtl_default(
     curate2.balances = vec![TokenIdBalance {
         address: addr2.clone(),
         amount: Uint256::from(1u128),
-    }];
+    }
+    .into()];
 
     // NFT "2a"
     let mut curate2a = CurateTokenId::default();
@@ -143,7 +151,8 @@ pub fn curate_addtl_default(
     curate2a.balances = vec![TokenIdBalance {
         address: addr2,
         amount: Uint256::from(1u128),
-    }];
+    }
+    .into()];
 
     // batch curate token_id "0a", "1", NFT "2" and NFT "3"
     let msg = ExecuteMsg::CurateTokenIds {
@@ -212,6 +221,43 @@ pub fn extract_cosmos_msg<U: DeserializeOwned>(
     ))
 }
 
+/// signs permit params with a private key. Returns the permit and the address of the signer
+pub fn sign_permit<P: Permissions>(
+    params: PermitParams<P>,
+    private_key: &[u8; 32],
+) -> StdResult<(Permit<P>, Addr)> {
+    let api = MockApi::default();
+    let signed_bytes = to_binary(&SignedPermit::from_params(&params))?;
+    let signature = api
+        .secp256k1_sign(signed_bytes.as_slice(), private_key)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    let pub_key = PrivateKey::parse(private_key)?
+        .pubkey()
+        .serialize_compressed();
+    let permit = Permit {
+        params,
+        signature: PermitSignature {
+            pub_key: PubKey {
+                r#type: "tendermint/PubKeySecp256k1".to_string(),
+                value: Binary::from(pub_key.to_vec()),
+            },
+            signature: Binary::from(signature),
+        },
+    };
+
+    // derive the signer's address
+    let deps = mock_dependencies();
+    let account = validate(
+        deps.as_ref(),
+        "sign_permit",
+        &permit,
+        permit.params.allowed_tokens[0].clone(),
+        None,
+    )?;
+
+    Ok((permit, Addr::unchecked(account)))
+}
+
 /// generates an array of viewing keys (as Strings)
 pub fn generate_viewing_keys(
     deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,